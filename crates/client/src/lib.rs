@@ -0,0 +1,391 @@
+#![forbid(unsafe_code)]
+
+//! Embedding facade for Rust programs that want `branch` / `think` / `merge` without MCP.
+//!
+//! Only the items exported here are covered by semver; `bm_storage` request structs and
+//! `StoreError` variants may change between releases without a major bump of this crate.
+
+use bm_core::canonical_identifier;
+use bm_core::ids::WorkspaceId;
+use bm_storage::{
+    AppendCommitRequest, CreateBranchRequest, CreateMergeRecordRequest, DeleteBranchRequest,
+    ListBranchesRequest, ShowCommitRequest, SqliteStore, StoreConfig, StoreError,
+};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub use bm_core::{MergeRecord, ThoughtBranch, ThoughtCommit};
+
+/// Counter behind generated merge ids, see `SqliteStore::create_merge_record_numbered`.
+const MERGE_COUNTER: &str = "client.merge";
+
+/// Failure of a client call: the stable error code shared with the MCP surface, a message and,
+/// when one applies, what to do about it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClientError {
+    pub code: &'static str,
+    pub message: String,
+    pub recovery: Option<&'static str>,
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.code, self.message)
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<StoreError> for ClientError {
+    fn from(err: StoreError) -> Self {
+        Self {
+            code: err.code(),
+            recovery: err.recovery_hint(),
+            message: err.to_string(),
+        }
+    }
+}
+
+/// Store limits for `Client::open_with_config`. Start from `default()` or `from_env()` and
+/// adjust with the setters; values are validated when the client opens.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ClientConfig {
+    store: StoreConfig,
+}
+
+impl ClientConfig {
+    /// Defaults, with SQL tracing switched on when `BM_TRACE_SQL=1`.
+    pub fn from_env() -> Self {
+        Self {
+            store: StoreConfig::from_env(),
+        }
+    }
+
+    /// How long SQLite waits on a locked database before a write attempt is retried.
+    pub fn busy_timeout(mut self, timeout: Duration) -> Self {
+        self.store.busy_timeout = timeout;
+        self
+    }
+
+    /// Retries of a contended write before the call fails with `BUSY`.
+    pub fn busy_retries(mut self, retries: u32) -> Self {
+        self.store.busy_retries = retries;
+        self
+    }
+
+    /// Longest allowed parent chain below a root branch.
+    pub fn max_branch_depth(mut self, depth: usize) -> Self {
+        self.store.max_branch_depth = depth;
+        self
+    }
+
+    /// Upper bound applied to `limit` of branch listings.
+    pub fn max_page_limit(mut self, limit: usize) -> Self {
+        self.store.max_page_limit = limit;
+        self
+    }
+
+    /// Upper bound applied to `limit` of `Client::think_log`.
+    pub fn max_log_limit(mut self, limit: usize) -> Self {
+        self.store.max_log_limit = limit;
+        self
+    }
+
+    /// Log every statement with parameter types and sizes to stderr.
+    pub fn trace_sql(mut self, enabled: bool) -> Self {
+        self.store.trace_sql = enabled;
+        self
+    }
+}
+
+/// Arguments of `Client::think_commit`, mirroring `think commit`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Thought {
+    pub branch: String,
+    pub commit_id: String,
+    pub message: String,
+    pub body: String,
+    pub author: Option<String>,
+    /// Refuse the write unless the branch head is still this commit.
+    pub if_head: Option<String>,
+}
+
+impl Thought {
+    pub fn new(
+        branch: impl Into<String>,
+        commit_id: impl Into<String>,
+        message: impl Into<String>,
+        body: impl Into<String>,
+    ) -> Self {
+        Self {
+            branch: branch.into(),
+            commit_id: commit_id.into(),
+            message: message.into(),
+            body: body.into(),
+            author: None,
+            if_head: None,
+        }
+    }
+
+    pub fn with_author(mut self, author: impl Into<String>) -> Self {
+        self.author = Some(author.into());
+        self
+    }
+
+    pub fn with_if_head(mut self, commit_id: impl Into<String>) -> Self {
+        self.if_head = Some(commit_id.into());
+        self
+    }
+}
+
+/// Arguments of `Client::merge_into`, mirroring `merge into` with a single source.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Merge {
+    pub from: String,
+    pub target: String,
+    /// Defaults to `squash`.
+    pub strategy: Option<String>,
+    /// Defaults to `merge <from> into <target>`.
+    pub summary: Option<String>,
+    /// Synthesis commit message; defaults to the summary.
+    pub message: Option<String>,
+    /// Synthesis commit body; defaults to the summary.
+    pub body: Option<String>,
+    pub author: Option<String>,
+}
+
+impl Merge {
+    pub fn new(from: impl Into<String>, target: impl Into<String>) -> Self {
+        Self {
+            from: from.into(),
+            target: target.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_strategy(mut self, strategy: impl Into<String>) -> Self {
+        self.strategy = Some(strategy.into());
+        self
+    }
+
+    pub fn with_summary(mut self, summary: impl Into<String>) -> Self {
+        self.summary = Some(summary.into());
+        self
+    }
+
+    pub fn with_message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+
+    pub fn with_body(mut self, body: impl Into<String>) -> Self {
+        self.body = Some(body.into());
+        self
+    }
+
+    pub fn with_author(mut self, author: impl Into<String>) -> Self {
+        self.author = Some(author.into());
+        self
+    }
+}
+
+/// A store handle bound to one workspace.
+pub struct Client {
+    store: SqliteStore,
+    workspace: WorkspaceId,
+}
+
+impl Client {
+    pub fn open(storage_dir: impl AsRef<Path>, workspace: &str) -> Result<Self, ClientError> {
+        Self::open_with_config(storage_dir, workspace, ClientConfig::from_env())
+    }
+
+    pub fn open_with_config(
+        storage_dir: impl AsRef<Path>,
+        workspace: &str,
+        config: ClientConfig,
+    ) -> Result<Self, ClientError> {
+        let workspace = WorkspaceId::try_new(workspace).map_err(|_| ClientError {
+            code: "INVALID_INPUT",
+            message: "invalid workspace".to_string(),
+            recovery: None,
+        })?;
+        Ok(Self {
+            store: SqliteStore::open_with_config(storage_dir, config.store)?,
+            workspace,
+        })
+    }
+
+    pub fn workspace(&self) -> &str {
+        self.workspace.as_str()
+    }
+
+    /// Creates a branch, forked from `from` when given.
+    pub fn branch_create(
+        &mut self,
+        branch: &str,
+        from: Option<&str>,
+    ) -> Result<ThoughtBranch, ClientError> {
+        Ok(self.store.create_branch(CreateBranchRequest {
+            workspace_id: self.workspace().to_string(),
+            branch_id: branch.to_string(),
+            parent_branch_id: from.map(ToOwned::to_owned),
+            created_at_ms: now_ms(),
+        })?)
+    }
+
+    /// Lists active branches, oldest first. `limit` is capped by `ClientConfig::max_page_limit`.
+    pub fn branch_list(
+        &self,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<ThoughtBranch>, ClientError> {
+        Ok(self.store.list_branches(ListBranchesRequest {
+            workspace_id: self.workspace().to_string(),
+            limit,
+            offset,
+        })?)
+    }
+
+    pub fn branch_delete(&mut self, branch: &str) -> Result<(), ClientError> {
+        Ok(self.store.delete_branch(DeleteBranchRequest {
+            workspace_id: self.workspace().to_string(),
+            branch_id: branch.to_string(),
+        })?)
+    }
+
+    /// Makes `branch` the current branch and returns the previous one.
+    pub fn branch_checkout(&mut self, branch: &str) -> Result<Option<String>, ClientError> {
+        let (previous, _) = self.store.branch_checkout_set(&self.workspace, branch)?;
+        Ok(previous)
+    }
+
+    pub fn current_branch(&self) -> Result<Option<String>, ClientError> {
+        Ok(self.store.branch_checkout_get(&self.workspace)?)
+    }
+
+    /// Appends a commit on top of the branch head.
+    pub fn think_commit(&mut self, thought: Thought) -> Result<ThoughtCommit, ClientError> {
+        Ok(self.store.append_commit(AppendCommitRequest {
+            workspace_id: self.workspace().to_string(),
+            branch_id: thought.branch,
+            commit_id: thought.commit_id,
+            parent_commit_id: None,
+            expected_head_commit_id: thought.if_head,
+            message: thought.message,
+            body: thought.body,
+            author: thought.author,
+            created_at_ms: now_ms(),
+        })?)
+    }
+
+    pub fn think_show(&self, commit_id: &str) -> Result<Option<ThoughtCommit>, ClientError> {
+        Ok(self.store.show_commit(ShowCommitRequest {
+            workspace_id: self.workspace().to_string(),
+            commit_id: commit_id.to_string(),
+        })?)
+    }
+
+    /// Walks a branch from its head towards the root, newest first; archived branches are
+    /// readable too. `limit` is capped by `ClientConfig::max_log_limit`.
+    pub fn think_log(&self, branch: &str, limit: usize) -> Result<Vec<ThoughtCommit>, ClientError> {
+        let limit = limit.min(self.store.config().max_log_limit);
+        let mut cursor = self.branch_head(branch)?;
+        let mut seen = BTreeSet::new();
+        let mut out = Vec::new();
+        while let Some(commit_id) = cursor {
+            if out.len() >= limit {
+                break;
+            }
+            if !seen.insert(commit_id.clone()) {
+                return Err(ClientError {
+                    code: "INTERNAL",
+                    message: "commit history loop detected".to_string(),
+                    recovery: Some("run integrity_check on the workspace"),
+                });
+            }
+            let commit = self.think_show(&commit_id)?.ok_or(StoreError::UnknownId)?;
+            cursor = commit.parent_commit_id().map(ToOwned::to_owned);
+            out.push(commit);
+        }
+        Ok(out)
+    }
+
+    /// Merges one branch into another through a synthesis commit on the target. The merge id
+    /// is numbered from a workspace counter allocated in the same transaction.
+    pub fn merge_into(&mut self, merge: Merge) -> Result<MergeRecord, ClientError> {
+        let workspace_id = self.workspace().to_string();
+        let summary = merge
+            .summary
+            .unwrap_or_else(|| format!("merge {} into {}", merge.from, merge.target));
+        let created_at_ms = now_ms();
+        Ok(self
+            .store
+            .create_merge_record_numbered(&workspace_id, MERGE_COUNTER, |seq| {
+                CreateMergeRecordRequest {
+                    workspace_id: workspace_id.clone(),
+                    merge_id: format!("merge-client-{seq}"),
+                    source_branch_id: merge.from,
+                    target_branch_id: merge.target,
+                    strategy: merge.strategy.unwrap_or_else(|| "squash".to_string()),
+                    synthesis_commit_id: format!("c-merge-client-{seq}"),
+                    synthesis_message: merge.message.unwrap_or_else(|| summary.clone()),
+                    synthesis_body: merge.body.unwrap_or_else(|| summary.clone()),
+                    summary,
+                    author: merge.author,
+                    created_at_ms,
+                }
+            })?)
+    }
+
+    /// Head of an active or archived branch.
+    fn branch_head(&self, branch: &str) -> Result<Option<String>, ClientError> {
+        let branch = canonical_identifier("branch_id", branch)
+            .map_err(|_| StoreError::InvalidInput("invalid branch_id"))?;
+        for archived in [false, true] {
+            if let Some(head) = self.find_branch_head(&branch, archived)? {
+                return Ok(head);
+            }
+        }
+        Err(StoreError::UnknownBranch.into())
+    }
+
+    fn find_branch_head(
+        &self,
+        branch: &str,
+        archived: bool,
+    ) -> Result<Option<Option<String>>, ClientError> {
+        let page = self.store.config().max_page_limit;
+        let mut offset = 0;
+        loop {
+            let request = ListBranchesRequest {
+                workspace_id: self.workspace().to_string(),
+                limit: page,
+                offset,
+            };
+            let branches = if archived {
+                self.store.list_archived_branches(request)?
+            } else {
+                self.store.list_branches(request)?
+            };
+            if let Some(found) = branches.iter().find(|b| b.branch_id() == branch) {
+                return Ok(Some(found.head_commit_id().map(ToOwned::to_owned)));
+            }
+            if branches.len() < page {
+                return Ok(None);
+            }
+            offset += page;
+        }
+    }
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| i64::try_from(d.as_millis()).unwrap_or(i64::MAX))
+        .unwrap_or(0)
+}
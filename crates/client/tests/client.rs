@@ -0,0 +1,133 @@
+use bm_client::{Client, ClientConfig, Merge, Thought};
+use bm_storage::{ArchiveBranchRequest, SqliteStore};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn temp_storage_dir(label: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("clock should be monotonic enough for tests")
+        .as_nanos();
+    path.push(format!("bm-client-{label}-{}-{nanos}", std::process::id()));
+    std::fs::create_dir_all(&path).expect("temp storage dir must be creatable");
+    path
+}
+
+fn thought(branch: &str, commit_id: &str) -> Thought {
+    Thought::new(
+        branch,
+        commit_id,
+        format!("msg {commit_id}"),
+        format!("body {commit_id}"),
+    )
+}
+
+#[test]
+fn client_drives_branch_think_and_merge() {
+    let dir = temp_storage_dir("roundtrip");
+    let mut client = Client::open(&dir, "WS-Embed").expect("client should open");
+    assert_eq!(client.workspace(), "ws-embed");
+
+    client
+        .branch_create("main", None)
+        .expect("main should be created");
+    client
+        .think_commit(thought("main", "m1"))
+        .expect("commit should append");
+    client
+        .branch_create("idea", Some("main"))
+        .expect("idea should be created");
+    client
+        .branch_checkout("idea")
+        .expect("checkout should succeed");
+    assert_eq!(
+        client.current_branch().expect("checkout should load"),
+        Some("idea".to_string())
+    );
+
+    let merge = client
+        .merge_into(Merge::new("idea", "main"))
+        .expect("merge should succeed");
+    assert_eq!(merge.merge_id(), "merge-client-1");
+    assert_eq!(merge.summary(), "merge idea into main");
+
+    let log = client.think_log("Main", 10).expect("log should load");
+    let ids = log.iter().map(|c| c.commit_id()).collect::<Vec<_>>();
+    assert_eq!(ids, vec!["c-merge-client-1", "m1"]);
+    assert_eq!(
+        client
+            .think_show("m1")
+            .expect("show should load")
+            .map(|c| c.body().to_string()),
+        Some("body m1".to_string())
+    );
+    assert_eq!(
+        client.branch_list(10, 0).expect("list should load").len(),
+        2
+    );
+}
+
+#[test]
+fn client_errors_carry_stable_codes() {
+    let dir = temp_storage_dir("errors");
+    let mut client = Client::open(&dir, "ws-embed").expect("client should open");
+    client
+        .branch_create("main", None)
+        .expect("main should be created");
+
+    let err = client
+        .branch_create("main", None)
+        .expect_err("duplicate branch must fail");
+    assert_eq!(err.code, "ALREADY_EXISTS");
+    assert!(err.recovery.is_some());
+
+    let err = client
+        .think_log("missing", 10)
+        .expect_err("unknown branch must fail");
+    assert_eq!(err.code, "NOT_FOUND");
+
+    let err = client
+        .think_commit(thought("main", "m2").with_if_head("m0"))
+        .expect_err("stale head must be rejected");
+    assert_eq!(err.code, "HEAD_MISMATCH");
+
+    assert!(Client::open(&dir, "-bad").is_err());
+}
+
+#[test]
+fn client_config_applies_limits_and_log_reads_archived_branches() {
+    let dir = temp_storage_dir("archived");
+    let mut client =
+        Client::open_with_config(&dir, "ws-embed", ClientConfig::default().max_log_limit(1))
+            .expect("client should open");
+    client
+        .branch_create("main", None)
+        .expect("main should be created");
+    for commit_id in ["m1", "m2"] {
+        client
+            .think_commit(thought("main", commit_id).with_author("alice"))
+            .expect("commit should append");
+    }
+
+    let mut store = SqliteStore::open(&dir).expect("store should open alongside the client");
+    store
+        .branch_archive(ArchiveBranchRequest {
+            workspace_id: "ws-embed".to_string(),
+            branch_id: "main".to_string(),
+            at_ms: 10,
+        })
+        .expect("archive should succeed");
+
+    let log = client
+        .think_log("main", 10)
+        .expect("archived branch log should load");
+    let ids = log.iter().map(|c| c.commit_id()).collect::<Vec<_>>();
+    assert_eq!(ids, vec!["m2"], "limit is capped by max_log_limit");
+
+    assert!(
+        Client::open_with_config(&dir, "ws-embed", ClientConfig::default().busy_retries(99))
+            .is_err(),
+        "config is validated on open"
+    );
+}
@@ -2,6 +2,8 @@
 
 use std::fmt;
 
+pub mod textdiff;
+
 pub const MAX_IDENTIFIER_LEN: usize = 128;
 pub const MAX_COMMIT_MESSAGE_LEN: usize = 1_024;
 pub const MAX_COMMIT_BODY_LEN: usize = 65_536;
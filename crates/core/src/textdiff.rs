@@ -0,0 +1,204 @@
+#![forbid(unsafe_code)]
+
+//! Word-level text diff for comparing commit texts.
+
+/// Above this many token pairs the middle section is reported as one delete plus one insert
+/// instead of running the quadratic LCS table.
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiffOp {
+    Equal,
+    Insert,
+    Delete,
+}
+
+impl DiffOp {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Equal => "equal",
+            Self::Insert => "insert",
+            Self::Delete => "delete",
+        }
+    }
+}
+
+/// A run of consecutive tokens with the same operation. Concatenating the `Equal` and
+/// `Delete` spans yields the old text; `Equal` and `Insert` spans yield the new text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiffSpan {
+    pub op: DiffOp,
+    pub text: String,
+}
+
+/// Diffs two texts on word boundaries. Whitespace runs are tokens too, so spans reproduce
+/// both inputs exactly.
+pub fn word_diff(old: &str, new: &str) -> Vec<DiffSpan> {
+    let old_tokens = tokenize(old);
+    let new_tokens = tokenize(new);
+
+    let prefix = old_tokens
+        .iter()
+        .zip(&new_tokens)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = old_tokens[prefix..]
+        .iter()
+        .rev()
+        .zip(new_tokens[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old_tokens[prefix..old_tokens.len() - suffix];
+    let new_mid = &new_tokens[prefix..new_tokens.len() - suffix];
+
+    let mut spans = Vec::new();
+    for token in &old_tokens[..prefix] {
+        push(&mut spans, DiffOp::Equal, token);
+    }
+    if old_mid.len().saturating_mul(new_mid.len()) > MAX_LCS_CELLS {
+        for token in old_mid {
+            push(&mut spans, DiffOp::Delete, token);
+        }
+        for token in new_mid {
+            push(&mut spans, DiffOp::Insert, token);
+        }
+    } else {
+        lcs_diff(old_mid, new_mid, &mut spans);
+    }
+    for token in &old_tokens[old_tokens.len() - suffix..] {
+        push(&mut spans, DiffOp::Equal, token);
+    }
+    spans
+}
+
+/// Renders spans as inline markdown: deletions as `~~struck~~`, insertions as `**bold**`.
+pub fn render_markdown(spans: &[DiffSpan]) -> String {
+    let mut out = String::new();
+    for span in spans {
+        match span.op {
+            DiffOp::Equal => out.push_str(&span.text),
+            DiffOp::Delete => wrap_words(&mut out, &span.text, "~~"),
+            DiffOp::Insert => wrap_words(&mut out, &span.text, "**"),
+        }
+    }
+    out
+}
+
+fn wrap_words(out: &mut String, text: &str, marker: &str) {
+    // Markers must hug the words; surrounding whitespace stays outside them.
+    let trimmed_start = text.trim_start();
+    let leading = &text[..text.len() - trimmed_start.len()];
+    let core = trimmed_start.trim_end();
+    let trailing = &trimmed_start[core.len()..];
+    out.push_str(leading);
+    if !core.is_empty() {
+        out.push_str(marker);
+        out.push_str(core);
+        out.push_str(marker);
+    }
+    out.push_str(trailing);
+}
+
+fn tokenize(text: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut in_space = None;
+    for (idx, ch) in text.char_indices() {
+        let space = ch.is_whitespace();
+        if in_space.is_some_and(|prev| prev != space) {
+            tokens.push(&text[start..idx]);
+            start = idx;
+        }
+        in_space = Some(space);
+    }
+    if start < text.len() {
+        tokens.push(&text[start..]);
+    }
+    tokens
+}
+
+fn lcs_diff(old: &[&str], new: &[&str], spans: &mut Vec<DiffSpan>) {
+    let cols = new.len() + 1;
+    // table[i * cols + j] = LCS length of old[i..] and new[j..].
+    let mut table = vec![0u32; (old.len() + 1) * cols];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            table[i * cols + j] = if old[i] == new[j] {
+                table[(i + 1) * cols + j + 1] + 1
+            } else {
+                table[(i + 1) * cols + j].max(table[i * cols + j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            push(spans, DiffOp::Equal, old[i]);
+            i += 1;
+            j += 1;
+        } else if table[(i + 1) * cols + j] >= table[i * cols + j + 1] {
+            push(spans, DiffOp::Delete, old[i]);
+            i += 1;
+        } else {
+            push(spans, DiffOp::Insert, new[j]);
+            j += 1;
+        }
+    }
+    for token in &old[i..] {
+        push(spans, DiffOp::Delete, token);
+    }
+    for token in &new[j..] {
+        push(spans, DiffOp::Insert, token);
+    }
+}
+
+fn push(spans: &mut Vec<DiffSpan>, op: DiffOp, token: &str) {
+    match spans.last_mut() {
+        Some(last) if last.op == op => last.text.push_str(token),
+        _ => spans.push(DiffSpan {
+            op,
+            text: token.to_string(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rebuild(spans: &[DiffSpan], skip: DiffOp) -> String {
+        spans
+            .iter()
+            .filter(|span| span.op != skip)
+            .map(|span| span.text.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn word_diff_marks_replaced_words_and_round_trips() {
+        let old = "use sqlite for the cache layer";
+        let new = "use redis for the hot cache layer";
+        let spans = word_diff(old, new);
+
+        assert_eq!(rebuild(&spans, DiffOp::Insert), old);
+        assert_eq!(rebuild(&spans, DiffOp::Delete), new);
+        assert_eq!(
+            render_markdown(&spans),
+            "use ~~sqlite~~**redis** for the **hot** cache layer"
+        );
+    }
+
+    #[test]
+    fn word_diff_of_identical_text_is_one_equal_span() {
+        let spans = word_diff("same text\nhere", "same text\nhere");
+        assert_eq!(
+            spans,
+            vec![DiffSpan {
+                op: DiffOp::Equal,
+                text: "same text\nhere".to_string()
+            }]
+        );
+        assert!(word_diff("", "").is_empty());
+    }
+}
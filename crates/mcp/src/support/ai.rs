@@ -13,6 +13,12 @@ pub(crate) fn format_store_error(err: StoreError) -> String {
         StoreError::BranchAlreadyExists => "Branch already exists".to_string(),
         StoreError::BranchCycle => "Branch base cycle".to_string(),
         StoreError::BranchDepthExceeded => "Branch base depth exceeded".to_string(),
+        StoreError::HeadMismatch { current_head } => match current_head {
+            Some(head) => format!("Branch head mismatch (current head: {head})"),
+            None => "Branch head mismatch (branch has no commits)".to_string(),
+        },
+        StoreError::Busy { waited_ms } => format!("Store busy (waited {waited_ms} ms)"),
+        StoreError::WorkspaceLocked { holder } => format!("Workspace locked by {holder}"),
     }
 }
 
@@ -0,0 +1,93 @@
+#![forbid(unsafe_code)]
+
+use crate::{McpServer, WorkspaceId};
+use bm_storage::ListBranchesRequest;
+use serde_json::{Value, json};
+
+const MAX_BRANCH_SUGGESTIONS: usize = 3;
+
+/// Adds machine-readable remediation data to an error response under `error.hints`.
+pub(crate) fn with_error_hints(mut response: Value, hints: Value) -> Value {
+    if let Some(error) = response.get_mut("error").and_then(|v| v.as_object_mut()) {
+        error.insert("hints".to_string(), hints);
+    }
+    response
+}
+
+/// Attaches `closest_branches` when `branch_id` does not name an existing branch, so the
+/// caller can retry without a separate `branch list` round-trip.
+pub(crate) fn with_branch_hints(
+    server: &McpServer,
+    workspace: &str,
+    branch_id: &str,
+    response: Value,
+) -> Value {
+    let Ok(workspace_id) = WorkspaceId::try_new(workspace.to_string()) else {
+        return response;
+    };
+    if server
+        .store
+        .branch_exists(&workspace_id, branch_id)
+        .unwrap_or(true)
+    {
+        return response;
+    }
+    with_error_hints(
+        response,
+        json!({ "closest_branches": closest_branch_names(server, workspace, branch_id) }),
+    )
+}
+
+/// Returns up to three existing branch names closest to `query` by edit distance, best first.
+pub(crate) fn closest_branch_names(
+    server: &McpServer,
+    workspace: &str,
+    query: &str,
+) -> Vec<String> {
+    let query = query.trim().to_ascii_lowercase();
+    let threshold = (query.chars().count() / 3).max(2);
+    let page_size = server.store.config().max_page_limit;
+    let mut scored = Vec::new();
+    let mut offset = 0usize;
+    loop {
+        let Ok(page) = server.store.list_branches(ListBranchesRequest {
+            workspace_id: workspace.to_string(),
+            limit: page_size,
+            offset,
+        }) else {
+            break;
+        };
+        for branch in &page {
+            let name = branch.branch_id();
+            let distance = edit_distance(&query, name);
+            if distance <= threshold || name.contains(query.as_str()) {
+                scored.push((distance, name.to_string()));
+            }
+        }
+        if page.len() < page_size {
+            break;
+        }
+        offset = offset.saturating_add(page_size);
+    }
+    scored.sort();
+    scored
+        .into_iter()
+        .take(MAX_BRANCH_SUGGESTIONS)
+        .map(|(_, name)| name)
+        .collect()
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut prev = (0..=b.len()).collect::<Vec<_>>();
+    let mut curr = vec![0; b.len() + 1];
+    for (i, ca) in a.chars().enumerate() {
+        curr[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(ca != *cb);
+            curr[j + 1] = substitution.min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+    prev[b.len()]
+}
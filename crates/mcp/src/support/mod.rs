@@ -2,6 +2,7 @@
 
 mod ai;
 mod build_info;
+mod hints;
 mod hot_reload;
 mod jsonrpc;
 mod runtime;
@@ -10,6 +11,7 @@ mod time;
 
 pub(crate) use ai::*;
 pub(crate) use build_info::*;
+pub(crate) use hints::*;
 pub(crate) use hot_reload::*;
 pub(crate) use jsonrpc::*;
 pub(crate) use runtime::*;
@@ -7,7 +7,8 @@ fn markdown_schema() -> Value {
         "type": "object",
         "properties": {
             "workspace": { "type": "string" },
-            "markdown": { "type": "string" }
+            "markdown": { "type": "string" },
+            "format": { "type": "string", "enum": ["json", "compact", "markdown"] }
         },
         "required": ["workspace", "markdown"]
     })
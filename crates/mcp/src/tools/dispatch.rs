@@ -3,14 +3,20 @@
 use crate::McpServer;
 use serde_json::Value;
 
+use super::format::{shape_response, take_output_format};
 use super::{tool_branch, tool_merge, tool_think};
 
-pub(crate) fn dispatch_tool(server: &mut McpServer, name: &str, args: Value) -> Option<Value> {
-    let resp = match name {
-        "think" => tool_think::handle(server, args),
-        "branch" => tool_branch::handle(server, args),
-        "merge" => tool_merge::handle(server, args),
+pub(crate) fn dispatch_tool(server: &mut McpServer, name: &str, mut args: Value) -> Option<Value> {
+    // Resolve the tool first so unknown names fail closed whatever `format` says.
+    let handle: fn(&mut McpServer, Value) -> Value = match name {
+        "think" => tool_think::handle,
+        "branch" => tool_branch::handle,
+        "merge" => tool_merge::handle,
         _ => return None,
     };
-    Some(resp)
+    let format = match take_output_format(&mut args) {
+        Ok(v) => v,
+        Err(err) => return Some(err),
+    };
+    Some(shape_response(handle(server, args), format))
 }
@@ -0,0 +1,117 @@
+#![forbid(unsafe_code)]
+
+use serde_json::Value;
+
+/// Strings longer than this many chars are cut in `compact` output.
+const COMPACT_MAX_CHARS: usize = 280;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum OutputFormat {
+    Json,
+    Compact,
+    Markdown,
+}
+
+/// Removes the optional top-level `format` key so the strict markdown parser never sees it.
+pub(crate) fn take_output_format(args: &mut Value) -> Result<OutputFormat, Value> {
+    let Some(obj) = args.as_object_mut() else {
+        return Ok(OutputFormat::Json);
+    };
+    match obj.remove("format") {
+        None => Ok(OutputFormat::Json),
+        Some(Value::String(raw)) => match raw.as_str() {
+            "json" => Ok(OutputFormat::Json),
+            "compact" => Ok(OutputFormat::Compact),
+            "markdown" => Ok(OutputFormat::Markdown),
+            _ => Err(invalid_format()),
+        },
+        Some(_) => Err(invalid_format()),
+    }
+}
+
+/// Reshapes only the `result` payload; the envelope (success/error/warnings) stays JSON.
+pub(crate) fn shape_response(mut response: Value, format: OutputFormat) -> Value {
+    let Some(result) = response.get_mut("result") else {
+        return response;
+    };
+    match format {
+        OutputFormat::Json => {}
+        OutputFormat::Compact => compact(result),
+        OutputFormat::Markdown => {
+            let mut out = String::new();
+            render_markdown(result, 0, &mut out);
+            *result = Value::String(out);
+        }
+    }
+    response
+}
+
+fn invalid_format() -> Value {
+    crate::ai_error_with(
+        "INVALID_INPUT",
+        "format must be one of: json, compact, markdown",
+        Some("Omit format for the default json output."),
+        Vec::new(),
+    )
+}
+
+fn compact(value: &mut Value) {
+    match value {
+        Value::Object(obj) => {
+            obj.retain(|_, v| !v.is_null());
+            obj.values_mut().for_each(compact);
+        }
+        Value::Array(items) => items.iter_mut().for_each(compact),
+        Value::String(text) => {
+            let total = text.chars().count();
+            if total > COMPACT_MAX_CHARS {
+                let kept = text.chars().take(COMPACT_MAX_CHARS).collect::<String>();
+                *text = format!("{kept}…[+{} chars]", total - COMPACT_MAX_CHARS);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn render_markdown(value: &Value, indent: usize, out: &mut String) {
+    let pad = " ".repeat(indent);
+    match value {
+        Value::Object(obj) => {
+            for (key, item) in obj {
+                match item {
+                    Value::Null => {}
+                    Value::Object(_) | Value::Array(_) => {
+                        out.push_str(&format!("{pad}- **{key}**:\n"));
+                        render_markdown(item, indent + 2, out);
+                    }
+                    scalar => {
+                        out.push_str(&format!(
+                            "{pad}- **{key}**: {}\n",
+                            scalar_text(scalar, &pad)
+                        ));
+                    }
+                }
+            }
+        }
+        Value::Array(items) => {
+            for (idx, item) in items.iter().enumerate() {
+                match item {
+                    Value::Object(_) | Value::Array(_) => {
+                        out.push_str(&format!("{pad}- #{}\n", idx + 1));
+                        render_markdown(item, indent + 2, out);
+                    }
+                    scalar => out.push_str(&format!("{pad}- {}\n", scalar_text(scalar, &pad))),
+                }
+            }
+        }
+        scalar => out.push_str(&format!("{pad}{}\n", scalar_text(scalar, &pad))),
+    }
+}
+
+fn scalar_text(value: &Value, pad: &str) -> String {
+    match value {
+        // Keep multi-line bodies inside their list item.
+        Value::String(text) => text.replace('\n', &format!("\n{pad}  ")),
+        other => other.to_string(),
+    }
+}
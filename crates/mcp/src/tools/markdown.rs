@@ -87,7 +87,7 @@ pub(crate) fn parse_tool_markdown(
             return Err(parser_error(
                 "UNKNOWN_ARG",
                 &format!("Unknown argument: {key}"),
-                "Use only workspace and markdown.",
+                "Use only workspace, markdown and format.",
             ));
         }
     }
@@ -2,6 +2,7 @@
 
 mod definitions;
 mod dispatch;
+mod format;
 mod markdown;
 mod tool_branch;
 mod tool_merge;
@@ -3,14 +3,28 @@
 use super::markdown::parse_tool_markdown;
 use crate::{McpServer, WorkspaceId};
 use bm_core::ThoughtBranch;
-use bm_storage::{CreateBranchRequest, DeleteBranchRequest, ListBranchesRequest, StoreError};
+use bm_storage::{
+    ArchiveBranchRequest, AutoCreateBranchRequest, CreateBranchRequest, CreateScratchBranchRequest,
+    DeleteBranchRequest, ListBranchesRequest, PruneScratchBranchesRequest, StoreError,
+};
 use serde_json::{Value, json};
+use std::collections::BTreeMap;
 
 pub(crate) fn handle(server: &mut McpServer, args: Value) -> Value {
     let parsed = match parse_tool_markdown(
         args,
         "branch",
-        &["create", "list", "checkout", "delete", "main"],
+        &[
+            "create",
+            "auto",
+            "list",
+            "checkout",
+            "delete",
+            "archive",
+            "unarchive",
+            "prune",
+            "main",
+        ],
     ) {
         Ok(v) => v,
         Err(err) => return err,
@@ -18,14 +32,20 @@ pub(crate) fn handle(server: &mut McpServer, args: Value) -> Value {
 
     match parsed.command.verb.as_str() {
         "create" => handle_create(server, &parsed.workspace, &parsed.command),
+        "auto" => handle_auto(server, &parsed.workspace, &parsed.command),
         "list" => handle_list(server, &parsed.workspace, &parsed.command),
         "checkout" => handle_checkout(server, &parsed.workspace, &parsed.command),
         "delete" => handle_delete(server, &parsed.workspace, &parsed.command),
+        "archive" => handle_archive(server, &parsed.workspace, &parsed.command, true),
+        "unarchive" => handle_archive(server, &parsed.workspace, &parsed.command, false),
+        "prune" => handle_prune(server, &parsed.workspace, &parsed.command),
         "main" => handle_main(server, &parsed.workspace, &parsed.command),
         _ => crate::ai_error_with(
             "UNKNOWN_VERB",
             "Unsupported branch verb",
-            Some("Use one of: create, list, checkout, delete, main."),
+            Some(
+                "Use one of: create, auto, list, checkout, delete, archive, unarchive, prune, main.",
+            ),
             Vec::new(),
         ),
     }
@@ -36,7 +56,7 @@ fn handle_create(
     workspace: &str,
     command: &super::markdown::ParsedCommand,
 ) -> Value {
-    if let Err(err) = command.reject_unknown_args(&["branch", "from", "parent"]) {
+    if let Err(err) = command.reject_unknown_args(&["branch", "from", "parent", "ttl_ms"]) {
         return err;
     }
 
@@ -55,16 +75,96 @@ fn handle_create(
         );
     }
     let parent_branch_id = from.or(parent).map(ToOwned::to_owned);
+    let ttl_ms = match command.optional_arg("ttl_ms").map(str::parse::<i64>) {
+        None => None,
+        Some(Ok(ttl_ms)) if ttl_ms > 0 => Some(ttl_ms),
+        Some(_) => {
+            return crate::ai_error_with(
+                "INVALID_INPUT",
+                "ttl_ms must be a positive integer",
+                Some("Pass the scratch lifetime in milliseconds, e.g. ttl_ms=3600000."),
+                Vec::new(),
+            );
+        }
+    };
 
-    match server.store.create_branch(CreateBranchRequest {
+    let now_ms = crate::now_ms_i64();
+    let created = match ttl_ms {
+        None => server.store.create_branch(CreateBranchRequest {
+            workspace_id: workspace.to_string(),
+            branch_id,
+            parent_branch_id: parent_branch_id.clone(),
+            created_at_ms: now_ms,
+        }),
+        Some(ttl_ms) => {
+            // Creating scratch branches is when stale ones pile up, so sweep expired ones first.
+            // A maintenance lock only postpones the sweep; it must not block creation.
+            match server
+                .store
+                .prune_scratch_branches(PruneScratchBranchesRequest {
+                    workspace_id: workspace.to_string(),
+                    now_ms,
+                }) {
+                Ok(_) | Err(StoreError::WorkspaceLocked { .. }) => {}
+                Err(err) => return map_store_error(err),
+            }
+            server
+                .store
+                .create_scratch_branch(CreateScratchBranchRequest {
+                    workspace_id: workspace.to_string(),
+                    branch_id,
+                    parent_branch_id: parent_branch_id.clone(),
+                    ttl_ms,
+                    created_at_ms: now_ms,
+                })
+        }
+    };
+    match created {
+        Ok(branch) => {
+            let mut branch = branch_to_json(&branch);
+            if let Some(ttl_ms) = ttl_ms {
+                branch["scratch_expires_at_ms"] = json!(now_ms.saturating_add(ttl_ms));
+            }
+            crate::ai_ok("branch.create", json!({ "branch": branch }))
+        }
+        Err(err) => match parent_branch_id.as_deref() {
+            Some(parent) => {
+                crate::with_branch_hints(server, workspace, parent, map_store_error(err))
+            }
+            None => map_store_error(err),
+        },
+    }
+}
+
+fn handle_auto(
+    server: &mut McpServer,
+    workspace: &str,
+    command: &super::markdown::ParsedCommand,
+) -> Value {
+    if let Err(err) = command.reject_unknown_args(&["scope", "from"]) {
+        return err;
+    }
+
+    let scope = match command.require_arg("scope") {
+        Ok(v) => v,
+        Err(err) => return err,
+    };
+    let parent_branch_id = command.optional_arg("from").map(ToOwned::to_owned);
+
+    match server.store.branch_auto_create(AutoCreateBranchRequest {
         workspace_id: workspace.to_string(),
-        branch_id,
+        scope,
         parent_branch_id,
         created_at_ms: crate::now_ms_i64(),
     }) {
-        Ok(branch) => crate::ai_ok(
-            "branch.create",
-            json!({ "branch": branch_to_json(&branch) }),
+        Ok(created) => crate::ai_ok(
+            "branch.auto",
+            json!({
+                "workspace": workspace,
+                "branch": branch_to_json(&created.branch),
+                "previous_branch": created.previous_checkout,
+                "checked_out": true
+            }),
         ),
         Err(err) => map_store_error(err),
     }
@@ -75,28 +175,63 @@ fn handle_list(
     workspace: &str,
     command: &super::markdown::ParsedCommand,
 ) -> Value {
-    if let Err(err) = command.reject_unknown_args(&["limit", "offset"]) {
+    if let Err(err) = command.reject_unknown_args(&["limit", "offset", "archived"]) {
         return err;
     }
 
     let limit = match command.optional_usize_arg("limit", 50) {
-        Ok(v) => v.min(500),
+        Ok(v) => v.min(server.store.config().max_page_limit),
         Err(err) => return err,
     };
     let offset = match command.optional_usize_arg("offset", 0) {
         Ok(v) => v,
         Err(err) => return err,
     };
-    match server.store.list_branches(ListBranchesRequest {
+    let archived = match command.optional_arg("archived") {
+        None | Some("false") => false,
+        Some("true") => true,
+        Some(_) => {
+            return crate::ai_error_with(
+                "INVALID_INPUT",
+                "archived must be true or false",
+                Some("Use archived=true to list archived branches."),
+                Vec::new(),
+            );
+        }
+    };
+    let request = ListBranchesRequest {
         workspace_id: workspace.to_string(),
         limit,
         offset,
-    }) {
+    };
+    let listed = if archived {
+        server.store.list_archived_branches(request)
+    } else {
+        server.store.list_branches(request)
+    };
+    let scratch = match server.store.list_scratch_branches(workspace) {
+        Ok(scratch) => scratch
+            .into_iter()
+            .map(|s| (s.branch_id, s.expires_at_ms))
+            .collect::<BTreeMap<_, _>>(),
+        Err(err) => return map_store_error(err),
+    };
+    match listed {
         Ok(branches) => crate::ai_ok(
             "branch.list",
             json!({
                 "workspace": workspace,
-                "items": branches.iter().map(branch_to_json).collect::<Vec<_>>(),
+                "items": branches
+                    .iter()
+                    .map(|branch| {
+                        let mut item = branch_to_json(branch);
+                        if let Some(expires_at_ms) = scratch.get(branch.branch_id()) {
+                            item["scratch_expires_at_ms"] = json!(expires_at_ms);
+                        }
+                        item
+                    })
+                    .collect::<Vec<_>>(),
+                "archived": archived,
                 "limit": limit,
                 "offset": offset,
             }),
@@ -135,11 +270,16 @@ fn handle_checkout(
         Err(err) => return map_store_error(err),
     };
     if !exists {
-        return crate::ai_error_with(
-            "UNKNOWN_ID",
-            "Unknown branch",
-            Some("Call branch with ```bm\\nlist\\n``` to inspect available branches."),
-            Vec::new(),
+        return crate::with_error_hints(
+            crate::ai_error_with(
+                "UNKNOWN_ID",
+                "Unknown branch",
+                Some("Call branch with ```bm\\nlist\\n``` to inspect available branches."),
+                Vec::new(),
+            ),
+            json!({
+                "closest_branches": crate::closest_branch_names(server, workspace, &branch_id)
+            }),
         );
     }
 
@@ -181,6 +321,75 @@ fn handle_delete(
                 "deleted": true
             }),
         ),
+        Err(err) => crate::with_branch_hints(server, workspace, &branch_id, map_store_error(err)),
+    }
+}
+
+fn handle_archive(
+    server: &mut McpServer,
+    workspace: &str,
+    command: &super::markdown::ParsedCommand,
+    archive: bool,
+) -> Value {
+    if let Err(err) = command.reject_unknown_args(&["branch"]) {
+        return err;
+    }
+
+    let branch_id = match command.require_arg("branch") {
+        Ok(v) => v,
+        Err(err) => return err,
+    };
+    let request = ArchiveBranchRequest {
+        workspace_id: workspace.to_string(),
+        branch_id: branch_id.clone(),
+        at_ms: crate::now_ms_i64(),
+    };
+    let (intent, changed) = if archive {
+        ("branch.archive", server.store.branch_archive(request))
+    } else {
+        ("branch.unarchive", server.store.branch_unarchive(request))
+    };
+    match changed {
+        Ok(changed) => crate::ai_ok(
+            intent,
+            json!({
+                "workspace": workspace,
+                "branch": branch_id,
+                "archived": archive,
+                "changed": changed
+            }),
+        ),
+        Err(err) => crate::with_branch_hints(server, workspace, &branch_id, map_store_error(err)),
+    }
+}
+
+fn handle_prune(
+    server: &mut McpServer,
+    workspace: &str,
+    command: &super::markdown::ParsedCommand,
+) -> Value {
+    if let Err(err) = command.reject_unknown_args(&[]) {
+        return err;
+    }
+
+    match server
+        .store
+        .prune_scratch_branches(PruneScratchBranchesRequest {
+            workspace_id: workspace.to_string(),
+            now_ms: crate::now_ms_i64(),
+        }) {
+        Ok(report) => crate::ai_ok(
+            "branch.prune",
+            json!({
+                "workspace": workspace,
+                "deleted": report.deleted,
+                "kept": report
+                    .kept
+                    .iter()
+                    .map(|(branch, reason)| json!({ "branch": branch, "reason": reason }))
+                    .collect::<Vec<_>>(),
+            }),
+        ),
         Err(err) => map_store_error(err),
     }
 }
@@ -279,6 +488,18 @@ fn map_store_error(err: StoreError) -> Value {
             Some("Choose a shallower parent branch."),
             Vec::new(),
         ),
+        StoreError::Busy { .. } => crate::ai_error_with(
+            "BUSY",
+            &crate::format_store_error(err),
+            Some("Another session is writing to the store. Back off briefly and retry."),
+            Vec::new(),
+        ),
+        StoreError::WorkspaceLocked { .. } => crate::ai_error_with(
+            "WORKSPACE_LOCKED",
+            &crate::format_store_error(err),
+            Some("A maintenance job holds the workspace lock. Retry after it is released."),
+            Vec::new(),
+        ),
         other => crate::ai_error_with(
             "STORE_ERROR",
             &crate::format_store_error(other),
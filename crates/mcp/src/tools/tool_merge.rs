@@ -30,9 +30,9 @@ fn handle_into(
     workspace: &str,
     command: &super::markdown::ParsedCommand,
 ) -> Value {
-    if let Err(err) =
-        command.reject_unknown_args(&["target", "from", "strategy", "summary", "message", "body"])
-    {
+    if let Err(err) = command.reject_unknown_args(&[
+        "target", "from", "strategy", "summary", "message", "body", "author",
+    ]) {
         return err;
     }
 
@@ -90,6 +90,8 @@ fn handle_into(
         })
         .unwrap_or_else(|| summary.clone());
 
+    let author = command.optional_arg("author").map(ToOwned::to_owned);
+
     let now_ms = crate::now_ms_i64();
     let mut merges = Vec::new();
     let mut warnings = Vec::new();
@@ -121,6 +123,7 @@ fn handle_into(
             synthesis_commit_id,
             synthesis_message: synthesis_message.clone(),
             synthesis_body: synthesis_body.clone(),
+            author: author.clone(),
             created_at_ms: now_ms,
         };
 
@@ -159,6 +162,7 @@ fn handle_into(
         "target": target_branch_id,
         "strategy": strategy,
         "summary": summary,
+        "author": author,
         "merged": merges,
     });
     if warnings.is_empty() {
@@ -194,6 +198,11 @@ fn merge_warning(source_branch: &str, err: StoreError) -> Value {
             "merge record already exists".to_string(),
             "Use a different merge id seed (retry).",
         ),
+        StoreError::Busy { waited_ms } => (
+            "BUSY",
+            format!("store busy (waited {waited_ms} ms)"),
+            "Back off briefly and retry the merge.",
+        ),
         other => (
             "STORE_ERROR",
             crate::format_store_error(other),
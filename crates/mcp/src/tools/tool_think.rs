@@ -1,29 +1,44 @@
 #![forbid(unsafe_code)]
 
 use super::markdown::parse_tool_markdown;
-use bm_core::{ThoughtBranch, ThoughtCommit};
-use bm_storage::{AppendCommitRequest, ListBranchesRequest, ShowCommitRequest, StoreError};
+use bm_core::{ThoughtBranch, ThoughtCommit, textdiff};
+use bm_storage::{
+    AckKind, AppendCommitRequest, AppendTemplatedCommitRequest, CommitAckRequest, CommitPinRequest,
+    ListBranchesRequest, ListPinnedCommitsRequest, SaveTemplateRequest, ShowCommitRequest,
+    StoreError,
+};
 use serde_json::{Value, json};
+use std::collections::BTreeMap;
 
 use crate::McpServer;
 
 pub(crate) fn handle(server: &mut McpServer, args: Value) -> Value {
-    let parsed =
-        match parse_tool_markdown(args, "think", &["commit", "log", "show", "delete", "amend"]) {
-            Ok(v) => v,
-            Err(err) => return err,
-        };
+    let parsed = match parse_tool_markdown(
+        args,
+        "think",
+        &[
+            "commit", "log", "show", "diff", "delete", "amend", "pin", "unpin", "template", "ack",
+        ],
+    ) {
+        Ok(v) => v,
+        Err(err) => return err,
+    };
 
     match parsed.command.verb.as_str() {
         "commit" => handle_commit(server, &parsed.workspace, &parsed.command),
         "log" => handle_log(server, &parsed.workspace, &parsed.command),
         "show" => handle_show(server, &parsed.workspace, &parsed.command),
+        "diff" => handle_diff(server, &parsed.workspace, &parsed.command),
         "delete" => handle_delete(server, &parsed.workspace, &parsed.command),
         "amend" => handle_amend(server, &parsed.workspace, &parsed.command),
+        "pin" => handle_pin(server, &parsed.workspace, &parsed.command, true),
+        "unpin" => handle_pin(server, &parsed.workspace, &parsed.command, false),
+        "template" => handle_template(server, &parsed.workspace, &parsed.command),
+        "ack" => handle_ack(server, &parsed.workspace, &parsed.command),
         _ => crate::ai_error_with(
             "UNKNOWN_VERB",
             "Unsupported think verb",
-            Some("Use one of: commit, log, show, delete, amend."),
+            Some("Use one of: commit, log, show, diff, delete, amend, pin, unpin, template, ack."),
             Vec::new(),
         ),
     }
@@ -34,11 +49,45 @@ fn handle_commit(
     workspace: &str,
     command: &super::markdown::ParsedCommand,
 ) -> Value {
-    if let Err(err) =
-        command.reject_unknown_args(&["branch", "commit", "message", "body", "parent"])
-    {
+    // `var_<name>=value` args fill the `{{name}}` placeholders of `template`.
+    let vars = command
+        .args
+        .iter()
+        .filter_map(|(key, value)| {
+            key.strip_prefix("var_")
+                .map(|name| (name.to_string(), value.clone()))
+        })
+        .collect::<BTreeMap<_, _>>();
+    let mut allowed = vec![
+        "branch", "commit", "message", "body", "parent", "if_head", "author", "template",
+    ];
+    allowed.extend(
+        command
+            .args
+            .keys()
+            .filter(|key| key.starts_with("var_"))
+            .map(String::as_str),
+    );
+    if let Err(err) = command.reject_unknown_args(&allowed) {
         return err;
     }
+    let template = command.optional_arg("template").map(ToOwned::to_owned);
+    if template.is_none() && !vars.is_empty() {
+        return crate::ai_error_with(
+            "INVALID_INPUT",
+            "var_* arguments require template",
+            Some("Add template=<name> or drop the var_* arguments."),
+            Vec::new(),
+        );
+    }
+    if template.is_some() && (command.optional_arg("body").is_some() || !command.body.is_empty()) {
+        return crate::ai_error_with(
+            "INVALID_INPUT",
+            "template and body are mutually exclusive",
+            Some("The template expansion becomes the body; pass values as var_<name>=..."),
+            Vec::new(),
+        );
+    }
 
     let branch_id = match command.require_arg("branch") {
         Ok(v) => v,
@@ -66,19 +115,42 @@ fn handle_commit(
         .unwrap_or_else(|| message.clone());
 
     let parent_commit_id = command.optional_arg("parent").map(ToOwned::to_owned);
+    let expected_head_commit_id = command.optional_arg("if_head").map(ToOwned::to_owned);
+    let author = command.optional_arg("author").map(ToOwned::to_owned);
     let request = AppendCommitRequest {
         workspace_id: workspace.to_string(),
         branch_id,
         commit_id,
         parent_commit_id,
+        expected_head_commit_id,
         message,
         body,
+        author,
         created_at_ms: crate::now_ms_i64(),
     };
 
-    match server.store.append_commit(request) {
-        Ok(commit) => crate::ai_ok("think.commit", json!({ "commit": commit_to_json(&commit) })),
-        Err(err) => map_store_error(err),
+    let branch_id = request.branch_id.clone();
+    let appended = match template.clone() {
+        None => server.store.append_commit(request),
+        Some(template) => server
+            .store
+            .append_templated_commit(AppendTemplatedCommitRequest {
+                commit: request,
+                template,
+                vars,
+            }),
+    };
+    match appended {
+        Ok(commit) => {
+            let author = match commit_author(server, &commit) {
+                Ok(v) => v,
+                Err(err) => return map_store_error(err),
+            };
+            let mut commit_json = commit_to_json(&commit, author.as_deref());
+            commit_json["template"] = json!(template);
+            crate::ai_ok("think.commit", json!({ "commit": commit_json }))
+        }
+        Err(err) => crate::with_branch_hints(server, workspace, &branch_id, map_store_error(err)),
     }
 }
 
@@ -87,7 +159,15 @@ fn handle_log(
     workspace: &str,
     command: &super::markdown::ParsedCommand,
 ) -> Value {
-    if let Err(err) = command.reject_unknown_args(&["branch", "limit", "offset", "from"]) {
+    if let Err(err) = command.reject_unknown_args(&[
+        "branch",
+        "limit",
+        "offset",
+        "from",
+        "author",
+        "pinned",
+        "max_bytes",
+    ]) {
         return err;
     }
 
@@ -96,22 +176,61 @@ fn handle_log(
         Err(err) => return err,
     };
     let limit = match command.optional_usize_arg("limit", 20) {
-        Ok(v) => v.min(200),
+        Ok(v) => v.min(server.store.config().max_log_limit),
         Err(err) => return err,
     };
     let offset = match command.optional_usize_arg("offset", 0) {
         Ok(v) => v,
         Err(err) => return err,
     };
+    // Byte budget over serialized items; the first item is always returned so paging advances.
+    let max_bytes = match command.optional_usize_arg("max_bytes", usize::MAX) {
+        Ok(v) => v,
+        Err(err) => return err,
+    };
+
+    let author_filter = match command
+        .optional_arg("author")
+        .map(|v| bm_core::canonical_identifier("author", v))
+        .transpose()
+    {
+        Ok(v) => v,
+        Err(_) => {
+            return crate::ai_error_with(
+                "INVALID_INPUT",
+                "invalid author",
+                Some("Use the same author id that was passed to think commit."),
+                Vec::new(),
+            );
+        }
+    };
+
+    let include_pinned = match command.optional_arg("pinned") {
+        None | Some("false") => false,
+        Some("true") => true,
+        Some(_) => {
+            return crate::ai_error_with(
+                "INVALID_INPUT",
+                "pinned must be true or false",
+                Some("Use pinned=true to list pinned commits ahead of the log."),
+                Vec::new(),
+            );
+        }
+    };
 
     let branch = match find_branch_by_id(server, workspace, &branch_id) {
         Ok(Some(branch)) => branch,
         Ok(None) => {
-            return crate::ai_error_with(
-                "UNKNOWN_ID",
-                "Unknown branch",
-                Some("Create the branch first or check branch list."),
-                Vec::new(),
+            return crate::with_error_hints(
+                crate::ai_error_with(
+                    "UNKNOWN_ID",
+                    "Unknown branch",
+                    Some("Create the branch first or check branch list."),
+                    Vec::new(),
+                ),
+                json!({
+                    "closest_branches": crate::closest_branch_names(server, workspace, &branch_id)
+                }),
             );
         }
         Err(err) => return map_store_error(err),
@@ -124,8 +243,38 @@ fn handle_log(
     let mut seen = std::collections::BTreeSet::new();
     let mut skipped = 0usize;
     let mut commits = Vec::new();
+    let mut used_bytes = 0usize;
     let mut truncated = false;
 
+    // The leading pinned section shares `limit` and the byte budget with the log items.
+    let mut pinned_items = None;
+    if include_pinned {
+        let pinned = match server.store.list_pinned_commits(ListPinnedCommitsRequest {
+            workspace_id: workspace.to_string(),
+            branch_id: branch_id.clone(),
+            limit,
+        }) {
+            Ok(v) => v,
+            Err(err) => return map_store_error(err),
+        };
+        let mut items = Vec::with_capacity(pinned.len());
+        for commit in &pinned {
+            let author = match commit_author(server, commit) {
+                Ok(v) => v,
+                Err(err) => return map_store_error(err),
+            };
+            let item = commit_to_json(commit, author.as_deref());
+            let item_bytes = item.to_string().len();
+            if used_bytes.saturating_add(item_bytes) > max_bytes {
+                truncated = true;
+                break;
+            }
+            used_bytes = used_bytes.saturating_add(item_bytes);
+            items.push(item);
+        }
+        pinned_items = Some(items);
+    }
+
     while let Some(commit_id) = cursor.clone() {
         if commits.len() >= limit {
             truncated = true;
@@ -159,11 +308,27 @@ fn handle_log(
 
         cursor = commit.parent_commit_id().map(ToOwned::to_owned);
 
+        let author = match commit_author(server, &commit) {
+            Ok(v) => v,
+            Err(err) => return map_store_error(err),
+        };
+        if author_filter.is_some() && author != author_filter {
+            continue;
+        }
+
         if skipped < offset {
             skipped += 1;
             continue;
         }
-        commits.push(commit_to_json(&commit));
+        let item = commit_to_json(&commit, author.as_deref());
+        let item_bytes = item.to_string().len();
+        if !commits.is_empty() && used_bytes.saturating_add(item_bytes) > max_bytes {
+            cursor = Some(commit_id);
+            truncated = true;
+            break;
+        }
+        used_bytes = used_bytes.saturating_add(item_bytes);
+        commits.push(item);
     }
 
     let mut result = json!({
@@ -174,6 +339,22 @@ fn handle_log(
         "items": commits,
         "next_commit_id": cursor,
     });
+    if let Some(author) = author_filter
+        && let Some(obj) = result.as_object_mut()
+    {
+        obj.insert("author".to_string(), Value::String(author));
+    }
+    if command.optional_arg("max_bytes").is_some()
+        && let Some(obj) = result.as_object_mut()
+    {
+        obj.insert("max_bytes".to_string(), json!(max_bytes));
+        obj.insert("used_bytes".to_string(), json!(used_bytes));
+    }
+    if let Some(pinned_items) = pinned_items
+        && let Some(obj) = result.as_object_mut()
+    {
+        obj.insert("pinned".to_string(), Value::Array(pinned_items));
+    }
     if truncated && let Some(obj) = result.as_object_mut() {
         obj.insert("truncated".to_string(), Value::Bool(true));
     }
@@ -185,23 +366,32 @@ fn find_branch_by_id(
     workspace: &str,
     branch_id: &str,
 ) -> Result<Option<ThoughtBranch>, StoreError> {
-    const PAGE_SIZE: usize = 1024;
-    let mut offset = 0usize;
+    let page_size = server.store.config().max_page_limit;
 
-    loop {
-        let page = server.store.list_branches(ListBranchesRequest {
-            workspace_id: workspace.to_string(),
-            limit: PAGE_SIZE,
-            offset,
-        })?;
-        if let Some(found) = page.iter().find(|branch| branch.branch_id() == branch_id) {
-            return Ok(Some(found.clone()));
-        }
-        if page.len() < PAGE_SIZE {
-            return Ok(None);
+    // Archived branches stay readable, so fall back to them when no active branch matches.
+    for archived in [false, true] {
+        let mut offset = 0usize;
+        loop {
+            let request = ListBranchesRequest {
+                workspace_id: workspace.to_string(),
+                limit: page_size,
+                offset,
+            };
+            let page = if archived {
+                server.store.list_archived_branches(request)?
+            } else {
+                server.store.list_branches(request)?
+            };
+            if let Some(found) = page.iter().find(|branch| branch.branch_id() == branch_id) {
+                return Ok(Some(found.clone()));
+            }
+            if page.len() < page_size {
+                break;
+            }
+            offset = offset.saturating_add(page_size);
         }
-        offset = offset.saturating_add(PAGE_SIZE);
     }
+    Ok(None)
 }
 
 fn handle_show(
@@ -222,7 +412,25 @@ fn handle_show(
         commit_id: commit_id.clone(),
     }) {
         Ok(Some(commit)) => {
-            crate::ai_ok("think.show", json!({ "commit": commit_to_json(&commit) }))
+            let request = ShowCommitRequest {
+                workspace_id: workspace.to_string(),
+                commit_id: commit.commit_id().to_string(),
+            };
+            let template = server.store.commit_template(request.clone());
+            let acks = server.store.commit_ack_counts(request);
+            match (commit_author(server, &commit), template, acks) {
+                (Ok(author), Ok(template), Ok(acks)) => {
+                    let mut commit_json = commit_to_json(&commit, author.as_deref());
+                    commit_json["template"] = json!(template);
+                    commit_json["acks"] = json!({
+                        "seen": acks.seen,
+                        "agree": acks.agree,
+                        "disagree": acks.disagree,
+                    });
+                    crate::ai_ok("think.show", json!({ "commit": commit_json }))
+                }
+                (Err(err), _, _) | (_, Err(err), _) | (_, _, Err(err)) => map_store_error(err),
+            }
         }
         Ok(None) => crate::ai_error_with(
             "UNKNOWN_ID",
@@ -234,14 +442,135 @@ fn handle_show(
     }
 }
 
-fn handle_amend(
+fn handle_template(
     server: &mut McpServer,
     workspace: &str,
     command: &super::markdown::ParsedCommand,
 ) -> Value {
-    if let Err(err) =
-        command.reject_unknown_args(&["commit", "new_commit", "branch", "message", "body"])
+    if let Err(err) = command.reject_unknown_args(&["name"]) {
+        return err;
+    }
+
+    let name = match command.require_arg("name") {
+        Ok(v) => v,
+        Err(err) => return err,
+    };
+    match server.store.template_save(SaveTemplateRequest {
+        workspace_id: workspace.to_string(),
+        name: name.clone(),
+        body: command.body.clone(),
+        saved_at_ms: crate::now_ms_i64(),
+    }) {
+        Ok(placeholders) => crate::ai_ok(
+            "think.template",
+            json!({
+                "workspace": workspace,
+                "name": name,
+                "placeholders": placeholders,
+            }),
+        ),
+        Err(err) => map_store_error(err),
+    }
+}
+
+fn handle_diff(
+    server: &mut McpServer,
+    workspace: &str,
+    command: &super::markdown::ParsedCommand,
+) -> Value {
+    if let Err(err) = command.reject_unknown_args(&["from", "to"]) {
+        return err;
+    }
+
+    let to_commit_id = match command.require_arg("to") {
+        Ok(v) => v,
+        Err(err) => return err,
+    };
+    let to = match load_commit(server, workspace, &to_commit_id) {
+        Ok(v) => v,
+        Err(err) => return err,
+    };
+    let from_commit_id = match command
+        .optional_arg("from")
+        .map(ToOwned::to_owned)
+        .or_else(|| to.parent_commit_id().map(ToOwned::to_owned))
     {
+        Some(v) => v,
+        None => {
+            return crate::ai_error_with(
+                "INVALID_INPUT",
+                "from is required when the commit has no parent",
+                Some("Pass from=<commit> to choose the base commit."),
+                Vec::new(),
+            );
+        }
+    };
+    let from = match load_commit(server, workspace, &from_commit_id) {
+        Ok(v) => v,
+        Err(err) => return err,
+    };
+
+    let message = textdiff::word_diff(from.message(), to.message());
+    let body = textdiff::word_diff(from.body(), to.body());
+    let changed =
+        |spans: &[textdiff::DiffSpan]| spans.iter().any(|span| span.op != textdiff::DiffOp::Equal);
+    crate::ai_ok(
+        "think.diff",
+        json!({
+            "workspace": workspace,
+            "from": from.commit_id(),
+            "to": to.commit_id(),
+            "message_changed": changed(&message),
+            "body_changed": changed(&body),
+            "message": spans_to_json(&message),
+            "body": spans_to_json(&body),
+            "markdown": textdiff::render_markdown(&body),
+        }),
+    )
+}
+
+fn load_commit(
+    server: &McpServer,
+    workspace: &str,
+    commit_id: &str,
+) -> Result<ThoughtCommit, Value> {
+    match server.store.show_commit(ShowCommitRequest {
+        workspace_id: workspace.to_string(),
+        commit_id: commit_id.to_string(),
+    }) {
+        Ok(Some(commit)) => Ok(commit),
+        Ok(None) => Err(crate::ai_error_with(
+            "UNKNOWN_ID",
+            &format!("Unknown commit: {commit_id}"),
+            Some("Call think log to discover existing commits."),
+            Vec::new(),
+        )),
+        Err(err) => Err(map_store_error(err)),
+    }
+}
+
+fn spans_to_json(spans: &[textdiff::DiffSpan]) -> Value {
+    Value::Array(
+        spans
+            .iter()
+            .map(|span| json!({ "op": span.op.as_str(), "text": span.text }))
+            .collect(),
+    )
+}
+
+fn handle_amend(
+    server: &mut McpServer,
+    workspace: &str,
+    command: &super::markdown::ParsedCommand,
+) -> Value {
+    if let Err(err) = command.reject_unknown_args(&[
+        "commit",
+        "new_commit",
+        "branch",
+        "message",
+        "body",
+        "author",
+    ]) {
         return err;
     }
 
@@ -289,23 +618,30 @@ fn handle_amend(
             }
         })
         .unwrap_or_else(|| source_commit.body().to_string());
+    let author = command.optional_arg("author").map(ToOwned::to_owned);
 
     let request = AppendCommitRequest {
         workspace_id: workspace.to_string(),
         branch_id,
         commit_id: new_commit_id,
         parent_commit_id: source_commit.parent_commit_id().map(ToOwned::to_owned),
+        expected_head_commit_id: None,
         message,
         body,
+        author,
         created_at_ms: crate::now_ms_i64(),
     };
 
-    match server.store.append_commit(request) {
-        Ok(amended) => crate::ai_ok(
+    let amended = match server.store.append_commit(request) {
+        Ok(v) => v,
+        Err(err) => return map_store_error(err),
+    };
+    match commit_author(server, &amended) {
+        Ok(author) => crate::ai_ok(
             "think.amend",
             json!({
                 "source_commit_id": source_commit_id,
-                "amended_commit": commit_to_json(&amended),
+                "amended_commit": commit_to_json(&amended, author.as_deref()),
             }),
         ),
         Err(err) => map_store_error(err),
@@ -317,9 +653,14 @@ fn handle_delete(
     workspace: &str,
     command: &super::markdown::ParsedCommand,
 ) -> Value {
-    if let Err(err) =
-        command.reject_unknown_args(&["commit", "new_commit", "branch", "message", "body"])
-    {
+    if let Err(err) = command.reject_unknown_args(&[
+        "commit",
+        "new_commit",
+        "branch",
+        "message",
+        "body",
+        "author",
+    ]) {
         return err;
     }
 
@@ -367,24 +708,31 @@ fn handle_delete(
             }
         })
         .unwrap_or_else(|| format!("tombstone for {}", source_commit.commit_id()));
+    let author = command.optional_arg("author").map(ToOwned::to_owned);
 
     let request = AppendCommitRequest {
         workspace_id: workspace.to_string(),
         branch_id,
         commit_id: new_commit_id,
         parent_commit_id: source_commit.parent_commit_id().map(ToOwned::to_owned),
+        expected_head_commit_id: None,
         message,
         body,
+        author,
         created_at_ms: crate::now_ms_i64(),
     };
 
-    match server.store.append_commit(request) {
-        Ok(tombstone) => crate::ai_ok_with_warnings(
+    let tombstone = match server.store.append_commit(request) {
+        Ok(v) => v,
+        Err(err) => return map_store_error(err),
+    };
+    match commit_author(server, &tombstone) {
+        Ok(author) => crate::ai_ok_with_warnings(
             "think.delete",
             json!({
                 "mode": "soft_delete",
                 "source_commit_id": source_commit_id,
-                "tombstone_commit": commit_to_json(&tombstone),
+                "tombstone_commit": commit_to_json(&tombstone, author.as_deref()),
             }),
             vec![crate::warning(
                 "SOFT_DELETE",
@@ -397,7 +745,99 @@ fn handle_delete(
     }
 }
 
-fn commit_to_json(commit: &ThoughtCommit) -> Value {
+fn handle_pin(
+    server: &mut McpServer,
+    workspace: &str,
+    command: &super::markdown::ParsedCommand,
+    pinned: bool,
+) -> Value {
+    if let Err(err) = command.reject_unknown_args(&["commit"]) {
+        return err;
+    }
+
+    let commit_id = match command.require_arg("commit") {
+        Ok(v) => v,
+        Err(err) => return err,
+    };
+    let intent = if pinned { "think.pin" } else { "think.unpin" };
+    match server.store.commit_pin_set(CommitPinRequest {
+        workspace_id: workspace.to_string(),
+        commit_id: commit_id.clone(),
+        pinned,
+        pinned_at_ms: crate::now_ms_i64(),
+    }) {
+        Ok(changed) => crate::ai_ok(
+            intent,
+            json!({
+                "workspace": workspace,
+                "commit_id": commit_id,
+                "pinned": pinned,
+                "changed": changed,
+            }),
+        ),
+        Err(err) => map_store_error(err),
+    }
+}
+
+fn handle_ack(
+    server: &mut McpServer,
+    workspace: &str,
+    command: &super::markdown::ParsedCommand,
+) -> Value {
+    if let Err(err) = command.reject_unknown_args(&["commit", "actor", "kind"]) {
+        return err;
+    }
+
+    let commit_id = match command.require_arg("commit") {
+        Ok(v) => v,
+        Err(err) => return err,
+    };
+    let actor = match command.require_arg("actor") {
+        Ok(v) => v,
+        Err(err) => return err,
+    };
+    let kind = match command.optional_arg("kind") {
+        None | Some("seen") => AckKind::Seen,
+        Some("agree") => AckKind::Agree,
+        Some("disagree") => AckKind::Disagree,
+        Some(_) => {
+            return crate::ai_error_with(
+                "INVALID_INPUT",
+                "kind must be seen, agree or disagree",
+                Some("Omit kind to record seen."),
+                Vec::new(),
+            );
+        }
+    };
+    match server.store.commit_ack(CommitAckRequest {
+        workspace_id: workspace.to_string(),
+        commit_id: commit_id.clone(),
+        actor: actor.clone(),
+        kind,
+        acked_at_ms: crate::now_ms_i64(),
+    }) {
+        Ok(changed) => crate::ai_ok(
+            "think.ack",
+            json!({
+                "workspace": workspace,
+                "commit_id": commit_id,
+                "actor": actor,
+                "kind": kind.as_str(),
+                "changed": changed,
+            }),
+        ),
+        Err(err) => map_store_error(err),
+    }
+}
+
+fn commit_author(server: &McpServer, commit: &ThoughtCommit) -> Result<Option<String>, StoreError> {
+    server.store.commit_author(ShowCommitRequest {
+        workspace_id: commit.workspace_id().to_string(),
+        commit_id: commit.commit_id().to_string(),
+    })
+}
+
+fn commit_to_json(commit: &ThoughtCommit, author: Option<&str>) -> Value {
     json!({
         "workspace_id": commit.workspace_id(),
         "branch_id": commit.branch_id(),
@@ -405,6 +845,7 @@ fn commit_to_json(commit: &ThoughtCommit) -> Value {
         "parent_commit_id": commit.parent_commit_id(),
         "message": commit.message(),
         "body": commit.body(),
+        "author": author,
         "created_at_ms": commit.created_at_ms(),
     })
 }
@@ -435,6 +876,25 @@ fn map_store_error(err: StoreError) -> Value {
             Some("Fix branch ancestry and retry."),
             Vec::new(),
         ),
+        StoreError::HeadMismatch { ref current_head } => crate::with_error_hints(
+            crate::ai_error_with(
+                "HEAD_MISMATCH",
+                &crate::format_store_error(StoreError::HeadMismatch {
+                    current_head: current_head.clone(),
+                }),
+                Some(
+                    "Another writer advanced the branch. Call think log, then retry with if_head set to the current head.",
+                ),
+                Vec::new(),
+            ),
+            json!({ "current_head": current_head }),
+        ),
+        StoreError::Busy { .. } => crate::ai_error_with(
+            "BUSY",
+            &crate::format_store_error(err),
+            Some("Another session is writing to the store. Back off briefly and retry."),
+            Vec::new(),
+        ),
         other => crate::ai_error_with(
             "STORE_ERROR",
             &crate::format_store_error(other),
@@ -639,3 +639,688 @@ fn branch_create_accepts_parent_alias_and_rejects_conflict_with_from() {
         Some("INVALID_INPUT")
     );
 }
+
+#[test]
+fn branch_auto_creates_next_session_branch_and_checks_it_out() {
+    let mut server = Server::start_initialized("branch_auto_session");
+    let workspace = "ws-branch-auto";
+
+    let main = call_markdown_tool(&mut server, 110, "branch", workspace, "```bm\nmain\n```");
+    assert_eq!(main.get("success").and_then(|v| v.as_bool()), Some(true));
+
+    for (id, expected) in [(111, "task-042/s1"), (112, "task-042/s2")] {
+        let auto = call_markdown_tool(
+            &mut server,
+            id,
+            "branch",
+            workspace,
+            "```bm\nauto scope=task-042 from=main\n```",
+        );
+        assert_eq!(
+            auto.get("success").and_then(|v| v.as_bool()),
+            Some(true),
+            "branch auto should succeed: {auto}"
+        );
+        let branch = auto
+            .get("result")
+            .and_then(|v| v.get("branch"))
+            .expect("result.branch");
+        assert_eq!(
+            branch.get("branch_id").and_then(|v| v.as_str()),
+            Some(expected)
+        );
+        assert_eq!(
+            branch.get("parent_branch_id").and_then(|v| v.as_str()),
+            Some("main")
+        );
+        assert_eq!(
+            auto.get("result")
+                .and_then(|v| v.get("checked_out"))
+                .and_then(|v| v.as_bool()),
+            Some(true)
+        );
+    }
+}
+
+#[test]
+fn think_commit_records_author_and_log_filters_by_writer() {
+    let mut server = Server::start_initialized("think_commit_author");
+    let workspace = "ws-think-author";
+
+    let main = call_markdown_tool(&mut server, 120, "branch", workspace, "```bm\nmain\n```");
+    assert_eq!(main.get("success").and_then(|v| v.as_bool()), Some(true));
+
+    for (id, commit_id, author) in [(121, "c1", "agent-a"), (122, "c2", "agent-b")] {
+        let markdown = format!(
+            "```bm\ncommit branch=main commit={commit_id} message=step author={author}\n```"
+        );
+        let commit = call_markdown_tool(&mut server, id, "think", workspace, &markdown);
+        assert_eq!(
+            commit
+                .get("result")
+                .and_then(|v| v.get("commit"))
+                .and_then(|v| v.get("author"))
+                .and_then(|v| v.as_str()),
+            Some(author),
+            "commit should echo its author: {commit}"
+        );
+    }
+
+    let log = call_markdown_tool(
+        &mut server,
+        123,
+        "think",
+        workspace,
+        "```bm\nlog branch=main author=agent-a\n```",
+    );
+    let items = log
+        .get("result")
+        .and_then(|v| v.get("items"))
+        .and_then(|v| v.as_array())
+        .expect("result.items");
+    assert_eq!(items.len(), 1, "only agent-a commits expected: {log}");
+    assert_eq!(
+        items[0].get("commit_id").and_then(|v| v.as_str()),
+        Some("c1")
+    );
+}
+
+#[test]
+fn think_pin_keeps_commit_in_leading_pinned_section_of_log() {
+    let mut server = Server::start_initialized("think_pin_log");
+    let workspace = "ws-think-pin";
+
+    let main = call_markdown_tool(&mut server, 130, "branch", workspace, "```bm\nmain\n```");
+    assert_eq!(main.get("success").and_then(|v| v.as_bool()), Some(true));
+
+    for (id, commit_id) in [(131, "c1"), (132, "c2"), (133, "c3")] {
+        let markdown = format!("```bm\ncommit branch=main commit={commit_id} message=step\n```");
+        let commit = call_markdown_tool(&mut server, id, "think", workspace, &markdown);
+        assert_eq!(commit.get("success").and_then(|v| v.as_bool()), Some(true));
+    }
+
+    let pin = call_markdown_tool(
+        &mut server,
+        134,
+        "think",
+        workspace,
+        "```bm\npin commit=c1\n```",
+    );
+    assert_eq!(
+        pin.get("success").and_then(|v| v.as_bool()),
+        Some(true),
+        "pin should succeed: {pin}"
+    );
+
+    let log = call_markdown_tool(
+        &mut server,
+        135,
+        "think",
+        workspace,
+        "```bm\nlog branch=main limit=1 pinned=true\n```",
+    );
+    let result = log.get("result").expect("result");
+    let head = result
+        .get("items")
+        .and_then(|v| v.as_array())
+        .expect("result.items");
+    assert_eq!(head.len(), 1);
+    assert_eq!(
+        head[0].get("commit_id").and_then(|v| v.as_str()),
+        Some("c3")
+    );
+    let pinned = result
+        .get("pinned")
+        .and_then(|v| v.as_array())
+        .expect("result.pinned");
+    assert_eq!(pinned.len(), 1);
+    assert_eq!(
+        pinned[0].get("commit_id").and_then(|v| v.as_str()),
+        Some("c1")
+    );
+}
+
+#[test]
+fn think_log_max_bytes_truncates_with_resumable_cursor() {
+    let mut server = Server::start_initialized("think_log_max_bytes");
+    let workspace = "ws-think-bytes";
+
+    let main = call_markdown_tool(&mut server, 140, "branch", workspace, "```bm\nmain\n```");
+    assert_eq!(main.get("success").and_then(|v| v.as_bool()), Some(true));
+
+    let large_body = "x".repeat(2048);
+    for (id, commit_id) in [(141, "c1"), (142, "c2"), (143, "c3")] {
+        let markdown = format!(
+            "```bm\ncommit branch=main commit={commit_id} message=step body={large_body}\n```"
+        );
+        let commit = call_markdown_tool(&mut server, id, "think", workspace, &markdown);
+        assert_eq!(commit.get("success").and_then(|v| v.as_bool()), Some(true));
+    }
+
+    let log = call_markdown_tool(
+        &mut server,
+        144,
+        "think",
+        workspace,
+        "```bm\nlog branch=main max_bytes=3000\n```",
+    );
+    let result = log.get("result").expect("result");
+    let items = result
+        .get("items")
+        .and_then(|v| v.as_array())
+        .expect("result.items");
+    assert_eq!(
+        items.len(),
+        1,
+        "budget should admit one large commit: {log}"
+    );
+    assert_eq!(
+        items[0].get("commit_id").and_then(|v| v.as_str()),
+        Some("c3")
+    );
+    assert_eq!(
+        result.get("truncated").and_then(|v| v.as_bool()),
+        Some(true)
+    );
+    assert_eq!(
+        result.get("next_commit_id").and_then(|v| v.as_str()),
+        Some("c2")
+    );
+}
+
+#[test]
+fn think_log_pinned_section_counts_against_limit_and_max_bytes() {
+    let mut server = Server::start_initialized("think_log_pinned_budget");
+    let workspace = "ws-think-pinned-budget";
+
+    let main = call_markdown_tool(&mut server, 145, "branch", workspace, "```bm\nmain\n```");
+    assert_eq!(main.get("success").and_then(|v| v.as_bool()), Some(true));
+
+    let large_body = "x".repeat(2048);
+    for (id, commit_id) in [(146, "c1"), (147, "c2"), (148, "c3")] {
+        let markdown = format!(
+            "```bm\ncommit branch=main commit={commit_id} message=step body={large_body}\n```"
+        );
+        let commit = call_markdown_tool(&mut server, id, "think", workspace, &markdown);
+        assert_eq!(commit.get("success").and_then(|v| v.as_bool()), Some(true));
+        let pin = call_markdown_tool(
+            &mut server,
+            id + 10,
+            "think",
+            workspace,
+            &format!("```bm\npin commit={commit_id}\n```"),
+        );
+        assert_eq!(pin.get("success").and_then(|v| v.as_bool()), Some(true));
+    }
+
+    let log = call_markdown_tool(
+        &mut server,
+        160,
+        "think",
+        workspace,
+        "```bm\nlog branch=main limit=2 pinned=true\n```",
+    );
+    let pinned = log
+        .get("result")
+        .and_then(|v| v.get("pinned"))
+        .and_then(|v| v.as_array())
+        .expect("result.pinned");
+    assert_eq!(pinned.len(), 2, "limit bounds the pinned section: {log}");
+
+    let log = call_markdown_tool(
+        &mut server,
+        161,
+        "think",
+        workspace,
+        "```bm\nlog branch=main pinned=true max_bytes=3000\n```",
+    );
+    let result = log.get("result").expect("result");
+    let pinned = result
+        .get("pinned")
+        .and_then(|v| v.as_array())
+        .expect("result.pinned");
+    assert_eq!(
+        pinned.len(),
+        1,
+        "budget should admit one pinned commit: {log}"
+    );
+    assert_eq!(
+        result.get("truncated").and_then(|v| v.as_bool()),
+        Some(true)
+    );
+    let used_bytes = result
+        .get("used_bytes")
+        .and_then(|v| v.as_u64())
+        .expect("result.used_bytes");
+    assert!(used_bytes > 2048, "pinned bytes are counted: {log}");
+}
+
+#[test]
+fn tool_format_compact_strips_nulls_and_truncates_long_text() {
+    let mut server = Server::start_initialized("tool_format_compact");
+    let workspace = "ws-format";
+
+    let main = call_markdown_tool(&mut server, 150, "branch", workspace, "```bm\nmain\n```");
+    assert_eq!(main.get("success").and_then(|v| v.as_bool()), Some(true));
+
+    let body = "y".repeat(400);
+    let resp = server.request(json!({
+        "jsonrpc": "2.0",
+        "id": 151,
+        "method": "tools/call",
+        "params": {
+            "name": "think",
+            "arguments": {
+                "workspace": workspace,
+                "markdown": format!("```bm\ncommit branch=main commit=c1 message=step body={body}\n```"),
+                "format": "compact"
+            }
+        }
+    }));
+    let commit = extract_tool_text(&resp);
+    let stored = commit
+        .get("result")
+        .and_then(|v| v.get("commit"))
+        .expect("result.commit");
+    assert!(
+        stored.get("parent_commit_id").is_none(),
+        "null fields are dropped: {stored}"
+    );
+    let shown_body = stored
+        .get("body")
+        .and_then(|v| v.as_str())
+        .expect("commit.body");
+    assert!(shown_body.ends_with("…[+120 chars]"), "{shown_body}");
+
+    let show = call_markdown_tool(
+        &mut server,
+        152,
+        "think",
+        workspace,
+        "```bm\nshow commit=c1\n```",
+    );
+    assert_eq!(
+        show.get("result")
+            .and_then(|v| v.get("commit"))
+            .and_then(|v| v.get("body"))
+            .and_then(|v| v.as_str()),
+        Some(body.as_str()),
+        "default json output keeps the full stored body"
+    );
+
+    let resp = server.request(json!({
+        "jsonrpc": "2.0",
+        "id": 153,
+        "method": "tools/call",
+        "params": {
+            "name": "think",
+            "arguments": {
+                "workspace": workspace,
+                "markdown": "```bm\nshow commit=c1\n```",
+                "format": "yaml"
+            }
+        }
+    }));
+    let rejected = extract_tool_text(&resp);
+    assert_eq!(
+        rejected
+            .get("error")
+            .and_then(|v| v.get("code"))
+            .and_then(|v| v.as_str()),
+        Some("INVALID_INPUT")
+    );
+}
+
+#[test]
+fn branch_archive_hides_branch_from_list_until_unarchived() {
+    let mut server = Server::start_initialized("branch_archive_list");
+    let workspace = "ws-branch-archive";
+
+    let main = call_markdown_tool(&mut server, 160, "branch", workspace, "```bm\nmain\n```");
+    assert_eq!(main.get("success").and_then(|v| v.as_bool()), Some(true));
+    let create = call_markdown_tool(
+        &mut server,
+        161,
+        "branch",
+        workspace,
+        "```bm\ncreate branch=exp from=main\n```",
+    );
+    assert_eq!(create.get("success").and_then(|v| v.as_bool()), Some(true));
+
+    let archive = call_markdown_tool(
+        &mut server,
+        162,
+        "branch",
+        workspace,
+        "```bm\narchive branch=exp\n```",
+    );
+    assert_eq!(
+        archive.get("success").and_then(|v| v.as_bool()),
+        Some(true),
+        "archive should succeed: {archive}"
+    );
+
+    let listed_ids = |resp: &serde_json::Value| {
+        resp.get("result")
+            .and_then(|v| v.get("items"))
+            .and_then(|v| v.as_array())
+            .expect("result.items")
+            .iter()
+            .filter_map(|b| b.get("branch_id").and_then(|v| v.as_str()))
+            .map(ToOwned::to_owned)
+            .collect::<Vec<_>>()
+    };
+    let active = call_markdown_tool(&mut server, 163, "branch", workspace, "```bm\nlist\n```");
+    assert!(!listed_ids(&active).contains(&"exp".to_string()));
+    let archived = call_markdown_tool(
+        &mut server,
+        164,
+        "branch",
+        workspace,
+        "```bm\nlist archived=true\n```",
+    );
+    assert_eq!(listed_ids(&archived), vec!["exp".to_string()]);
+
+    let commit = call_markdown_tool(
+        &mut server,
+        165,
+        "think",
+        workspace,
+        "```bm\ncommit branch=exp commit=c1 message=late\n```",
+    );
+    assert_eq!(
+        commit
+            .get("error")
+            .and_then(|v| v.get("code"))
+            .and_then(|v| v.as_str()),
+        Some("INVALID_INPUT")
+    );
+
+    let unarchive = call_markdown_tool(
+        &mut server,
+        166,
+        "branch",
+        workspace,
+        "```bm\nunarchive branch=exp\n```",
+    );
+    assert_eq!(
+        unarchive.get("success").and_then(|v| v.as_bool()),
+        Some(true)
+    );
+    let active = call_markdown_tool(&mut server, 167, "branch", workspace, "```bm\nlist\n```");
+    assert!(listed_ids(&active).contains(&"exp".to_string()));
+}
+
+#[test]
+fn think_diff_shows_word_changes_between_source_and_amended_commit() {
+    let mut server = Server::start_initialized("think_diff_amend");
+    let workspace = "ws-think-diff";
+
+    let main = call_markdown_tool(&mut server, 170, "branch", workspace, "```bm\nmain\n```");
+    assert_eq!(main.get("success").and_then(|v| v.as_bool()), Some(true));
+    let commit = call_markdown_tool(
+        &mut server,
+        171,
+        "think",
+        workspace,
+        "```bm\ncommit branch=main commit=c1 message=decision\nuse sqlite for the cache\n```",
+    );
+    assert_eq!(commit.get("success").and_then(|v| v.as_bool()), Some(true));
+    let amend = call_markdown_tool(
+        &mut server,
+        172,
+        "think",
+        workspace,
+        "```bm\namend commit=c1 new_commit=c1b\nuse redis for the cache\n```",
+    );
+    assert_eq!(amend.get("success").and_then(|v| v.as_bool()), Some(true));
+
+    let diff = call_markdown_tool(
+        &mut server,
+        173,
+        "think",
+        workspace,
+        "```bm\ndiff from=c1 to=c1b\n```",
+    );
+    let result = diff.get("result").expect("result");
+    assert_eq!(
+        result.get("message_changed").and_then(|v| v.as_bool()),
+        Some(false)
+    );
+    assert_eq!(
+        result.get("markdown").and_then(|v| v.as_str()),
+        Some("use ~~sqlite~~**redis** for the cache"),
+        "diff: {diff}"
+    );
+}
+
+#[test]
+fn unknown_branch_errors_suggest_closest_branch_names() {
+    let mut server = Server::start_initialized("unknown_branch_hints");
+    let workspace = "ws-branch-hints";
+
+    let main = call_markdown_tool(&mut server, 180, "branch", workspace, "```bm\nmain\n```");
+    assert_eq!(main.get("success").and_then(|v| v.as_bool()), Some(true));
+    let create = call_markdown_tool(
+        &mut server,
+        181,
+        "branch",
+        workspace,
+        "```bm\ncreate branch=feature-auth from=main\n```",
+    );
+    assert_eq!(create.get("success").and_then(|v| v.as_bool()), Some(true));
+
+    let commit = call_markdown_tool(
+        &mut server,
+        182,
+        "think",
+        workspace,
+        "```bm\ncommit branch=feature-aut commit=c1 message=typo\n```",
+    );
+    assert_eq!(
+        commit
+            .get("error")
+            .and_then(|v| v.get("hints"))
+            .and_then(|v| v.get("closest_branches")),
+        Some(&json!(["feature-auth"])),
+        "unknown branch should carry suggestions: {commit}"
+    );
+
+    let log = call_markdown_tool(
+        &mut server,
+        183,
+        "think",
+        workspace,
+        "```bm\nlog branch=mian\n```",
+    );
+    assert_eq!(
+        log.get("error")
+            .and_then(|v| v.get("hints"))
+            .and_then(|v| v.get("closest_branches")),
+        Some(&json!(["main"]))
+    );
+}
+
+#[test]
+fn branch_create_with_ttl_marks_scratch_branch_in_list() {
+    let mut server = Server::start_initialized("branch_scratch_ttl");
+    let workspace = "ws-branch-scratch";
+
+    let main = call_markdown_tool(&mut server, 190, "branch", workspace, "```bm\nmain\n```");
+    assert_eq!(main.get("success").and_then(|v| v.as_bool()), Some(true));
+    let create = call_markdown_tool(
+        &mut server,
+        191,
+        "branch",
+        workspace,
+        "```bm\ncreate branch=what-if from=main ttl_ms=3600000\n```",
+    );
+    assert_eq!(
+        create.get("success").and_then(|v| v.as_bool()),
+        Some(true),
+        "scratch create should succeed: {create}"
+    );
+
+    let listed = call_markdown_tool(&mut server, 192, "branch", workspace, "```bm\nlist\n```");
+    let items = listed
+        .get("result")
+        .and_then(|v| v.get("items"))
+        .and_then(|v| v.as_array())
+        .expect("result.items");
+    let flagged = |name: &str| {
+        items
+            .iter()
+            .find(|b| b.get("branch_id").and_then(|v| v.as_str()) == Some(name))
+            .and_then(|b| b.get("scratch_expires_at_ms"))
+            .is_some()
+    };
+    assert!(flagged("what-if"));
+    assert!(!flagged("main"));
+
+    let pruned = call_markdown_tool(&mut server, 193, "branch", workspace, "```bm\nprune\n```");
+    assert_eq!(
+        pruned
+            .get("result")
+            .and_then(|v| v.get("deleted"))
+            .and_then(|v| v.as_array())
+            .map(Vec::len),
+        Some(0),
+        "unexpired scratch branch must survive prune: {pruned}"
+    );
+
+    let bad = call_markdown_tool(
+        &mut server,
+        194,
+        "branch",
+        workspace,
+        "```bm\ncreate branch=bad from=main ttl_ms=0\n```",
+    );
+    assert_eq!(
+        bad.get("error")
+            .and_then(|v| v.get("code"))
+            .and_then(|v| v.as_str()),
+        Some("INVALID_INPUT")
+    );
+}
+
+#[test]
+fn think_commit_expands_saved_template() {
+    let mut server = Server::start_initialized("think_template_commit");
+    let workspace = "ws-think-template";
+
+    let main = call_markdown_tool(&mut server, 200, "branch", workspace, "```bm\nmain\n```");
+    assert_eq!(main.get("success").and_then(|v| v.as_bool()), Some(true));
+    let saved = call_markdown_tool(
+        &mut server,
+        201,
+        "think",
+        workspace,
+        "```bm\ntemplate name=summary\nDecision: {{decision}}\nWhy: {{why}}\n```",
+    );
+    assert_eq!(
+        saved
+            .get("result")
+            .and_then(|v| v.get("placeholders"))
+            .cloned(),
+        Some(serde_json::json!(["decision", "why"])),
+        "template should save: {saved}"
+    );
+
+    let commit = call_markdown_tool(
+        &mut server,
+        202,
+        "think",
+        workspace,
+        "```bm\ncommit branch=main commit=s1 message=summary template=summary var_decision=sqlite var_why=\"embedded, no server\"\n```",
+    );
+    let commit = commit
+        .get("result")
+        .and_then(|v| v.get("commit"))
+        .cloned()
+        .unwrap_or_else(|| panic!("templated commit should succeed: {commit}"));
+    assert_eq!(
+        commit.get("body").and_then(|v| v.as_str()),
+        Some("Decision: sqlite\nWhy: embedded, no server")
+    );
+    assert_eq!(
+        commit.get("template").and_then(|v| v.as_str()),
+        Some("summary")
+    );
+
+    let stray = call_markdown_tool(
+        &mut server,
+        203,
+        "think",
+        workspace,
+        "```bm\ncommit branch=main commit=s2 message=x var_decision=y\n```",
+    );
+    assert_eq!(
+        stray
+            .get("error")
+            .and_then(|v| v.get("code"))
+            .and_then(|v| v.as_str()),
+        Some("INVALID_INPUT")
+    );
+}
+
+#[test]
+fn think_ack_counts_surface_in_show() {
+    let mut server = Server::start_initialized("think_ack_show");
+    let workspace = "ws-think-ack";
+
+    let main = call_markdown_tool(&mut server, 210, "branch", workspace, "```bm\nmain\n```");
+    assert_eq!(main.get("success").and_then(|v| v.as_bool()), Some(true));
+    let commit = call_markdown_tool(
+        &mut server,
+        211,
+        "think",
+        workspace,
+        "```bm\ncommit branch=main commit=c1 message=conclusion\n```",
+    );
+    assert_eq!(commit.get("success").and_then(|v| v.as_bool()), Some(true));
+
+    for (id, md) in [
+        (212, "```bm\nack commit=c1 actor=critic\n```"),
+        (213, "```bm\nack commit=c1 actor=critic kind=agree\n```"),
+        (
+            214,
+            "```bm\nack commit=c1 actor=reviewer kind=disagree\n```",
+        ),
+    ] {
+        let resp = call_markdown_tool(&mut server, id, "think", workspace, md);
+        assert_eq!(
+            resp.get("success").and_then(|v| v.as_bool()),
+            Some(true),
+            "ack should succeed: {resp}"
+        );
+    }
+
+    let show = call_markdown_tool(
+        &mut server,
+        215,
+        "think",
+        workspace,
+        "```bm\nshow commit=c1\n```",
+    );
+    assert_eq!(
+        show.get("result")
+            .and_then(|v| v.get("commit"))
+            .and_then(|v| v.get("acks"))
+            .cloned(),
+        Some(serde_json::json!({ "seen": 1, "agree": 1, "disagree": 1 }))
+    );
+
+    let bad = call_markdown_tool(
+        &mut server,
+        216,
+        "think",
+        workspace,
+        "```bm\nack commit=c1 actor=critic kind=maybe\n```",
+    );
+    assert_eq!(
+        bad.get("error")
+            .and_then(|v| v.get("code"))
+            .and_then(|v| v.as_str()),
+        Some("INVALID_INPUT")
+    );
+}
@@ -60,6 +60,31 @@ fn unknown_tools_fail_closed() {
     );
 }
 
+#[test]
+fn unknown_tool_with_invalid_format_still_fails_closed() {
+    let mut server = Server::start_initialized_with_args("v3_surface_unknown_tool_format", &[]);
+
+    let unsupported_tool = server.request(json!({
+        "jsonrpc": "2.0",
+        "id": 2,
+        "method": "tools/call",
+        "params": {
+            "name": "status",
+            "arguments": { "format": "yaml" }
+        }
+    }));
+    let payload = extract_tool_text(&unsupported_tool);
+    let code = payload
+        .get("error")
+        .and_then(|v| v.get("code"))
+        .and_then(|v| v.as_str());
+    assert_eq!(
+        code,
+        Some("UNKNOWN_TOOL"),
+        "tool name must be resolved before format is parsed"
+    );
+}
+
 #[test]
 fn namespaced_tool_names_fail_closed() {
     let mut server = Server::start_initialized_with_args("v3_surface_unknown_namespaced_tool", &[]);
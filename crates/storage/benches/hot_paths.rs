@@ -0,0 +1,82 @@
+//! Wall-clock timings of the store's hot paths: `cargo bench -p bm_storage`.
+//!
+//! Plain `std::time` harness so no benchmark framework is pulled into the dependency set.
+
+use bm_storage::{
+    AppendCommitRequest, CreateBranchRequest, ListBranchesRequest, ShowCommitRequest, SqliteStore,
+};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const COMMITS: usize = 2_000;
+
+fn main() {
+    let mut dir = std::env::temp_dir();
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("clock should be after the epoch")
+        .as_nanos();
+    dir.push(format!("bm-storage-bench-{}-{nanos}", std::process::id()));
+    let mut store = SqliteStore::open(&dir).expect("bench storage should open");
+    store
+        .create_branch(CreateBranchRequest {
+            workspace_id: "ws-bench".to_string(),
+            branch_id: "main".to_string(),
+            parent_branch_id: None,
+            created_at_ms: 1,
+        })
+        .expect("branch should be created");
+
+    let started = Instant::now();
+    for idx in 0..COMMITS {
+        store
+            .append_commit(AppendCommitRequest {
+                workspace_id: "ws-bench".to_string(),
+                branch_id: "main".to_string(),
+                commit_id: format!("c{idx}"),
+                parent_commit_id: None,
+                expected_head_commit_id: None,
+                message: "bench".to_string(),
+                body: "body".to_string(),
+                author: Some("bench".to_string()),
+                created_at_ms: 2 + idx as i64,
+            })
+            .expect("commit should append");
+    }
+    report("append_commit", started.elapsed(), COMMITS);
+
+    let started = Instant::now();
+    let mut cursor = Some(format!("c{}", COMMITS - 1));
+    let mut walked = 0;
+    while let Some(commit_id) = cursor {
+        let commit = store
+            .show_commit(ShowCommitRequest {
+                workspace_id: "ws-bench".to_string(),
+                commit_id,
+            })
+            .expect("show should run")
+            .expect("commit must exist");
+        cursor = commit.parent_commit_id().map(ToOwned::to_owned);
+        walked += 1;
+    }
+    report("show_commit (log walk)", started.elapsed(), walked);
+
+    let started = Instant::now();
+    for _ in 0..COMMITS {
+        store
+            .list_branches(ListBranchesRequest {
+                workspace_id: "ws-bench".to_string(),
+                limit: 50,
+                offset: 0,
+            })
+            .expect("branches should list");
+    }
+    report("list_branches", started.elapsed(), COMMITS);
+
+    drop(store);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+fn report(name: &str, elapsed: Duration, ops: usize) {
+    let per_op = elapsed.as_nanos() / u128::try_from(ops.max(1)).unwrap_or(1);
+    println!("{name:<24} {ops:>6} ops  {per_op:>9} ns/op");
+}
@@ -0,0 +1,96 @@
+#![forbid(unsafe_code)]
+
+use super::{
+    AckKind, CommitAckRequest, ShowCommitRequest, SqliteStore, StoreError, audit::audit_tx,
+    canonicalize_author, canonicalize_commit, canonicalize_workspace, ensure_commit_exists_tx,
+};
+use rusqlite::params;
+
+/// Number of distinct actors per acknowledgement kind on one commit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AckCounts {
+    pub seen: u64,
+    pub agree: u64,
+    pub disagree: u64,
+}
+
+impl AckKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AckKind::Seen => "seen",
+            AckKind::Agree => "agree",
+            AckKind::Disagree => "disagree",
+        }
+    }
+}
+
+impl SqliteStore {
+    /// Records that `actor` acknowledged a commit. Returns `true` when the record is new.
+    ///
+    /// An actor holds at most one verdict per commit: `agree` replaces an earlier `disagree`
+    /// and the other way round. `seen` is independent of the verdict.
+    pub fn commit_ack(&mut self, request: CommitAckRequest) -> Result<bool, StoreError> {
+        let workspace_id = canonicalize_workspace(&request.workspace_id)?;
+        let commit_id = canonicalize_commit(&request.commit_id)?;
+        let actor = canonicalize_author(&request.actor)?;
+
+        let tx = self.write_tx()?;
+        ensure_commit_exists_tx(&tx, &workspace_id, &commit_id)?;
+
+        let opposite = match request.kind {
+            AckKind::Seen => None,
+            AckKind::Agree => Some(AckKind::Disagree),
+            AckKind::Disagree => Some(AckKind::Agree),
+        };
+        if let Some(opposite) = opposite {
+            tx.execute(
+                "DELETE FROM commit_acks WHERE workspace=?1 AND commit_id=?2 AND actor=?3 AND kind=?4",
+                params![workspace_id, commit_id, actor, opposite.as_str()],
+            )?;
+        }
+        let changed = tx.execute(
+            "INSERT OR IGNORE INTO commit_acks(workspace, commit_id, actor, kind, acked_at_ms) \
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                workspace_id,
+                commit_id,
+                actor,
+                request.kind.as_str(),
+                request.acked_at_ms
+            ],
+        )?;
+        if changed > 0 {
+            audit_tx(
+                &tx,
+                &workspace_id,
+                &format!("commit.ack.{}", request.kind.as_str()),
+                &commit_id,
+                request.acked_at_ms,
+            )?;
+        }
+
+        self.commit_tx(tx)?;
+        Ok(changed > 0)
+    }
+
+    pub fn commit_ack_counts(&self, request: ShowCommitRequest) -> Result<AckCounts, StoreError> {
+        let workspace_id = canonicalize_workspace(&request.workspace_id)?;
+        let commit_id = canonicalize_commit(&request.commit_id)?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT kind, COUNT(1) FROM commit_acks WHERE workspace=?1 AND commit_id=?2 GROUP BY kind",
+        )?;
+        let mut rows = stmt.query(params![workspace_id, commit_id])?;
+        let mut counts = AckCounts::default();
+        while let Some(row) = rows.next()? {
+            let count = u64::try_from(row.get::<_, i64>(1)?).unwrap_or(0);
+            match row.get::<_, String>(0)?.as_str() {
+                "seen" => counts.seen = count,
+                "agree" => counts.agree = count,
+                "disagree" => counts.disagree = count,
+                _ => {}
+            }
+        }
+        Ok(counts)
+    }
+}
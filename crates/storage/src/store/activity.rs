@@ -0,0 +1,69 @@
+#![forbid(unsafe_code)]
+
+use super::{
+    ActivityAggregateRequest, ActivityBucket, ActivityGroupBy, SqliteStore, StoreError,
+    canonicalize_workspace,
+};
+use rusqlite::params;
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+const WEEK_MS: i64 = 7 * DAY_MS;
+/// The Unix epoch fell on a Thursday; shifting by three days aligns weeks to Monday.
+const WEEK_ALIGN_MS: i64 = 3 * DAY_MS;
+
+/// Commit activity for one bucket and group.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ActivityRow {
+    pub bucket_start_ms: i64,
+    /// Branch id or author; `None` for commits without a recorded author.
+    pub key: Option<String>,
+    pub commits: u64,
+    /// UTF-8 bytes of commit messages and bodies.
+    pub bytes: u64,
+}
+
+impl SqliteStore {
+    /// Counts commits and their text volume per time bucket, grouped by branch or author.
+    ///
+    /// Rows are ordered by bucket, then key.
+    pub fn activity_aggregate(
+        &self,
+        request: ActivityAggregateRequest,
+    ) -> Result<Vec<ActivityRow>, StoreError> {
+        let workspace_id = canonicalize_workspace(&request.workspace_id)?;
+        let (width_ms, align_ms) = match request.bucket {
+            ActivityBucket::Day => (DAY_MS, 0),
+            ActivityBucket::Week => (WEEK_MS, WEEK_ALIGN_MS),
+        };
+        let key_expr = match request.group_by {
+            ActivityGroupBy::Branch => "c.branch",
+            ActivityGroupBy::Author => "a.author",
+        };
+
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT ((c.created_at_ms + ?2) / ?3) * ?3 - ?2 AS bucket_start, {key_expr} AS key, \
+                    COUNT(1), SUM(length(CAST(c.message AS BLOB)) + length(CAST(c.body AS BLOB))) \
+             FROM commits c \
+             LEFT JOIN commit_authors a ON a.workspace=c.workspace AND a.commit_id=c.commit_id \
+             WHERE c.workspace=?1 AND c.created_at_ms >= ?4 \
+             GROUP BY bucket_start, key \
+             ORDER BY bucket_start ASC, key ASC"
+        ))?;
+        let mut rows = stmt.query(params![
+            workspace_id,
+            align_ms,
+            width_ms,
+            request.since_ms.unwrap_or(i64::MIN)
+        ])?;
+        let mut out = Vec::new();
+        while let Some(row) = rows.next()? {
+            out.push(ActivityRow {
+                bucket_start_ms: row.get(0)?,
+                key: row.get(1)?,
+                commits: u64::try_from(row.get::<_, i64>(2)?).unwrap_or(0),
+                bytes: u64::try_from(row.get::<_, i64>(3)?).unwrap_or(0),
+            });
+        }
+        Ok(out)
+    }
+}
@@ -0,0 +1,138 @@
+#![forbid(unsafe_code)]
+
+use super::{
+    ArchiveBranchRequest, ListBranchesRequest, SqliteStore, StoreError, audit::audit_tx,
+    canonicalize_branch, canonicalize_workspace, ensure_branch_exists_tx, ensure_unlocked,
+};
+use bm_core::ThoughtBranch;
+use rusqlite::{Connection, OptionalExtension, params};
+
+const COUNT_ACTIVE_CHILDREN_SQL: &str = "SELECT COUNT(1) FROM branches b \
+     WHERE b.workspace=?1 AND b.parent_branch_id=?2 \
+       AND NOT EXISTS (SELECT 1 FROM branch_archive a WHERE a.workspace=b.workspace AND a.branch=b.name)";
+
+const DELETE_BRANCH_ARCHIVE_SQL: &str =
+    "DELETE FROM branch_archive WHERE workspace=?1 AND branch=?2";
+
+const INSERT_BRANCH_ARCHIVE_SQL: &str =
+    "INSERT OR IGNORE INTO branch_archive(workspace, branch, archived_at_ms) VALUES (?1, ?2, ?3)";
+
+impl SqliteStore {
+    /// Hides a branch from listings and freezes it against writes. Returns `true` when the
+    /// branch was not archived before.
+    ///
+    /// History is kept in place, so archiving frees no space. Archiving is refused for the
+    /// checked-out branch and for branches that still have active children.
+    pub fn branch_archive(&mut self, request: ArchiveBranchRequest) -> Result<bool, StoreError> {
+        let workspace_id = canonicalize_workspace(&request.workspace_id)?;
+        let branch_id = canonicalize_branch(&request.branch_id)?;
+
+        let tx = self.write_tx()?;
+        ensure_unlocked(&tx, &workspace_id, self.own_lock_holder(&workspace_id))?;
+        ensure_branch_exists_tx(&tx, &workspace_id, &branch_id)?;
+
+        let checked_out = tx
+            .prepare_cached("SELECT 1 FROM branch_checkout WHERE workspace=?1 AND branch=?2")?
+            .query_row(params![workspace_id, branch_id], |row| row.get::<_, i64>(0))
+            .optional()?;
+        if checked_out.is_some() {
+            return Err(StoreError::InvalidInput(
+                "cannot archive the checked-out branch",
+            ));
+        }
+
+        let active_children = {
+            let mut stmt = tx.prepare_cached(COUNT_ACTIVE_CHILDREN_SQL)?;
+            stmt.query_row(params![workspace_id, branch_id], |row| row.get::<_, i64>(0))?
+        };
+        if active_children > 0 {
+            return Err(StoreError::InvalidInput(
+                "branch has active child branches and cannot be archived",
+            ));
+        }
+
+        let changed = {
+            let mut stmt = tx.prepare_cached(INSERT_BRANCH_ARCHIVE_SQL)?;
+            stmt.execute(params![workspace_id, branch_id, request.at_ms])?
+        };
+        if changed > 0 {
+            audit_tx(
+                &tx,
+                &workspace_id,
+                "branch.archive",
+                &branch_id,
+                request.at_ms,
+            )?;
+        }
+
+        self.commit_tx(tx)?;
+        Ok(changed > 0)
+    }
+
+    /// Restores an archived branch. Returns `true` when the branch was archived before.
+    ///
+    /// A branch whose parent is still archived must wait for the parent to be restored.
+    pub fn branch_unarchive(&mut self, request: ArchiveBranchRequest) -> Result<bool, StoreError> {
+        let workspace_id = canonicalize_workspace(&request.workspace_id)?;
+        let branch_id = canonicalize_branch(&request.branch_id)?;
+
+        let tx = self.write_tx()?;
+        ensure_unlocked(&tx, &workspace_id, self.own_lock_holder(&workspace_id))?;
+        ensure_branch_exists_tx(&tx, &workspace_id, &branch_id)?;
+
+        let parent_archived = tx
+            .prepare_cached(
+                "SELECT 1 FROM branches b \
+                 JOIN branch_archive a ON a.workspace=b.workspace AND a.branch=b.parent_branch_id \
+                 WHERE b.workspace=?1 AND b.name=?2",
+            )?
+            .query_row(params![workspace_id, branch_id], |row| row.get::<_, i64>(0))
+            .optional()?;
+        if parent_archived.is_some() {
+            return Err(StoreError::InvalidInput(
+                "parent branch is archived; unarchive it first",
+            ));
+        }
+
+        let changed = {
+            let mut stmt = tx.prepare_cached(DELETE_BRANCH_ARCHIVE_SQL)?;
+            stmt.execute(params![workspace_id, branch_id])?
+        };
+        if changed > 0 {
+            audit_tx(
+                &tx,
+                &workspace_id,
+                "branch.unarchive",
+                &branch_id,
+                request.at_ms,
+            )?;
+        }
+
+        self.commit_tx(tx)?;
+        Ok(changed > 0)
+    }
+
+    /// Lists archived branches in the same order as `list_branches`.
+    pub fn list_archived_branches(
+        &self,
+        request: ListBranchesRequest,
+    ) -> Result<Vec<ThoughtBranch>, StoreError> {
+        self.query_branches(request, true)
+    }
+}
+
+/// Rejects writes that would touch an archived branch.
+pub(super) fn ensure_branch_active_tx(
+    conn: &Connection,
+    workspace_id: &str,
+    branch_id: &str,
+) -> Result<(), StoreError> {
+    let archived = conn
+        .prepare_cached("SELECT 1 FROM branch_archive WHERE workspace=?1 AND branch=?2")?
+        .query_row(params![workspace_id, branch_id], |row| row.get::<_, i64>(0))
+        .optional()?;
+    if archived.is_some() {
+        return Err(StoreError::InvalidInput("branch is archived"));
+    }
+    Ok(())
+}
@@ -0,0 +1,327 @@
+#![forbid(unsafe_code)]
+
+use super::{SqliteStore, StoreError, canonicalize_workspace};
+use bm_core::ThoughtCommit;
+use rusqlite::{OptionalExtension, Transaction, params};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeSet, HashMap};
+
+/// `prev_hash` of the first record in every workspace chain.
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+const INSERT_AUDIT_RECORD_SQL: &str = "INSERT INTO audit_log(workspace, seq, at_ms, op, subject, prev_hash, hash, digest, branch) \
+     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)";
+
+const UPSERT_AUDIT_HEAD_SQL: &str = "INSERT INTO audit_head(workspace, seq, hash) VALUES (?1, ?2, ?3) \
+     ON CONFLICT(workspace) DO UPDATE SET seq=excluded.seq, hash=excluded.hash";
+
+/// Result of re-walking a workspace audit chain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuditVerification {
+    pub records: u64,
+    /// Hash of the last record; record it externally to detect rewrites of the whole chain.
+    pub head_hash: Option<String>,
+    /// First record whose sequence, link or hash does not match, or whose commit no longer
+    /// matches the content digest it was recorded with or is gone without a branch deletion
+    /// covering it, if any.
+    pub broken_at_seq: Option<u64>,
+    /// The chain ends before the recorded head, i.e. trailing records were removed, or the
+    /// chain is empty while the workspace has commits.
+    pub truncated: bool,
+}
+
+impl AuditVerification {
+    pub fn is_intact(&self) -> bool {
+        self.broken_at_seq.is_none() && !self.truncated
+    }
+}
+
+impl SqliteStore {
+    /// Recomputes the hash chain of a workspace audit log and reports the first
+    /// inconsistency: a missing sequence number, a broken `prev_hash` link, a record whose
+    /// content no longer matches its hash, a commit edited or removed since it was appended,
+    /// or a chain shorter than the recorded head.
+    ///
+    /// Commits redacted through the store, or removed with their branch by a later
+    /// `branch.delete` / `branch.prune` record, are not compared against their digest.
+    pub fn audit_verify(&self, workspace_id: &str) -> Result<AuditVerification, StoreError> {
+        let workspace_id = canonicalize_workspace(workspace_id)?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT seq, at_ms, op, subject, prev_hash, hash, digest, branch FROM audit_log \
+             WHERE workspace=?1 ORDER BY seq ASC",
+        )?;
+        let mut rows = stmt.query(params![workspace_id])?;
+        let mut records = 0u64;
+        let mut expected_prev = GENESIS_HASH.to_string();
+        let mut broken_at_seq = None;
+        let mut content_checks = Vec::new();
+        let mut redacted = BTreeSet::new();
+        let mut branch_removed_at = HashMap::new();
+        while let Some(row) = rows.next()? {
+            let seq = row.get::<_, i64>(0)?;
+            let at_ms = row.get::<_, i64>(1)?;
+            let op = row.get::<_, String>(2)?;
+            let subject = row.get::<_, String>(3)?;
+            let prev_hash = row.get::<_, String>(4)?;
+            let hash = row.get::<_, String>(5)?;
+            let digest = row.get::<_, Option<String>>(6)?;
+            let branch = row.get::<_, Option<String>>(7)?;
+
+            records += 1;
+            let expected_seq = i64::try_from(records).unwrap_or(i64::MAX);
+            if broken_at_seq.is_none()
+                && (seq != expected_seq
+                    || prev_hash != expected_prev
+                    || hash
+                        != record_hash(
+                            &prev_hash,
+                            seq,
+                            at_ms,
+                            &op,
+                            &subject,
+                            digest.as_deref(),
+                            branch.as_deref(),
+                        ))
+            {
+                broken_at_seq = Some(records);
+            }
+            match op.as_str() {
+                "commit.redact" => {
+                    redacted.insert(subject.clone());
+                }
+                "branch.delete" | "branch.prune" => {
+                    branch_removed_at.insert(subject.clone(), records);
+                }
+                _ => {}
+            }
+            if let Some(digest) = digest {
+                content_checks.push((records, op, subject, digest, branch));
+            }
+            expected_prev = hash;
+        }
+
+        for (record, op, subject, digest, branch) in content_checks {
+            if broken_at_seq.is_some_and(|broken| broken <= record) {
+                break;
+            }
+            let removed_with_branch = branch
+                .as_ref()
+                .and_then(|branch| branch_removed_at.get(branch))
+                .is_some_and(|removed| *removed > record);
+            if removed_with_branch {
+                continue;
+            }
+            let current = match self.audited_commit_id(&workspace_id, &op, &subject)? {
+                Some(commit_id) if redacted.contains(&commit_id) => continue,
+                Some(commit_id) => self
+                    .conn
+                    .prepare_cached(
+                        "SELECT branch, parent_commit_id, message, body, created_at_ms \
+                         FROM commits WHERE workspace=?1 AND commit_id=?2",
+                    )?
+                    .query_row(params![workspace_id, commit_id], |row| {
+                        Ok(content_digest(
+                            &row.get::<_, String>(0)?,
+                            row.get::<_, Option<String>>(1)?.as_deref(),
+                            &row.get::<_, String>(2)?,
+                            &row.get::<_, String>(3)?,
+                            row.get::<_, i64>(4)?,
+                        ))
+                    })
+                    .optional()?,
+                // Removing either branch of a merge drops its merge record, so a missing
+                // record is explained by any later branch removal.
+                None if branch_removed_at.values().any(|removed| *removed > record) => continue,
+                None => None,
+            };
+            if current.is_none_or(|current| current != digest) {
+                broken_at_seq = Some(record);
+                break;
+            }
+        }
+
+        let head = self
+            .conn
+            .prepare_cached("SELECT seq, hash FROM audit_head WHERE workspace=?1")?
+            .query_row(params![workspace_id], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+            })
+            .optional()?;
+        let head_hash = (records > 0).then_some(expected_prev);
+        let truncated = match &head {
+            Some((seq, hash)) => {
+                u64::try_from(*seq).unwrap_or(0) > records || head_hash.as_ref() != Some(hash)
+            }
+            None => records == 0 && self.workspace_has_commits(&workspace_id)?,
+        };
+
+        Ok(AuditVerification {
+            records,
+            head_hash,
+            broken_at_seq,
+            truncated,
+        })
+    }
+
+    /// Commit whose content an audit record with a digest vouches for.
+    fn audited_commit_id(
+        &self,
+        workspace_id: &str,
+        op: &str,
+        subject: &str,
+    ) -> Result<Option<String>, StoreError> {
+        match op {
+            "merge.create" => Ok(self
+                .conn
+                .prepare_cached(
+                    "SELECT synthesis_commit_id FROM merge_records \
+                     WHERE workspace=?1 AND merge_id=?2",
+                )?
+                .query_row(params![workspace_id, subject], |row| row.get(0))
+                .optional()?),
+            _ => Ok(Some(subject.to_string())),
+        }
+    }
+
+    fn workspace_has_commits(&self, workspace_id: &str) -> Result<bool, StoreError> {
+        Ok(self
+            .conn
+            .prepare_cached("SELECT EXISTS(SELECT 1 FROM commits WHERE workspace=?1)")?
+            .query_row(params![workspace_id], |row| row.get(0))?)
+    }
+}
+
+/// Appends one record to the workspace audit chain inside the caller's write transaction.
+pub(super) fn audit_tx(
+    tx: &Transaction<'_>,
+    workspace_id: &str,
+    op: &str,
+    subject: &str,
+    at_ms: i64,
+) -> Result<(), StoreError> {
+    append_record_tx(tx, workspace_id, op, subject, None, at_ms)
+}
+
+/// Content a record vouches for: the commit's digest and the branch it was written to, so a
+/// later removal of that branch explains the commit going missing.
+struct RecordContent<'a> {
+    digest: &'a str,
+    branch: &'a str,
+}
+
+/// Like `audit_tx`, but also chains the content digest and branch of the commit the record
+/// creates, so `audit_verify` notices when that commit is edited or removed later.
+pub(super) fn audit_commit_tx(
+    tx: &Transaction<'_>,
+    workspace_id: &str,
+    op: &str,
+    subject: &str,
+    commit: &ThoughtCommit,
+    at_ms: i64,
+) -> Result<(), StoreError> {
+    let digest = content_digest(
+        commit.branch_id(),
+        commit.parent_commit_id(),
+        commit.message(),
+        commit.body(),
+        commit.created_at_ms(),
+    );
+    let content = RecordContent {
+        digest: &digest,
+        branch: commit.branch_id(),
+    };
+    append_record_tx(tx, workspace_id, op, subject, Some(content), at_ms)
+}
+
+fn append_record_tx(
+    tx: &Transaction<'_>,
+    workspace_id: &str,
+    op: &str,
+    subject: &str,
+    content: Option<RecordContent<'_>>,
+    at_ms: i64,
+) -> Result<(), StoreError> {
+    let digest = content.as_ref().map(|content| content.digest);
+    let branch = content.as_ref().map(|content| content.branch);
+    let head = tx
+        .prepare_cached("SELECT seq, hash FROM audit_head WHERE workspace=?1")?
+        .query_row(params![workspace_id], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })
+        .optional()?;
+    let (seq, prev_hash) = match head {
+        Some((seq, hash)) => (seq + 1, hash),
+        None => (1, GENESIS_HASH.to_string()),
+    };
+    let hash = record_hash(&prev_hash, seq, at_ms, op, subject, digest, branch);
+
+    let mut stmt = tx.prepare_cached(INSERT_AUDIT_RECORD_SQL)?;
+    stmt.execute(params![
+        workspace_id,
+        seq,
+        at_ms,
+        op,
+        subject,
+        prev_hash,
+        hash,
+        digest,
+        branch
+    ])?;
+    let mut stmt = tx.prepare_cached(UPSERT_AUDIT_HEAD_SQL)?;
+    stmt.execute(params![workspace_id, seq, hash])?;
+    Ok(())
+}
+
+fn record_hash(
+    prev_hash: &str,
+    seq: i64,
+    at_ms: i64,
+    op: &str,
+    subject: &str,
+    digest: Option<&str>,
+    branch: Option<&str>,
+) -> String {
+    let mut hasher = Sha256::new();
+    for part in [prev_hash, &seq.to_string(), &at_ms.to_string(), op, subject]
+        .into_iter()
+        .chain(digest)
+        .chain(branch)
+    {
+        hasher.update(part.as_bytes());
+        hasher.update(b"\n");
+    }
+    hex(hasher)
+}
+
+/// Digest of where a commit sits and what it says. Each part is length-prefixed so text
+/// cannot move between parts without changing the digest.
+fn content_digest(
+    branch: &str,
+    parent_commit_id: Option<&str>,
+    message: &str,
+    body: &str,
+    created_at_ms: i64,
+) -> String {
+    let mut hasher = Sha256::new();
+    let created_at_ms = created_at_ms.to_string();
+    for part in [
+        branch,
+        parent_commit_id.unwrap_or(""),
+        message,
+        body,
+        &created_at_ms,
+    ] {
+        hasher.update((part.len() as u64).to_le_bytes());
+        hasher.update(part.as_bytes());
+    }
+    hex(hasher)
+}
+
+fn hex(hasher: Sha256) -> String {
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
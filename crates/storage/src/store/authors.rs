@@ -0,0 +1,42 @@
+#![forbid(unsafe_code)]
+
+use super::{
+    ShowCommitRequest, SqliteStore, StoreError, canonicalize_commit, canonicalize_workspace,
+};
+use rusqlite::{Connection, OptionalExtension, Transaction, params};
+
+const INSERT_COMMIT_AUTHOR_SQL: &str =
+    "INSERT INTO commit_authors(workspace, commit_id, author) VALUES (?1, ?2, ?3)";
+
+impl SqliteStore {
+    /// Returns the writer recorded for a commit, if the commit was attributed.
+    pub fn commit_author(&self, request: ShowCommitRequest) -> Result<Option<String>, StoreError> {
+        let workspace_id = canonicalize_workspace(&request.workspace_id)?;
+        let commit_id = canonicalize_commit(&request.commit_id)?;
+        author_by_commit(&self.conn, &workspace_id, &commit_id)
+    }
+}
+
+pub(super) fn insert_commit_author_tx(
+    tx: &Transaction<'_>,
+    workspace_id: &str,
+    commit_id: &str,
+    author: &str,
+) -> Result<(), StoreError> {
+    let mut stmt = tx.prepare_cached(INSERT_COMMIT_AUTHOR_SQL)?;
+    stmt.execute(params![workspace_id, commit_id, author])?;
+    Ok(())
+}
+
+pub(super) fn author_by_commit(
+    conn: &Connection,
+    workspace_id: &str,
+    commit_id: &str,
+) -> Result<Option<String>, StoreError> {
+    Ok(conn
+        .prepare_cached("SELECT author FROM commit_authors WHERE workspace=?1 AND commit_id=?2")?
+        .query_row(params![workspace_id, commit_id], |row| {
+            row.get::<_, String>(0)
+        })
+        .optional()?)
+}
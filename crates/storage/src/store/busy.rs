@@ -0,0 +1,91 @@
+#![forbid(unsafe_code)]
+
+use super::{SqliteStore, StoreConfig, StoreError};
+use rusqlite::{ErrorCode, Transaction, TransactionBehavior};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const BUSY_BACKOFF_BASE_MS: u64 = 10;
+/// Longest single sleep between retries, jitter included.
+const BUSY_BACKOFF_MAX_MS: u64 = 250;
+/// Time spent retrying on top of `busy_timeout` before giving up, whatever `busy_retries` says.
+const BUSY_RETRY_BUDGET_MS: u64 = 1_000;
+
+impl SqliteStore {
+    /// Sets how long SQLite itself waits on a locked database before a write
+    /// attempt gives up and is retried by the store. Bounded like
+    /// `StoreConfig::busy_timeout`.
+    pub fn set_busy_timeout(&mut self, timeout: Duration) -> Result<(), StoreError> {
+        let config = StoreConfig {
+            busy_timeout: timeout,
+            ..self.config.clone()
+        };
+        config.validate()?;
+        self.conn.busy_timeout(timeout)?;
+        self.config = config;
+        Ok(())
+    }
+
+    /// Begins an IMMEDIATE write transaction so lock contention surfaces before
+    /// any statement runs, retrying with jittered backoff while another writer
+    /// holds the database.
+    pub(super) fn write_tx(&self) -> Result<Transaction<'_>, StoreError> {
+        self.retry_busy(|| Transaction::new_unchecked(&self.conn, TransactionBehavior::Immediate))
+    }
+
+    /// Commits a transaction from `write_tx`. A busy `COMMIT` (e.g. waiting for readers
+    /// to release the database in rollback-journal mode) is retried like `BEGIN`; the
+    /// transaction is rolled back only when the retries run out.
+    pub(super) fn commit_tx(&self, tx: Transaction<'_>) -> Result<(), StoreError> {
+        // A failed COMMIT leaves the transaction open, so it is retried on the same handle;
+        // once it succeeds the connection is back in autocommit and dropping `tx` is a no-op.
+        self.retry_busy(|| tx.execute_batch("COMMIT"))
+    }
+
+    /// Runs `attempt` until it is not busy. No new attempt starts once `busy_timeout`
+    /// plus `BUSY_RETRY_BUDGET_MS` has elapsed, so the wait stays bounded at any
+    /// `busy_retries`; running out maps to `StoreError::Busy`.
+    fn retry_busy<T>(
+        &self,
+        mut attempt: impl FnMut() -> rusqlite::Result<T>,
+    ) -> Result<T, StoreError> {
+        let started = Instant::now();
+        let budget = self.config.busy_timeout + Duration::from_millis(BUSY_RETRY_BUDGET_MS);
+        let mut retries = 0;
+        loop {
+            match attempt() {
+                Ok(value) => return Ok(value),
+                Err(err) if is_busy(&err) => {
+                    let delay = backoff(retries);
+                    if retries >= self.config.busy_retries || started.elapsed() + delay > budget {
+                        let waited_ms =
+                            u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX);
+                        return Err(StoreError::Busy { waited_ms });
+                    }
+                    std::thread::sleep(delay);
+                    retries += 1;
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+}
+
+fn is_busy(err: &rusqlite::Error) -> bool {
+    matches!(
+        err.sqlite_error_code(),
+        Some(ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked)
+    )
+}
+
+fn backoff(attempt: u32) -> Duration {
+    let base = 1u64
+        .checked_shl(attempt)
+        .and_then(|factor| BUSY_BACKOFF_BASE_MS.checked_mul(factor))
+        .unwrap_or(BUSY_BACKOFF_MAX_MS)
+        .min(BUSY_BACKOFF_MAX_MS);
+    let jitter = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| u64::from(elapsed.subsec_nanos()) % base)
+        .unwrap_or(0);
+    Duration::from_millis((base + jitter).min(BUSY_BACKOFF_MAX_MS))
+}
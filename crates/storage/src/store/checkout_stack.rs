@@ -0,0 +1,136 @@
+#![forbid(unsafe_code)]
+
+use super::{
+    CheckoutPopRequest, CheckoutPushRequest, SqliteStore, StoreError, audit::audit_tx,
+    branch_exists_tx, canonicalize_branch, canonicalize_workspace, ensure_branch_active_tx,
+    ensure_workspace_tx, set_checkout_tx,
+};
+use rusqlite::{OptionalExtension, params};
+
+/// Deepest nesting of pushed checkouts; deeper pushes are refused.
+const MAX_CHECKOUT_STACK: i64 = 64;
+
+/// A checkout saved by `branch_checkout_push`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CheckoutFrame {
+    pub branch_id: String,
+    pub pushed_at_ms: i64,
+}
+
+impl SqliteStore {
+    /// Checks out a branch and saves the current checkout so `branch_checkout_pop` can return
+    /// to it. Returns the saved checkout; with no current checkout nothing is saved.
+    pub fn branch_checkout_push(
+        &mut self,
+        request: CheckoutPushRequest,
+    ) -> Result<Option<String>, StoreError> {
+        let workspace_id = canonicalize_workspace(&request.workspace_id)?;
+        let branch_id = canonicalize_branch(&request.branch_id)?;
+
+        let tx = self.write_tx()?;
+        ensure_workspace_tx(&tx, &workspace_id, request.at_ms)?;
+        if !branch_exists_tx(&tx, &workspace_id, &branch_id)? {
+            return Err(StoreError::UnknownBranch);
+        }
+        ensure_branch_active_tx(&tx, &workspace_id, &branch_id)?;
+
+        let previous = set_checkout_tx(&tx, &workspace_id, &branch_id, request.at_ms)?;
+        if let Some(previous) = previous.as_deref() {
+            let depth = tx.query_row(
+                "SELECT COALESCE(MAX(depth), 0) FROM checkout_stack WHERE workspace=?1",
+                params![workspace_id],
+                |row| row.get::<_, i64>(0),
+            )?;
+            if depth >= MAX_CHECKOUT_STACK {
+                return Err(StoreError::InvalidInput("checkout stack is full"));
+            }
+            tx.execute(
+                "INSERT INTO checkout_stack(workspace, depth, branch, pushed_at_ms) \
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![workspace_id, depth + 1, previous, request.at_ms],
+            )?;
+        }
+        audit_tx(
+            &tx,
+            &workspace_id,
+            "branch.checkout.push",
+            &branch_id,
+            request.at_ms,
+        )?;
+
+        self.commit_tx(tx)?;
+        Ok(previous)
+    }
+
+    /// Returns to the most recently saved checkout and removes it from the stack.
+    ///
+    /// Saved branches that were deleted or archived since are dropped on the way down. Returns
+    /// the restored branch, or `None` (checkout unchanged) when nothing usable was saved.
+    pub fn branch_checkout_pop(
+        &mut self,
+        request: CheckoutPopRequest,
+    ) -> Result<Option<String>, StoreError> {
+        let workspace_id = canonicalize_workspace(&request.workspace_id)?;
+
+        let tx = self.write_tx()?;
+        let mut restored = None;
+        while let Some((depth, branch_id)) = tx
+            .query_row(
+                "SELECT depth, branch FROM checkout_stack WHERE workspace=?1 \
+                 ORDER BY depth DESC LIMIT 1",
+                params![workspace_id],
+                |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)),
+            )
+            .optional()?
+        {
+            tx.execute(
+                "DELETE FROM checkout_stack WHERE workspace=?1 AND depth=?2",
+                params![workspace_id, depth],
+            )?;
+            if !branch_exists_tx(&tx, &workspace_id, &branch_id)? {
+                continue;
+            }
+            match ensure_branch_active_tx(&tx, &workspace_id, &branch_id) {
+                Ok(()) => {
+                    restored = Some(branch_id);
+                    break;
+                }
+                Err(StoreError::InvalidInput(_)) => continue,
+                Err(err) => return Err(err),
+            }
+        }
+        if let Some(branch_id) = restored.as_deref() {
+            set_checkout_tx(&tx, &workspace_id, branch_id, request.at_ms)?;
+            audit_tx(
+                &tx,
+                &workspace_id,
+                "branch.checkout.pop",
+                branch_id,
+                request.at_ms,
+            )?;
+        }
+
+        self.commit_tx(tx)?;
+        Ok(restored)
+    }
+
+    /// Lists saved checkouts, most recent first. The current checkout itself is not included;
+    /// see `branch_checkout_get`.
+    pub fn branch_checkout_stack(
+        &self,
+        workspace_id: &str,
+    ) -> Result<Vec<CheckoutFrame>, StoreError> {
+        let workspace_id = canonicalize_workspace(workspace_id)?;
+        let mut stmt = self.conn.prepare(
+            "SELECT branch, pushed_at_ms FROM checkout_stack WHERE workspace=?1 \
+             ORDER BY depth DESC",
+        )?;
+        let rows = stmt.query_map(params![workspace_id], |row| {
+            Ok(CheckoutFrame {
+                branch_id: row.get(0)?,
+                pushed_at_ms: row.get(1)?,
+            })
+        })?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+}
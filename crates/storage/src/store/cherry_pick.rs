@@ -0,0 +1,148 @@
+#![forbid(unsafe_code)]
+
+use super::authors::author_by_commit;
+use super::{
+    AppendCommitRequest, CherryPickRequest, ShowCommitRequest, SqliteStore, StoreError,
+    append_commit_tx, canonicalize_branch, canonicalize_commit, canonicalize_workspace,
+    commit_by_id,
+};
+use bm_core::ThoughtCommit;
+use rusqlite::{OptionalExtension, params};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+
+/// Outcome of a cherry-pick.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CherryPickReport {
+    /// New commits on the target branch, in the order they were appended.
+    pub picked: Vec<ThoughtCommit>,
+    /// Source commits skipped because they were already picked onto the target branch.
+    pub already_picked: Vec<String>,
+}
+
+impl SqliteStore {
+    /// Copies selected commits of one branch onto the head of another, without a merge.
+    ///
+    /// Every listed commit must belong to the source branch. Each copy keeps the original
+    /// message and body, gets a derived id, and records the commit it was picked from. Picking
+    /// the same commit onto the same branch twice is a no-op reported in `already_picked`.
+    pub fn commit_cherry_pick(
+        &mut self,
+        request: CherryPickRequest,
+    ) -> Result<CherryPickReport, StoreError> {
+        let workspace_id = canonicalize_workspace(&request.workspace_id)?;
+        let source_branch_id = canonicalize_branch(&request.source_branch_id)?;
+        let target_branch_id = canonicalize_branch(&request.target_branch_id)?;
+        if source_branch_id == target_branch_id {
+            return Err(StoreError::InvalidInput(
+                "cherry-pick source and target branch must differ",
+            ));
+        }
+        if request.commit_ids.is_empty() {
+            return Err(StoreError::InvalidInput(
+                "cherry-pick needs at least one commit",
+            ));
+        }
+        let mut commit_ids = Vec::with_capacity(request.commit_ids.len());
+        let mut seen = BTreeSet::new();
+        for commit_id in &request.commit_ids {
+            let commit_id = canonicalize_commit(commit_id)?;
+            if seen.insert(commit_id.clone()) {
+                commit_ids.push(commit_id);
+            }
+        }
+
+        let tx = self.write_tx()?;
+        let mut report = CherryPickReport {
+            picked: Vec::new(),
+            already_picked: Vec::new(),
+        };
+        for source_commit_id in commit_ids {
+            let source = commit_by_id(&tx, &workspace_id, &source_commit_id)?
+                .ok_or(StoreError::UnknownId)?;
+            if source.branch_id() != source_branch_id {
+                return Err(StoreError::InvalidInput(
+                    "cherry-picked commit must belong to the source branch",
+                ));
+            }
+
+            let existing = tx
+                .query_row(
+                    "SELECT commit_id FROM commit_picks \
+                     WHERE workspace=?1 AND target_branch=?2 AND source_commit_id=?3",
+                    params![workspace_id, target_branch_id, source_commit_id],
+                    |row| row.get::<_, String>(0),
+                )
+                .optional()?;
+            if existing.is_some() {
+                report.already_picked.push(source_commit_id);
+                continue;
+            }
+
+            let author = match request.author.clone() {
+                Some(author) => Some(author),
+                None => author_by_commit(&tx, &workspace_id, &source_commit_id)?,
+            };
+            let commit = append_commit_tx(
+                &tx,
+                AppendCommitRequest {
+                    workspace_id: workspace_id.clone(),
+                    branch_id: target_branch_id.clone(),
+                    commit_id: picked_commit_id(&target_branch_id, &source_commit_id),
+                    parent_commit_id: None,
+                    expected_head_commit_id: None,
+                    message: source.message().to_string(),
+                    body: source.body().to_string(),
+                    author,
+                    created_at_ms: request.picked_at_ms,
+                },
+            )?;
+            tx.execute(
+                "INSERT INTO commit_picks(workspace, commit_id, source_commit_id, target_branch, picked_at_ms) \
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    workspace_id,
+                    commit.commit_id(),
+                    source_commit_id,
+                    target_branch_id,
+                    request.picked_at_ms
+                ],
+            )?;
+            report.picked.push(commit);
+        }
+
+        self.commit_tx(tx)?;
+        Ok(report)
+    }
+
+    /// Returns the commit a cherry-picked commit was copied from, if it is a copy.
+    pub fn commit_picked_from(
+        &self,
+        request: ShowCommitRequest,
+    ) -> Result<Option<String>, StoreError> {
+        let workspace_id = canonicalize_workspace(&request.workspace_id)?;
+        let commit_id = canonicalize_commit(&request.commit_id)?;
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT source_commit_id FROM commit_picks WHERE workspace=?1 AND commit_id=?2",
+                params![workspace_id, commit_id],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()?)
+    }
+}
+
+/// Derives the id of a picked copy, stable for a (target branch, source commit) pair so that
+/// retries collide instead of duplicating.
+fn picked_commit_id(target_branch_id: &str, source_commit_id: &str) -> String {
+    let digest = Sha256::digest(format!(
+        "branchmind.cherry_pick\n{target_branch_id}\n{source_commit_id}"
+    ));
+    let hex = digest
+        .iter()
+        .take(6)
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+    format!("pick-{hex}")
+}
@@ -0,0 +1,72 @@
+#![forbid(unsafe_code)]
+
+use super::StoreError;
+use std::time::Duration;
+
+/// Tunable limits of a store, fixed at open time by `SqliteStore::open_with_config`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StoreConfig {
+    /// How long SQLite waits on a locked database before a write attempt is retried.
+    pub busy_timeout: Duration,
+    /// Retries of a contended write transaction `BEGIN` or `COMMIT` before
+    /// `StoreError::Busy`; retrying also stops about one second past `busy_timeout`.
+    pub busy_retries: u32,
+    /// Longest allowed parent chain below a root branch.
+    pub max_branch_depth: usize,
+    /// Upper bound applied to `limit` of branch, merge and provenance listings.
+    pub max_page_limit: usize,
+    /// Upper bound on commits returned by one log page.
+    pub max_log_limit: usize,
+    /// Log every executed statement with parameter types and sizes, duration and scan counters
+    /// to stderr; bound values are never printed.
+    pub trace_sql: bool,
+}
+
+impl Default for StoreConfig {
+    fn default() -> Self {
+        Self {
+            busy_timeout: Duration::from_secs(5),
+            busy_retries: 4,
+            max_branch_depth: 128,
+            max_page_limit: 500,
+            max_log_limit: 200,
+            trace_sql: false,
+        }
+    }
+}
+
+impl StoreConfig {
+    /// Defaults, with `trace_sql` switched on when `BM_TRACE_SQL=1`.
+    pub fn from_env() -> Self {
+        Self {
+            trace_sql: std::env::var("BM_TRACE_SQL").is_ok_and(|value| value == "1"),
+            ..Self::default()
+        }
+    }
+
+    /// Rejects values outside the ranges the store is tested and sized for.
+    pub fn validate(&self) -> Result<(), StoreError> {
+        if self.busy_timeout > Duration::from_secs(60) {
+            return Err(StoreError::InvalidInput("busy_timeout must be at most 60s"));
+        }
+        if self.busy_retries > 32 {
+            return Err(StoreError::InvalidInput("busy_retries must be at most 32"));
+        }
+        if !(1..=4096).contains(&self.max_branch_depth) {
+            return Err(StoreError::InvalidInput(
+                "max_branch_depth must be between 1 and 4096",
+            ));
+        }
+        if !(1..=100_000).contains(&self.max_page_limit) {
+            return Err(StoreError::InvalidInput(
+                "max_page_limit must be between 1 and 100000",
+            ));
+        }
+        if !(1..=10_000).contains(&self.max_log_limit) {
+            return Err(StoreError::InvalidInput(
+                "max_log_limit must be between 1 and 10000",
+            ));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,97 @@
+#![forbid(unsafe_code)]
+
+use super::{
+    CreateMergeRecordRequest, SqliteStore, StoreError, audit::audit_tx, canonical_identifier,
+    canonicalize_workspace, create_merge_record_tx, ensure_workspace_tx, now_ms,
+};
+use bm_core::MergeRecord;
+use rusqlite::{OptionalExtension, Transaction, params};
+
+/// Counter names under this prefix are kept for the store itself.
+const RESERVED_COUNTER_PREFIX: &str = "bm.";
+
+impl SqliteStore {
+    /// Allocates the next value of a workspace counter. The first value is 1 and values never
+    /// repeat, even across restarts, because allocation commits with the rest of the store.
+    pub fn counter_next(&mut self, workspace_id: &str, name: &str) -> Result<u64, StoreError> {
+        let workspace_id = canonicalize_workspace(workspace_id)?;
+        let name = canonicalize_counter(name)?;
+
+        let tx = self.write_tx()?;
+        let value = counter_next_tx(&tx, &workspace_id, &name, now_ms())?;
+        self.commit_tx(tx)?;
+        Ok(value)
+    }
+
+    /// Creates a merge record whose ids are built from the next value of a workspace
+    /// counter. The value is allocated in the merge transaction, so a failed merge does not
+    /// consume it and a committed value always has its merge.
+    pub fn create_merge_record_numbered(
+        &mut self,
+        workspace_id: &str,
+        counter: &str,
+        build: impl FnOnce(u64) -> CreateMergeRecordRequest,
+    ) -> Result<MergeRecord, StoreError> {
+        let workspace_id = canonicalize_workspace(workspace_id)?;
+        let name = canonicalize_counter(counter)?;
+
+        let tx = self.write_tx()?;
+        let value = counter_next_tx(&tx, &workspace_id, &name, now_ms())?;
+        let request = build(value);
+        if canonicalize_workspace(&request.workspace_id)? != workspace_id {
+            return Err(StoreError::InvalidInput(
+                "merge must be created in the counter's workspace",
+            ));
+        }
+        let merge_record = create_merge_record_tx(&tx, request)?;
+        self.commit_tx(tx)?;
+        Ok(merge_record)
+    }
+
+    /// Returns the last value handed out by `counter_next`, or 0 if none was.
+    pub fn counter_peek(&self, workspace_id: &str, name: &str) -> Result<u64, StoreError> {
+        let workspace_id = canonicalize_workspace(workspace_id)?;
+        let name = canonicalize_counter(name)?;
+        let value = self
+            .conn
+            .query_row(
+                "SELECT value FROM workspace_counters WHERE workspace=?1 AND name=?2",
+                params![workspace_id, name],
+                |row| row.get::<_, i64>(0),
+            )
+            .optional()?
+            .unwrap_or(0);
+        u64::try_from(value).map_err(|_| StoreError::InvalidInput("counter value out of range"))
+    }
+}
+
+/// Allocates the next counter value inside the caller's write transaction. `workspace_id`
+/// and `name` must already be canonical.
+pub(super) fn counter_next_tx(
+    tx: &Transaction<'_>,
+    workspace_id: &str,
+    name: &str,
+    now_ms: i64,
+) -> Result<u64, StoreError> {
+    ensure_workspace_tx(tx, workspace_id, now_ms)?;
+    let value = tx.query_row(
+        "INSERT INTO workspace_counters(workspace, name, value) VALUES (?1, ?2, 1) \
+         ON CONFLICT(workspace, name) DO UPDATE SET value=value+1 \
+         RETURNING value",
+        params![workspace_id, name],
+        |row| row.get::<_, i64>(0),
+    )?;
+    audit_tx(tx, workspace_id, "counter.next", name, now_ms)?;
+    u64::try_from(value).map_err(|_| StoreError::InvalidInput("counter value out of range"))
+}
+
+fn canonicalize_counter(value: &str) -> Result<String, StoreError> {
+    let name = canonical_identifier("counter", value.to_string())
+        .map_err(|_| StoreError::InvalidInput("invalid counter name"))?;
+    if name.starts_with(RESERVED_COUNTER_PREFIX) {
+        return Err(StoreError::InvalidInput(
+            "counter name uses the reserved bm. prefix",
+        ));
+    }
+    Ok(name)
+}
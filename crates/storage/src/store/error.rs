@@ -10,6 +10,16 @@ pub enum StoreError {
     BranchAlreadyExists,
     BranchCycle,
     BranchDepthExceeded,
+    HeadMismatch {
+        current_head: Option<String>,
+    },
+    Busy {
+        waited_ms: u64,
+    },
+    /// Another holder has the workspace maintenance lock.
+    WorkspaceLocked {
+        holder: String,
+    },
 }
 
 impl StoreError {
@@ -24,6 +34,9 @@ impl StoreError {
             Self::BranchAlreadyExists => "ALREADY_EXISTS",
             Self::BranchCycle => "BRANCH_CYCLE",
             Self::BranchDepthExceeded => "BRANCH_DEPTH_EXCEEDED",
+            Self::HeadMismatch { .. } => "HEAD_MISMATCH",
+            Self::Busy { .. } => "BUSY",
+            Self::WorkspaceLocked { .. } => "WORKSPACE_LOCKED",
         }
     }
 
@@ -36,6 +49,11 @@ impl StoreError {
                 Some("use a different identifier or delete existing record")
             }
             Self::UnknownId | Self::UnknownBranch => Some("create required entity before retry"),
+            Self::HeadMismatch { .. } => Some("re-read the branch head and retry against it"),
+            Self::Busy { .. } => Some("another writer holds the store; back off and retry"),
+            Self::WorkspaceLocked { .. } => {
+                Some("wait for the lock to be released or expire, or force-release a stale lock")
+            }
             _ => None,
         }
     }
@@ -52,6 +70,12 @@ impl std::fmt::Display for StoreError {
             Self::BranchAlreadyExists => write!(f, "branch already exists"),
             Self::BranchCycle => write!(f, "branch parent cycle"),
             Self::BranchDepthExceeded => write!(f, "branch depth exceeded"),
+            Self::HeadMismatch { current_head } => match current_head {
+                Some(head) => write!(f, "branch head mismatch (current head: {head})"),
+                None => write!(f, "branch head mismatch (branch has no commits)"),
+            },
+            Self::Busy { waited_ms } => write!(f, "store busy (waited {waited_ms} ms)"),
+            Self::WorkspaceLocked { holder } => write!(f, "workspace locked by {holder}"),
         }
     }
 }
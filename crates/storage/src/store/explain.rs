@@ -0,0 +1,175 @@
+#![forbid(unsafe_code)]
+
+use super::{
+    ExplainTarget, QUERY_BRANCHES_SQL, SqliteStore, StoreError, commit_by_id_sql,
+    list_merge_records_sql, pins::list_pinned_commits_sql,
+};
+use rusqlite::trace::{TraceEvent, TraceEventCodes};
+use rusqlite::{Connection, StatementStatus};
+use std::collections::HashMap;
+
+/// `EXPLAIN QUERY PLAN` output for one statement.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QueryPlan {
+    pub sql: String,
+    /// Plan lines in SQLite order, indented two spaces per nesting level.
+    pub steps: Vec<String>,
+}
+
+impl SqliteStore {
+    /// Explains the exact statement the given read path prepares. Parameters are left unbound,
+    /// which does not change the plan SQLite picks for these queries.
+    pub fn explain_query_plan(&self, target: ExplainTarget) -> Result<QueryPlan, StoreError> {
+        let sql = match target {
+            ExplainTarget::ListBranches => QUERY_BRANCHES_SQL.to_string(),
+            ExplainTarget::ListMergeRecords => list_merge_records_sql(),
+            ExplainTarget::ShowCommit => commit_by_id_sql(),
+            ExplainTarget::ListPinnedCommits => list_pinned_commits_sql(),
+        };
+
+        let mut stmt = self.conn.prepare(&format!("EXPLAIN QUERY PLAN {sql}"))?;
+        // Unbound parameters are NULL; `query` would reject the missing bindings.
+        let mut rows = stmt.raw_query();
+        let mut depth_by_id = HashMap::<i64, usize>::new();
+        let mut steps = Vec::new();
+        while let Some(row) = rows.next()? {
+            let id = row.get::<_, i64>(0)?;
+            let parent = row.get::<_, i64>(1)?;
+            let detail = row.get::<_, String>(3)?;
+            let depth = depth_by_id.get(&parent).map_or(0, |depth| depth + 1);
+            depth_by_id.insert(id, depth);
+            steps.push(format!("{}{detail}", "  ".repeat(depth)));
+        }
+        Ok(QueryPlan { sql, steps })
+    }
+}
+
+/// Logs every finished statement to stderr with its duration, SQLite's per-statement
+/// counters (`fullscan` steps point at a missing index) and the type and size of each bound
+/// parameter. Bound values themselves are never printed: they carry commit bodies, including
+/// ones that were redacted since.
+pub(super) fn install_sql_trace(conn: &Connection) {
+    conn.trace_v2(TraceEventCodes::SQLITE_TRACE_PROFILE, Some(trace_sql_event));
+}
+
+fn trace_sql_event(event: TraceEvent<'_>) {
+    if let TraceEvent::Profile(stmt, duration) = event {
+        let sql = stmt.sql();
+        let params = stmt
+            .expanded_sql()
+            .and_then(|expanded| param_summary(&sql, &expanded))
+            .map_or_else(|| "?".to_string(), |params| params.join(","));
+        eprintln!(
+            "bm_storage sql {}us vm_steps={} fullscan={} sorts={} params=[{}] {}",
+            duration.as_micros(),
+            stmt.get_status(StatementStatus::VmStep),
+            stmt.get_status(StatementStatus::FullscanStep),
+            stmt.get_status(StatementStatus::Sort),
+            params,
+            sql.split_whitespace().collect::<Vec<_>>().join(" ")
+        );
+    }
+}
+
+/// Describes each parameter placeholder as `null`, `int`, `real`, `text:<bytes>` or
+/// `blob:<bytes>` by walking the statement text alongside SQLite's expansion of it, which
+/// differs only where a parameter was substituted by its literal. `None` if the two
+/// cannot be aligned.
+fn param_summary(sql: &str, expanded: &str) -> Option<Vec<String>> {
+    let (sql, expanded) = (sql.as_bytes(), expanded.as_bytes());
+    let (mut i, mut j) = (0, 0);
+    let mut params = Vec::new();
+    while i < sql.len() {
+        match sql[i] {
+            b':' | b'@' | b'$'
+                if sql
+                    .get(i + 1)
+                    .is_some_and(|next| next.is_ascii_alphabetic()) =>
+            {
+                i += 1;
+                while i < sql.len() && (sql[i].is_ascii_alphanumeric() || sql[i] == b'_') {
+                    i += 1;
+                }
+                let (summary, len) = literal_summary(&expanded[j..])?;
+                params.push(summary);
+                j += len;
+            }
+            b'\'' => {
+                let end = quoted_end(sql, i)?;
+                if expanded.get(j..j + (end - i))? != &sql[i..end] {
+                    return None;
+                }
+                j += end - i;
+                i = end;
+            }
+            b'?' => {
+                i += 1;
+                while i < sql.len() && (sql[i].is_ascii_alphanumeric() || sql[i] == b'_') {
+                    i += 1;
+                }
+                let (summary, len) = literal_summary(&expanded[j..])?;
+                params.push(summary);
+                j += len;
+            }
+            byte => {
+                if expanded.get(j) != Some(&byte) {
+                    return None;
+                }
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    (j == expanded.len()).then_some(params)
+}
+
+/// Summary and byte length of the SQL literal at the start of `text`.
+fn literal_summary(text: &[u8]) -> Option<(String, usize)> {
+    if text.starts_with(b"NULL") {
+        return Some(("null".to_string(), 4));
+    }
+    match text.first()? {
+        b'\'' => {
+            let end = quoted_end(text, 0)?;
+            let escaped_quotes = text[1..end - 1]
+                .iter()
+                .filter(|byte| **byte == b'\'')
+                .count()
+                / 2;
+            Some((format!("text:{}", end - 2 - escaped_quotes), end))
+        }
+        b'x' | b'X' if text.get(1) == Some(&b'\'') => {
+            let end = quoted_end(text, 1)?;
+            Some((format!("blob:{}", (end - 3) / 2), end))
+        }
+        _ => {
+            let mut len = usize::from(text[0] == b'-');
+            let mut real = false;
+            while let Some(byte) = text.get(len) {
+                match byte {
+                    b'0'..=b'9' => len += 1,
+                    b'.' => (real, len) = (true, len + 1),
+                    b'e' | b'E' => {
+                        (real, len) = (true, len + 1);
+                        len += usize::from(matches!(text.get(len), Some(b'+' | b'-')));
+                    }
+                    _ => break,
+                }
+            }
+            let kind = if real { "real" } else { "int" };
+            (len > usize::from(text[0] == b'-')).then(|| (kind.to_string(), len))
+        }
+    }
+}
+
+/// Index just past the single-quoted literal opening at `start`; `''` is an escaped quote.
+fn quoted_end(text: &[u8], start: usize) -> Option<usize> {
+    let mut k = start + 1;
+    loop {
+        match text.get(k)? {
+            b'\'' if text.get(k + 1) == Some(&b'\'') => k += 2,
+            b'\'' => return Some(k + 1),
+            _ => k += 1,
+        }
+    }
+}
@@ -0,0 +1,130 @@
+#![forbid(unsafe_code)]
+
+use super::{ExportTable, ExportTableRequest, SqliteStore, StoreError, canonicalize_workspace};
+use rusqlite::params;
+use rusqlite::types::ValueRef;
+use std::io::Write;
+
+impl ExportTable {
+    /// Column header of the CSV export. Columns are only ever appended, never reordered.
+    pub fn columns(self) -> &'static [&'static str] {
+        match self {
+            ExportTable::Branches => &[
+                "workspace",
+                "branch",
+                "parent_branch",
+                "head_commit_id",
+                "created_at_ms",
+                "updated_at_ms",
+                "archived",
+            ],
+            ExportTable::Commits => &[
+                "workspace",
+                "commit_id",
+                "branch",
+                "parent_commit_id",
+                "author",
+                "message",
+                "body_bytes",
+                "created_at_ms",
+            ],
+            ExportTable::Merges => &[
+                "workspace",
+                "merge_id",
+                "source_branch",
+                "target_branch",
+                "synthesis_commit_id",
+                "strategy",
+                "created_at_ms",
+            ],
+            ExportTable::Audit => &["workspace", "seq", "at_ms", "op", "subject"],
+        }
+    }
+
+    fn select_sql(self) -> &'static str {
+        match self {
+            ExportTable::Branches => {
+                "SELECT b.workspace, b.name, b.parent_branch_id, b.head_commit_id, b.created_at_ms, \
+                        b.updated_at_ms, \
+                        EXISTS (SELECT 1 FROM branch_archive a WHERE a.workspace=b.workspace AND a.branch=b.name) \
+                 FROM branches b WHERE b.workspace=?1 ORDER BY b.created_at_ms ASC, b.name ASC"
+            }
+            ExportTable::Commits => {
+                "SELECT c.workspace, c.commit_id, c.branch, c.parent_commit_id, a.author, c.message, \
+                        length(CAST(c.body AS BLOB)), c.created_at_ms \
+                 FROM commits c \
+                 LEFT JOIN commit_authors a ON a.workspace=c.workspace AND a.commit_id=c.commit_id \
+                 WHERE c.workspace=?1 ORDER BY c.created_at_ms ASC, c.commit_id ASC"
+            }
+            ExportTable::Merges => {
+                "SELECT workspace, merge_id, source_branch, target_branch, synthesis_commit_id, \
+                        strategy, created_at_ms \
+                 FROM merge_records WHERE workspace=?1 ORDER BY created_at_ms ASC, merge_id ASC"
+            }
+            ExportTable::Audit => {
+                "SELECT workspace, seq, at_ms, op, subject \
+                 FROM audit_log WHERE workspace=?1 ORDER BY seq ASC"
+            }
+        }
+    }
+}
+
+impl SqliteStore {
+    /// Writes one table of a workspace as RFC 4180 CSV (header row, CRLF line ends) and
+    /// returns the number of data rows.
+    ///
+    /// Commit bodies are not exported, only their size in bytes (`body_bytes`). CSV is the only
+    /// format; Parquet is not offered.
+    pub fn export_table_csv(
+        &self,
+        request: ExportTableRequest,
+        mut out: impl Write,
+    ) -> Result<usize, StoreError> {
+        let workspace_id = canonicalize_workspace(&request.workspace_id)?;
+        let columns = request.table.columns();
+
+        write_record(&mut out, columns.iter().map(|c| c.to_string()))?;
+        let mut stmt = self.conn.prepare(request.table.select_sql())?;
+        let mut rows = stmt.query(params![workspace_id])?;
+        let mut count = 0;
+        while let Some(row) = rows.next()? {
+            let mut fields = Vec::with_capacity(columns.len());
+            for idx in 0..columns.len() {
+                fields.push(match row.get_ref(idx)? {
+                    ValueRef::Null => String::new(),
+                    ValueRef::Integer(value) => value.to_string(),
+                    ValueRef::Real(value) => value.to_string(),
+                    ValueRef::Text(value) | ValueRef::Blob(value) => {
+                        String::from_utf8_lossy(value).into_owned()
+                    }
+                });
+            }
+            write_record(&mut out, fields.into_iter())?;
+            count += 1;
+        }
+        out.flush()?;
+        Ok(count)
+    }
+}
+
+fn write_record(
+    out: &mut impl Write,
+    fields: impl Iterator<Item = String>,
+) -> Result<(), StoreError> {
+    let mut line = String::new();
+    for (idx, field) in fields.enumerate() {
+        if idx > 0 {
+            line.push(',');
+        }
+        if field.contains([',', '"', '\n', '\r']) {
+            line.push('"');
+            line.push_str(&field.replace('"', "\"\""));
+            line.push('"');
+        } else {
+            line.push_str(&field);
+        }
+    }
+    line.push_str("\r\n");
+    out.write_all(line.as_bytes())?;
+    Ok(())
+}
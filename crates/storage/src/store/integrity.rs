@@ -0,0 +1,191 @@
+#![forbid(unsafe_code)]
+
+use super::{
+    IntegrityRepairRequest, SqliteStore, StoreError, audit::audit_tx, canonicalize_workspace,
+    ensure_unlocked,
+};
+use rusqlite::{Connection, OptionalExtension, params};
+
+/// Invariants that the schema does not enforce with foreign keys.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IntegrityIssueKind {
+    /// A branch head names a commit that no longer exists.
+    DanglingHead,
+    /// A template use names a commit that no longer exists.
+    OrphanTemplateUse,
+    /// A scratch expiry names a branch that no longer exists.
+    OrphanScratch,
+    /// A merge synthesis commit is not on the merge target branch.
+    SynthesisOffTarget,
+    /// The workspace audit chain does not verify.
+    AuditChain,
+}
+
+impl IntegrityIssueKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            IntegrityIssueKind::DanglingHead => "dangling_head",
+            IntegrityIssueKind::OrphanTemplateUse => "orphan_template_use",
+            IntegrityIssueKind::OrphanScratch => "orphan_scratch",
+            IntegrityIssueKind::SynthesisOffTarget => "synthesis_off_target",
+            IntegrityIssueKind::AuditChain => "audit_chain",
+        }
+    }
+
+    /// Whether `integrity_repair` fixes this kind without losing reasoning content.
+    pub fn is_repairable(self) -> bool {
+        matches!(
+            self,
+            IntegrityIssueKind::DanglingHead
+                | IntegrityIssueKind::OrphanTemplateUse
+                | IntegrityIssueKind::OrphanScratch
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IntegrityIssue {
+    pub kind: IntegrityIssueKind,
+    /// Branch, commit or merge id the issue is about; empty for `AuditChain`.
+    pub subject: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IntegrityReport {
+    pub issues: Vec<IntegrityIssue>,
+}
+
+impl IntegrityReport {
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl SqliteStore {
+    /// Scans a workspace for broken invariants. Read-only.
+    pub fn integrity_check(&self, workspace_id: &str) -> Result<IntegrityReport, StoreError> {
+        let workspace_id = canonicalize_workspace(workspace_id)?;
+        let mut issues = scan_issues(&self.conn, &workspace_id)?;
+        if !self.audit_verify(&workspace_id)?.is_intact() {
+            issues.push(IntegrityIssue {
+                kind: IntegrityIssueKind::AuditChain,
+                subject: String::new(),
+            });
+        }
+        Ok(IntegrityReport { issues })
+    }
+
+    /// Fixes the repairable issues `integrity_check` reports and returns what was fixed.
+    ///
+    /// A dangling head is moved to the tip of its branch's remaining chain, the commit no
+    /// other commit of the branch builds on, preferring the newest on a tie (or cleared);
+    /// orphaned template uses and scratch expiries are deleted. Other issues are left for an
+    /// operator and show up in the next `integrity_check`.
+    pub fn integrity_repair(
+        &mut self,
+        request: IntegrityRepairRequest,
+    ) -> Result<IntegrityReport, StoreError> {
+        let workspace_id = canonicalize_workspace(&request.workspace_id)?;
+
+        let tx = self.write_tx()?;
+        ensure_unlocked(&tx, &workspace_id, self.own_lock_holder(&workspace_id))?;
+        let mut repaired = Vec::new();
+        for issue in scan_issues(&tx, &workspace_id)? {
+            match issue.kind {
+                IntegrityIssueKind::DanglingHead => {
+                    let head = tx
+                        .query_row(
+                            "SELECT c.commit_id FROM commits c \
+                             WHERE c.workspace=?1 AND c.branch=?2 \
+                               AND NOT EXISTS (SELECT 1 FROM commits d \
+                                 WHERE d.workspace=c.workspace AND d.branch=c.branch \
+                                   AND d.parent_commit_id=c.commit_id) \
+                             ORDER BY c.created_at_ms DESC, c.commit_id DESC LIMIT 1",
+                            params![workspace_id, issue.subject],
+                            |row| row.get::<_, String>(0),
+                        )
+                        .optional()?;
+                    tx.execute(
+                        "UPDATE branches SET head_commit_id=?3 WHERE workspace=?1 AND name=?2",
+                        params![workspace_id, issue.subject, head],
+                    )?;
+                }
+                IntegrityIssueKind::OrphanTemplateUse => {
+                    tx.execute(
+                        "DELETE FROM commit_template_uses WHERE workspace=?1 AND commit_id=?2",
+                        params![workspace_id, issue.subject],
+                    )?;
+                }
+                IntegrityIssueKind::OrphanScratch => {
+                    tx.execute(
+                        "DELETE FROM branch_scratch WHERE workspace=?1 AND branch=?2",
+                        params![workspace_id, issue.subject],
+                    )?;
+                }
+                IntegrityIssueKind::SynthesisOffTarget | IntegrityIssueKind::AuditChain => {
+                    continue;
+                }
+            }
+            audit_tx(
+                &tx,
+                &workspace_id,
+                &format!("integrity.repair.{}", issue.kind.as_str()),
+                &issue.subject,
+                request.repaired_at_ms,
+            )?;
+            repaired.push(issue);
+        }
+
+        self.commit_tx(tx)?;
+        Ok(IntegrityReport { issues: repaired })
+    }
+}
+
+fn scan_issues(conn: &Connection, workspace_id: &str) -> Result<Vec<IntegrityIssue>, StoreError> {
+    let checks = [
+        (
+            IntegrityIssueKind::DanglingHead,
+            "SELECT b.name FROM branches b \
+             WHERE b.workspace=?1 AND b.head_commit_id IS NOT NULL \
+               AND NOT EXISTS (SELECT 1 FROM commits c \
+                               WHERE c.workspace=b.workspace AND c.commit_id=b.head_commit_id) \
+             ORDER BY b.name",
+        ),
+        (
+            IntegrityIssueKind::OrphanTemplateUse,
+            "SELECT u.commit_id FROM commit_template_uses u \
+             WHERE u.workspace=?1 \
+               AND NOT EXISTS (SELECT 1 FROM commits c \
+                               WHERE c.workspace=u.workspace AND c.commit_id=u.commit_id) \
+             ORDER BY u.commit_id",
+        ),
+        (
+            IntegrityIssueKind::OrphanScratch,
+            "SELECT s.branch FROM branch_scratch s \
+             WHERE s.workspace=?1 \
+               AND NOT EXISTS (SELECT 1 FROM branches b \
+                               WHERE b.workspace=s.workspace AND b.name=s.branch) \
+             ORDER BY s.branch",
+        ),
+        (
+            IntegrityIssueKind::SynthesisOffTarget,
+            "SELECT m.merge_id FROM merge_records m \
+             JOIN commits c ON c.workspace=m.workspace AND c.commit_id=m.synthesis_commit_id \
+             WHERE m.workspace=?1 AND c.branch<>m.target_branch \
+             ORDER BY m.merge_id",
+        ),
+    ];
+
+    let mut issues = Vec::new();
+    for (kind, sql) in checks {
+        let mut stmt = conn.prepare(sql)?;
+        let rows = stmt.query_map(params![workspace_id], |row| row.get::<_, String>(0))?;
+        for subject in rows {
+            issues.push(IntegrityIssue {
+                kind,
+                subject: subject?,
+            });
+        }
+    }
+    Ok(issues)
+}
@@ -0,0 +1,205 @@
+#![forbid(unsafe_code)]
+
+use super::{
+    SqliteStore, StoreError, WorkspaceLockRequest, audit::audit_tx, canonicalize_author,
+    canonicalize_workspace, ensure_workspace_tx, now_ms,
+};
+use rusqlite::{Connection, OptionalExtension, Row, params};
+
+/// Advisory lock that keeps other holders out of destructive maintenance on a workspace.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WorkspaceLock {
+    pub workspace_id: String,
+    pub holder: String,
+    pub purpose: String,
+    pub acquired_at_ms: i64,
+    pub expires_at_ms: i64,
+}
+
+impl SqliteStore {
+    /// Takes or renews the maintenance lock of a workspace.
+    ///
+    /// Fails with `WorkspaceLocked` while another holder has an unexpired lock. Once held,
+    /// branch deletion, scratch pruning, integrity repair, workspace merge into the workspace
+    /// and workspace deletion through this handle proceed; through any other handle they fail
+    /// until the lock is released or expires.
+    pub fn workspace_lock(
+        &mut self,
+        request: WorkspaceLockRequest,
+    ) -> Result<WorkspaceLock, StoreError> {
+        let workspace_id = canonicalize_workspace(&request.workspace_id)?;
+        let holder = canonicalize_author(&request.holder)
+            .map_err(|_| StoreError::InvalidInput("invalid lock holder"))?;
+        let purpose = request.purpose.trim().to_string();
+        if purpose.is_empty() {
+            return Err(StoreError::InvalidInput("lock purpose must not be empty"));
+        }
+        if request.ttl_ms <= 0 {
+            return Err(StoreError::InvalidInput("lock ttl_ms must be positive"));
+        }
+        let now_ms = now_ms();
+
+        let tx = self.write_tx()?;
+        ensure_workspace_tx(&tx, &workspace_id, now_ms)?;
+        if let Some(current) = live_lock(&tx, &workspace_id, now_ms)?
+            && current.holder != holder
+        {
+            return Err(StoreError::WorkspaceLocked {
+                holder: current.holder,
+            });
+        }
+        let lock = WorkspaceLock {
+            workspace_id,
+            holder,
+            purpose,
+            acquired_at_ms: now_ms,
+            expires_at_ms: now_ms.saturating_add(request.ttl_ms),
+        };
+        tx.execute(
+            "INSERT INTO workspace_locks(workspace, holder, purpose, acquired_at_ms, expires_at_ms) \
+             VALUES (?1, ?2, ?3, ?4, ?5) \
+             ON CONFLICT(workspace) DO UPDATE SET holder=excluded.holder, purpose=excluded.purpose, \
+               acquired_at_ms=excluded.acquired_at_ms, expires_at_ms=excluded.expires_at_ms",
+            params![
+                lock.workspace_id,
+                lock.holder,
+                lock.purpose,
+                lock.acquired_at_ms,
+                lock.expires_at_ms
+            ],
+        )?;
+        audit_tx(
+            &tx,
+            &lock.workspace_id,
+            "workspace.lock",
+            &lock.holder,
+            now_ms,
+        )?;
+
+        self.commit_tx(tx)?;
+        self.lock_holders
+            .insert(lock.workspace_id.clone(), lock.holder.clone());
+        Ok(lock)
+    }
+
+    /// Releases the lock if `holder` holds it. Returns `false` when there was nothing to release.
+    pub fn workspace_unlock(
+        &mut self,
+        workspace_id: &str,
+        holder: &str,
+    ) -> Result<bool, StoreError> {
+        let workspace_id = canonicalize_workspace(workspace_id)?;
+        let holder = canonicalize_author(holder)
+            .map_err(|_| StoreError::InvalidInput("invalid lock holder"))?;
+        let now_ms = now_ms();
+
+        let tx = self.write_tx()?;
+        let released = tx.execute(
+            "DELETE FROM workspace_locks WHERE workspace=?1 AND holder=?2",
+            params![workspace_id, holder],
+        )?;
+        if released > 0 {
+            audit_tx(&tx, &workspace_id, "workspace.unlock", &holder, now_ms)?;
+        }
+
+        self.commit_tx(tx)?;
+        if self.own_lock_holder(&workspace_id) == Some(holder.as_str()) {
+            self.lock_holders.remove(&workspace_id);
+        }
+        Ok(released > 0)
+    }
+
+    /// Removes a lock regardless of its holder, for locks left behind by a crashed process.
+    /// Returns the lock that was removed, if any.
+    pub fn workspace_lock_force_release(
+        &mut self,
+        workspace_id: &str,
+    ) -> Result<Option<WorkspaceLock>, StoreError> {
+        let workspace_id = canonicalize_workspace(workspace_id)?;
+        let now_ms = now_ms();
+
+        let tx = self.write_tx()?;
+        let lock = tx
+            .query_row(
+                &format!("SELECT {LOCK_COLUMNS} FROM workspace_locks WHERE workspace=?1"),
+                params![workspace_id],
+                lock_from_row,
+            )
+            .optional()?;
+        if let Some(lock) = lock.as_ref() {
+            tx.execute(
+                "DELETE FROM workspace_locks WHERE workspace=?1",
+                params![workspace_id],
+            )?;
+            audit_tx(
+                &tx,
+                &workspace_id,
+                "workspace.lock.force_release",
+                &lock.holder,
+                now_ms,
+            )?;
+        }
+
+        self.commit_tx(tx)?;
+        self.lock_holders.remove(&workspace_id);
+        Ok(lock)
+    }
+
+    /// Lists unexpired locks across all workspaces, ordered by workspace.
+    pub fn list_workspace_locks(&self) -> Result<Vec<WorkspaceLock>, StoreError> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {LOCK_COLUMNS} FROM workspace_locks WHERE expires_at_ms>?1 ORDER BY workspace"
+        ))?;
+        let rows = stmt.query_map(params![now_ms()], lock_from_row)?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// Holder this handle locked `workspace_id` as, if it did.
+    pub(super) fn own_lock_holder(&self, workspace_id: &str) -> Option<&str> {
+        self.lock_holders.get(workspace_id).map(String::as_str)
+    }
+}
+
+const LOCK_COLUMNS: &str = "workspace, holder, purpose, acquired_at_ms, expires_at_ms";
+
+fn lock_from_row(row: &Row<'_>) -> rusqlite::Result<WorkspaceLock> {
+    Ok(WorkspaceLock {
+        workspace_id: row.get(0)?,
+        holder: row.get(1)?,
+        purpose: row.get(2)?,
+        acquired_at_ms: row.get(3)?,
+        expires_at_ms: row.get(4)?,
+    })
+}
+
+fn live_lock(
+    conn: &Connection,
+    workspace_id: &str,
+    now_ms: i64,
+) -> Result<Option<WorkspaceLock>, StoreError> {
+    Ok(conn
+        .query_row(
+            &format!(
+                "SELECT {LOCK_COLUMNS} FROM workspace_locks WHERE workspace=?1 AND expires_at_ms>?2"
+            ),
+            params![workspace_id, now_ms],
+            lock_from_row,
+        )
+        .optional()?)
+}
+
+/// Refuses destructive maintenance while a holder other than `own_holder` has a live lock.
+pub(super) fn ensure_unlocked(
+    conn: &Connection,
+    workspace_id: &str,
+    own_holder: Option<&str>,
+) -> Result<(), StoreError> {
+    match live_lock(conn, workspace_id, now_ms())? {
+        Some(lock) if Some(lock.holder.as_str()) != own_holder => {
+            Err(StoreError::WorkspaceLocked {
+                holder: lock.holder,
+            })
+        }
+        _ => Ok(()),
+    }
+}
@@ -1,41 +1,140 @@
 #![forbid(unsafe_code)]
 
+mod acks;
+mod activity;
+mod archive;
+mod audit;
+mod authors;
+mod backup;
+mod busy;
+mod checkout_stack;
+mod cherry_pick;
+mod config;
+mod counters;
 mod error;
+mod explain;
+mod export;
+mod integrity;
+mod locks;
+mod pins;
+mod provenance;
+mod redact;
 mod requests;
-
+mod scratch;
+mod session_branch;
+mod templates;
+mod workspace_delete;
+mod workspace_diff;
+mod workspace_merge;
+
+pub use acks::AckCounts;
+pub use activity::ActivityRow;
+pub use audit::AuditVerification;
+pub use backup::BackupManifest;
+pub use checkout_stack::CheckoutFrame;
+pub use cherry_pick::CherryPickReport;
+pub use config::StoreConfig;
 pub use error::StoreError;
+pub use explain::QueryPlan;
+pub use integrity::{IntegrityIssue, IntegrityIssueKind, IntegrityReport};
+pub use locks::WorkspaceLock;
+pub use provenance::ProvenanceStep;
+pub use redact::{CommitRedaction, REDACTED_BODY, REDACTED_MESSAGE};
 pub use requests::*;
-
+pub use scratch::{ScratchBranch, ScratchPruneReport};
+pub use session_branch::AutoBranch;
+pub use workspace_delete::WorkspaceDeleteReport;
+pub use workspace_diff::{BranchActivity, WorkspaceDiff};
+pub use workspace_merge::WorkspaceMergeReport;
+
+use archive::ensure_branch_active_tx;
+use audit::{audit_commit_tx, audit_tx};
+use authors::insert_commit_author_tx;
 use bm_core::{MergeRecord, ThoughtBranch, ThoughtCommit, canonical_identifier, ids::WorkspaceId};
-use rusqlite::{Connection, ErrorCode, OptionalExtension, Transaction, params};
-use std::collections::BTreeSet;
+use locks::ensure_unlocked;
+use rusqlite::{Connection, ErrorCode, OptionalExtension, Row, Transaction, params};
+use std::collections::{BTreeSet, HashMap};
 use std::path::{Path, PathBuf};
-use std::time::Duration;
 
 const DEFAULT_BRANCH: &str = "main";
 const V3_SCHEMA_VERSION: i64 = 3;
-const MAX_BRANCH_DEPTH: usize = 128;
+
+const V3_TABLES: [&str; 6] = [
+    "workspace_state",
+    "workspaces",
+    "branches",
+    "branch_checkout",
+    "commits",
+    "merge_records",
+];
+
+// Tables added on top of the v3 baseline. `install_schema` creates them when missing, so a
+// store written by an older build opens without a reset.
+const V3_ADDITIVE_TABLES: [&str; 16] = [
+    "merge_sources",
+    "commit_authors",
+    "commit_pins",
+    "branch_archive",
+    "audit_log",
+    "audit_head",
+    "branch_scratch",
+    "commit_templates",
+    "commit_template_uses",
+    "commit_acks",
+    "workspace_counters",
+    "commit_picks",
+    "workspace_locks",
+    "commit_redactions",
+    "checkout_stack",
+    "commit_copies",
+];
 
 #[derive(Debug)]
 pub struct SqliteStore {
     conn: Connection,
     storage_dir: PathBuf,
+    config: StoreConfig,
+    /// Holder of each workspace maintenance lock taken through this handle, by workspace.
+    lock_holders: HashMap<String, String>,
 }
 
 impl SqliteStore {
     pub fn open(storage_dir: impl AsRef<Path>) -> Result<Self, StoreError> {
+        Self::open_with_config(storage_dir, StoreConfig::from_env())
+    }
+
+    /// Opens the store with explicit limits; the config is validated before touching disk.
+    pub fn open_with_config(
+        storage_dir: impl AsRef<Path>,
+        config: StoreConfig,
+    ) -> Result<Self, StoreError> {
+        config.validate()?;
         let storage_dir = storage_dir.as_ref().to_path_buf();
         std::fs::create_dir_all(&storage_dir)?;
 
         let db_path = storage_dir.join("branchmind_rust.db");
         let conn = Connection::open(db_path)?;
-        conn.busy_timeout(Duration::from_secs(5))?;
+        conn.busy_timeout(config.busy_timeout)?;
+        // Hot paths use `prepare_cached`; room for all of them avoids evicting each other.
+        conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
         conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        if config.trace_sql {
+            explain::install_sql_trace(&conn);
+        }
 
         preflight_gate(&conn)?;
         install_schema(&conn)?;
 
-        Ok(Self { conn, storage_dir })
+        Ok(Self {
+            conn,
+            storage_dir,
+            config,
+            lock_holders: HashMap::new(),
+        })
+    }
+
+    pub fn config(&self) -> &StoreConfig {
+        &self.config
     }
 
     pub fn storage_dir(&self) -> &Path {
@@ -65,68 +164,48 @@ impl SqliteStore {
             return Err(StoreError::BranchCycle);
         }
 
-        let tx = self.conn.transaction()?;
+        let tx = self.write_tx()?;
         ensure_workspace_tx(&tx, &workspace_id, request.created_at_ms)?;
-
-        let parent_head_commit_id = if let Some(parent_branch_id) = parent_branch_id.as_deref() {
-            let state = branch_state_tx(&tx, &workspace_id, parent_branch_id)?;
-            let depth = branch_depth_tx(&tx, &workspace_id, parent_branch_id)?;
-            if depth + 1 > MAX_BRANCH_DEPTH {
-                return Err(StoreError::BranchDepthExceeded);
-            }
-            state.head_commit_id
-        } else {
-            None
-        };
-
-        let branch = ThoughtBranch::try_new(
-            workspace_id.clone(),
-            branch_id.clone(),
-            parent_branch_id.clone(),
-            parent_head_commit_id,
+        let branch = insert_branch_tx(
+            &tx,
+            &workspace_id,
+            &branch_id,
+            parent_branch_id.as_deref(),
             request.created_at_ms,
+            self.config.max_branch_depth,
+        )?;
+        audit_tx(
+            &tx,
+            &workspace_id,
+            "branch.create",
+            &branch_id,
             request.created_at_ms,
-        )
-        .map_err(|_| StoreError::InvalidInput("invalid branch payload"))?;
-
-        let insert = tx.execute(
-            "INSERT INTO branches(workspace, name, parent_branch_id, head_commit_id, created_at_ms, updated_at_ms) \
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-            params![
-                branch.workspace_id(),
-                branch.branch_id(),
-                branch.parent_branch_id(),
-                branch.head_commit_id(),
-                branch.created_at_ms(),
-                branch.updated_at_ms(),
-            ],
-        );
-
-        if let Err(err) = insert {
-            return Err(map_insert_conflict(err));
-        }
+        )?;
 
-        tx.commit()?;
+        self.commit_tx(tx)?;
         Ok(branch)
     }
 
+    /// Lists active branches; archived branches are listed by `list_archived_branches`.
     pub fn list_branches(
         &self,
         request: ListBranchesRequest,
+    ) -> Result<Vec<ThoughtBranch>, StoreError> {
+        self.query_branches(request, false)
+    }
+
+    fn query_branches(
+        &self,
+        request: ListBranchesRequest,
+        archived: bool,
     ) -> Result<Vec<ThoughtBranch>, StoreError> {
         let workspace_id = canonicalize_workspace(&request.workspace_id)?;
-        let limit = to_sqlite_i64(request.limit)?;
+        let limit = to_sqlite_i64(request.limit.min(self.config.max_page_limit))?;
         let offset = to_sqlite_i64(request.offset)?;
 
-        let mut stmt = self.conn.prepare(
-            "SELECT workspace, name, parent_branch_id, head_commit_id, created_at_ms, updated_at_ms \
-             FROM branches \
-             WHERE workspace=?1 \
-             ORDER BY created_at_ms ASC, name ASC \
-             LIMIT ?2 OFFSET ?3",
-        )?;
+        let mut stmt = self.conn.prepare_cached(QUERY_BRANCHES_SQL)?;
 
-        let mut rows = stmt.query(params![workspace_id, limit, offset])?;
+        let mut rows = stmt.query(params![workspace_id, limit, offset, archived])?;
         let mut out = Vec::new();
 
         while let Some(row) = rows.next()? {
@@ -150,34 +229,12 @@ impl SqliteStore {
         let workspace_id = canonicalize_workspace(&request.workspace_id)?;
         let branch_id = canonicalize_branch(&request.branch_id)?;
 
-        let tx = self.conn.transaction()?;
-        ensure_branch_exists_tx(&tx, &workspace_id, &branch_id)?;
-
-        let descendants = tx.query_row(
-            "SELECT COUNT(1) FROM branches WHERE workspace=?1 AND parent_branch_id=?2",
-            params![workspace_id, branch_id],
-            |row| row.get::<_, i64>(0),
-        )?;
-
-        if descendants > 0 {
-            return Err(StoreError::InvalidInput(
-                "branch has descendants and cannot be deleted",
-            ));
-        }
-
-        tx.execute(
-            "DELETE FROM merge_records WHERE workspace=?1 AND (source_branch=?2 OR target_branch=?2)",
-            params![workspace_id, branch_id],
-        )?;
-
-        delete_branch_commits_tx(&tx, &workspace_id, &branch_id)?;
-
-        tx.execute(
-            "DELETE FROM branches WHERE workspace=?1 AND name=?2",
-            params![workspace_id, branch_id],
-        )?;
+        let tx = self.write_tx()?;
+        ensure_unlocked(&tx, &workspace_id, self.own_lock_holder(&workspace_id))?;
+        delete_branch_tx(&tx, &workspace_id, &branch_id)?;
+        audit_tx(&tx, &workspace_id, "branch.delete", &branch_id, now_ms())?;
 
-        tx.commit()?;
+        self.commit_tx(tx)?;
         Ok(())
     }
 
@@ -185,65 +242,10 @@ impl SqliteStore {
         &mut self,
         request: AppendCommitRequest,
     ) -> Result<ThoughtCommit, StoreError> {
-        let workspace_id = canonicalize_workspace(&request.workspace_id)?;
-        let branch_id = canonicalize_branch(&request.branch_id)?;
-        let commit_id = canonicalize_commit(&request.commit_id)?;
-        let explicit_parent = request
-            .parent_commit_id
-            .as_deref()
-            .map(canonicalize_commit)
-            .transpose()?;
-
-        let tx = self.conn.transaction()?;
-        let branch_state = branch_state_tx(&tx, &workspace_id, &branch_id)?;
-
-        let parent_commit_id = explicit_parent.or(branch_state.head_commit_id);
-        if let Some(parent_commit_id) = parent_commit_id.as_deref() {
-            ensure_commit_exists_tx(&tx, &workspace_id, parent_commit_id)?;
-            ensure_commit_belongs_to_branch_tx(&tx, &workspace_id, parent_commit_id, &branch_id)?;
-        }
+        let tx = self.write_tx()?;
+        let commit = append_commit_tx(&tx, request)?;
 
-        let commit = ThoughtCommit::try_new(
-            workspace_id,
-            branch_id,
-            commit_id,
-            parent_commit_id,
-            request.message,
-            request.body,
-            request.created_at_ms,
-        )
-        .map_err(|_| StoreError::InvalidInput("invalid commit payload"))?;
-
-        let insert = tx.execute(
-            "INSERT INTO commits(workspace, branch, commit_id, parent_commit_id, message, body, created_at_ms) \
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-            params![
-                commit.workspace_id(),
-                commit.branch_id(),
-                commit.commit_id(),
-                commit.parent_commit_id(),
-                commit.message(),
-                commit.body(),
-                commit.created_at_ms(),
-            ],
-        );
-
-        if let Err(err) = insert {
-            return Err(map_insert_conflict(err));
-        }
-
-        let updated_at_ms = branch_state.updated_at_ms.max(commit.created_at_ms());
-        tx.execute(
-            "UPDATE branches SET head_commit_id=?3, updated_at_ms=?4 WHERE workspace=?1 AND name=?2",
-            params![
-                commit.workspace_id(),
-                commit.branch_id(),
-                commit.commit_id(),
-                updated_at_ms,
-            ],
-        )?;
-
-        tx.commit()?;
+        self.commit_tx(tx)?;
         Ok(commit)
     }
 
@@ -254,149 +256,16 @@ impl SqliteStore {
         let workspace_id = canonicalize_workspace(&request.workspace_id)?;
         let commit_id = canonicalize_commit(&request.commit_id)?;
 
-        let row = self
-            .conn
-            .query_row(
-                "SELECT workspace, branch, commit_id, parent_commit_id, message, body, created_at_ms \
-                 FROM commits WHERE workspace=?1 AND commit_id=?2",
-                params![workspace_id, commit_id],
-                |row| {
-                    Ok((
-                        row.get::<_, String>(0)?,
-                        row.get::<_, String>(1)?,
-                        row.get::<_, String>(2)?,
-                        row.get::<_, Option<String>>(3)?,
-                        row.get::<_, String>(4)?,
-                        row.get::<_, String>(5)?,
-                        row.get::<_, i64>(6)?,
-                    ))
-                },
-            )
-            .optional()?;
-
-        match row {
-            Some((
-                workspace,
-                branch,
-                commit_id,
-                parent_commit_id,
-                message,
-                body,
-                created_at_ms,
-            )) => Ok(Some(
-                ThoughtCommit::try_new(
-                    workspace,
-                    branch,
-                    commit_id,
-                    parent_commit_id,
-                    message,
-                    body,
-                    created_at_ms,
-                )
-                .map_err(|_| StoreError::InvalidInput("invalid commit row"))?,
-            )),
-            None => Ok(None),
-        }
+        commit_by_id(&self.conn, &workspace_id, &commit_id)
     }
 
     pub fn create_merge_record(
         &mut self,
         request: CreateMergeRecordRequest,
     ) -> Result<MergeRecord, StoreError> {
-        let workspace_id = canonicalize_workspace(&request.workspace_id)?;
-        let source_branch_id = canonicalize_branch(&request.source_branch_id)?;
-        let target_branch_id = canonicalize_branch(&request.target_branch_id)?;
-        let merge_id = canonicalize_merge(&request.merge_id)?;
-        let synthesis_commit_id = canonicalize_commit(&request.synthesis_commit_id)?;
-
-        let tx = self.conn.transaction()?;
-        ensure_branch_exists_tx(&tx, &workspace_id, &source_branch_id)?;
-        let target_state = branch_state_tx(&tx, &workspace_id, &target_branch_id)?;
-
-        let synthesis_commit = ThoughtCommit::try_new(
-            workspace_id.clone(),
-            target_branch_id.clone(),
-            synthesis_commit_id,
-            target_state.head_commit_id,
-            request.synthesis_message,
-            request.synthesis_body,
-            request.created_at_ms,
-        )
-        .map_err(|_| StoreError::InvalidInput("invalid synthesis commit payload"))?;
-
-        if let Some(parent_commit_id) = synthesis_commit.parent_commit_id() {
-            ensure_commit_exists_tx(&tx, &workspace_id, parent_commit_id)?;
-            ensure_commit_belongs_to_branch_tx(
-                &tx,
-                &workspace_id,
-                parent_commit_id,
-                &target_branch_id,
-            )?;
-        }
-
-        let merge_record = MergeRecord::try_new(
-            workspace_id,
-            merge_id,
-            source_branch_id,
-            target_branch_id,
-            synthesis_commit.commit_id(),
-            request.strategy,
-            request.summary,
-            request.created_at_ms,
-        )
-        .map_err(|_| StoreError::InvalidInput("invalid merge payload"))?;
-
-        let insert_commit = tx.execute(
-            "INSERT INTO commits(workspace, branch, commit_id, parent_commit_id, message, body, created_at_ms) \
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-            params![
-                synthesis_commit.workspace_id(),
-                synthesis_commit.branch_id(),
-                synthesis_commit.commit_id(),
-                synthesis_commit.parent_commit_id(),
-                synthesis_commit.message(),
-                synthesis_commit.body(),
-                synthesis_commit.created_at_ms(),
-            ],
-        );
-
-        if let Err(err) = insert_commit {
-            return Err(map_insert_conflict(err));
-        }
-
-        let insert_merge = tx.execute(
-            "INSERT INTO merge_records(workspace, merge_id, source_branch, target_branch, synthesis_commit_id, strategy, summary, created_at_ms) \
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
-            params![
-                merge_record.workspace_id(),
-                merge_record.merge_id(),
-                merge_record.source_branch_id(),
-                merge_record.target_branch_id(),
-                merge_record.synthesis_commit_id(),
-                merge_record.strategy(),
-                merge_record.summary(),
-                merge_record.created_at_ms(),
-            ],
-        );
-
-        if let Err(err) = insert_merge {
-            return Err(map_insert_conflict(err));
-        }
-
-        let updated_at_ms = target_state
-            .updated_at_ms
-            .max(synthesis_commit.created_at_ms());
-        tx.execute(
-            "UPDATE branches SET head_commit_id=?3, updated_at_ms=?4 WHERE workspace=?1 AND name=?2",
-            params![
-                synthesis_commit.workspace_id(),
-                synthesis_commit.branch_id(),
-                synthesis_commit.commit_id(),
-                updated_at_ms,
-            ],
-        )?;
-
-        tx.commit()?;
+        let tx = self.write_tx()?;
+        let merge_record = create_merge_record_tx(&tx, request)?;
+        self.commit_tx(tx)?;
         Ok(merge_record)
     }
 
@@ -405,34 +274,16 @@ impl SqliteStore {
         request: ListMergeRecordsRequest,
     ) -> Result<Vec<MergeRecord>, StoreError> {
         let workspace_id = canonicalize_workspace(&request.workspace_id)?;
-        let limit = to_sqlite_i64(request.limit)?;
+        let limit = to_sqlite_i64(request.limit.min(self.config.max_page_limit))?;
         let offset = to_sqlite_i64(request.offset)?;
 
-        let mut stmt = self.conn.prepare(
-            "SELECT workspace, merge_id, source_branch, target_branch, synthesis_commit_id, strategy, summary, created_at_ms \
-             FROM merge_records \
-             WHERE workspace=?1 \
-             ORDER BY created_at_ms ASC, merge_id ASC \
-             LIMIT ?2 OFFSET ?3",
-        )?;
+        let mut stmt = self.conn.prepare_cached(&list_merge_records_sql())?;
 
         let mut rows = stmt.query(params![workspace_id, limit, offset])?;
         let mut out = Vec::new();
 
         while let Some(row) = rows.next()? {
-            out.push(
-                MergeRecord::try_new(
-                    row.get::<_, String>(0)?,
-                    row.get::<_, String>(1)?,
-                    row.get::<_, String>(2)?,
-                    row.get::<_, String>(3)?,
-                    row.get::<_, String>(4)?,
-                    row.get::<_, String>(5)?,
-                    row.get::<_, String>(6)?,
-                    row.get::<_, i64>(7)?,
-                )
-                .map_err(|_| StoreError::InvalidInput("invalid merge row"))?,
-            );
+            out.push(merge_record_from_row(row)?);
         }
 
         Ok(out)
@@ -443,11 +294,8 @@ impl SqliteStore {
         let branch_id = canonicalize_branch(branch)?;
         Ok(self
             .conn
-            .query_row(
-                "SELECT 1 FROM branches WHERE workspace=?1 AND name=?2",
-                params![workspace_id, branch_id],
-                |row| row.get::<_, i64>(0),
-            )
+            .prepare_cached("SELECT 1 FROM branches WHERE workspace=?1 AND name=?2")?
+            .query_row(params![workspace_id, branch_id], |row| row.get::<_, i64>(0))
             .optional()?
             .is_some())
     }
@@ -459,11 +307,8 @@ impl SqliteStore {
         let workspace_id = canonicalize_workspace(workspace.as_str())?;
         Ok(self
             .conn
-            .query_row(
-                "SELECT branch FROM branch_checkout WHERE workspace=?1",
-                params![workspace_id],
-                |row| row.get::<_, String>(0),
-            )
+            .prepare_cached("SELECT branch FROM branch_checkout WHERE workspace=?1")?
+            .query_row(params![workspace_id], |row| row.get::<_, String>(0))
             .optional()?)
     }
 
@@ -476,34 +321,76 @@ impl SqliteStore {
         let branch_id = canonicalize_branch(branch)?;
         let now_ms = now_ms();
 
-        let tx = self.conn.transaction()?;
+        let tx = self.write_tx()?;
         ensure_workspace_tx(&tx, &workspace_id, now_ms)?;
         if !branch_exists_tx(&tx, &workspace_id, &branch_id)? {
             return Err(StoreError::UnknownBranch);
         }
+        ensure_branch_active_tx(&tx, &workspace_id, &branch_id)?;
 
-        let previous = tx
-            .query_row(
-                "SELECT branch FROM branch_checkout WHERE workspace=?1",
-                params![workspace_id],
-                |row| row.get::<_, String>(0),
-            )
-            .optional()?;
-
-        tx.execute(
-            r#"
-            INSERT INTO branch_checkout(workspace, branch, updated_at_ms)
-            VALUES (?1, ?2, ?3)
-            ON CONFLICT(workspace) DO UPDATE SET branch=excluded.branch, updated_at_ms=excluded.updated_at_ms
-            "#,
-            params![workspace_id, branch_id, now_ms],
-        )?;
+        let previous = set_checkout_tx(&tx, &workspace_id, &branch_id, now_ms)?;
+        audit_tx(&tx, &workspace_id, "branch.checkout", &branch_id, now_ms)?;
 
-        tx.commit()?;
+        self.commit_tx(tx)?;
         Ok((previous, branch_id))
     }
 }
 
+const STATEMENT_CACHE_CAPACITY: usize = 64;
+
+const COMMIT_COLUMNS: &str =
+    "workspace, branch, commit_id, parent_commit_id, message, body, created_at_ms";
+
+const INSERT_COMMIT_SQL: &str = "INSERT INTO commits(workspace, branch, commit_id, parent_commit_id, message, body, created_at_ms) \
+     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)";
+
+const INSERT_BRANCH_SQL: &str = "INSERT INTO branches(workspace, name, parent_branch_id, head_commit_id, created_at_ms, updated_at_ms) \
+     VALUES (?1, ?2, ?3, ?4, ?5, ?6)";
+
+const INSERT_MERGE_RECORD_SQL: &str = "INSERT INTO merge_records(workspace, merge_id, source_branch, target_branch, synthesis_commit_id, strategy, summary, created_at_ms) \
+     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)";
+
+const INSERT_MERGE_SOURCE_SQL: &str =
+    "INSERT INTO merge_sources(workspace, merge_id, source_head_commit_id) VALUES (?1, ?2, ?3)";
+
+const INSERT_WORKSPACE_SQL: &str =
+    "INSERT OR IGNORE INTO workspaces(workspace, created_at_ms) VALUES (?1, ?2)";
+
+const DELETE_BRANCH_MERGES_SQL: &str =
+    "DELETE FROM merge_records WHERE workspace=?1 AND (source_branch=?2 OR target_branch=?2)";
+
+const DELETE_BRANCH_SQL: &str = "DELETE FROM branches WHERE workspace=?1 AND name=?2";
+
+const DELETE_BRANCH_SCRATCH_SQL: &str =
+    "DELETE FROM branch_scratch WHERE workspace=?1 AND branch=?2";
+
+// Deletes the commits no other commit of the branch builds on; repeated until the branch is
+// empty so parent links are never left dangling.
+const DELETE_LEAF_COMMITS_SQL: &str = "DELETE FROM commits \
+     WHERE workspace=?1 AND branch=?2 \
+       AND commit_id NOT IN ( \
+           SELECT parent_commit_id \
+           FROM commits \
+           WHERE workspace=?1 AND branch=?2 AND parent_commit_id IS NOT NULL \
+       )";
+
+const UPDATE_BRANCH_HEAD_SQL: &str =
+    "UPDATE branches SET head_commit_id=?3, updated_at_ms=?4 WHERE workspace=?1 AND name=?2";
+
+const UPSERT_WORKSPACE_STATE_SQL: &str = "INSERT INTO workspace_state(singleton, schema_version, created_at_ms, updated_at_ms) \
+     VALUES (1, ?1, ?2, ?2) \
+     ON CONFLICT(singleton) DO UPDATE SET schema_version=excluded.schema_version, updated_at_ms=excluded.updated_at_ms";
+
+// `?4` selects archived (1) or active (0) branches.
+const QUERY_BRANCHES_SQL: &str = "SELECT workspace, name, parent_branch_id, head_commit_id, created_at_ms, updated_at_ms \
+     FROM branches \
+     WHERE workspace=?1 \
+       AND EXISTS (SELECT 1 FROM branch_archive a WHERE a.workspace=branches.workspace AND a.branch=branches.name)=?4 \
+     ORDER BY created_at_ms ASC, name ASC \
+     LIMIT ?2 OFFSET ?3";
+
+const MERGE_COLUMNS: &str = "workspace, merge_id, source_branch, target_branch, synthesis_commit_id, strategy, summary, created_at_ms";
+
 #[derive(Debug)]
 struct BranchState {
     head_commit_id: Option<String>,
@@ -524,20 +411,12 @@ fn preflight_gate(conn: &Connection) -> Result<(), StoreError> {
         return Ok(());
     }
 
-    let required: BTreeSet<&str> = [
-        "workspace_state",
-        "workspaces",
-        "branches",
-        "branch_checkout",
-        "commits",
-        "merge_records",
-    ]
-    .into_iter()
-    .collect();
+    let required: BTreeSet<&str> = V3_TABLES.into_iter().collect();
+    let additive: BTreeSet<&str> = V3_ADDITIVE_TABLES.into_iter().collect();
 
     if tables
         .iter()
-        .any(|table| !required.contains(table.as_str()))
+        .any(|table| !required.contains(table.as_str()) && !additive.contains(table.as_str()))
     {
         return Err(StoreError::InvalidInput(
             "RESET_REQUIRED: unsupported tables detected",
@@ -553,11 +432,8 @@ fn preflight_gate(conn: &Connection) -> Result<(), StoreError> {
     }
 
     let version = conn
-        .query_row(
-            "SELECT schema_version FROM workspace_state WHERE singleton=1",
-            [],
-            |row| row.get::<_, i64>(0),
-        )
+        .prepare_cached("SELECT schema_version FROM workspace_state WHERE singleton=1")?
+        .query_row([], |row| row.get::<_, i64>(0))
         .optional()?;
 
     match version {
@@ -660,29 +536,394 @@ fn install_schema(conn: &Connection) -> Result<(), StoreError> {
 
         CREATE INDEX IF NOT EXISTS idx_merge_records_workspace_created
           ON merge_records(workspace, created_at_ms, merge_id);
+
+        CREATE INDEX IF NOT EXISTS idx_merge_records_workspace_synthesis
+          ON merge_records(workspace, synthesis_commit_id);
+
+        CREATE TABLE IF NOT EXISTS merge_sources (
+          workspace TEXT NOT NULL,
+          merge_id TEXT NOT NULL,
+          source_head_commit_id TEXT NOT NULL,
+          PRIMARY KEY(workspace, merge_id),
+          FOREIGN KEY(workspace, merge_id)
+            REFERENCES merge_records(workspace, merge_id)
+            ON DELETE CASCADE,
+          FOREIGN KEY(workspace, source_head_commit_id)
+            REFERENCES commits(workspace, commit_id)
+            ON DELETE RESTRICT
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_merge_sources_workspace_head
+          ON merge_sources(workspace, source_head_commit_id);
+
+        CREATE TABLE IF NOT EXISTS commit_authors (
+          workspace TEXT NOT NULL,
+          commit_id TEXT NOT NULL,
+          author TEXT NOT NULL,
+          PRIMARY KEY(workspace, commit_id),
+          FOREIGN KEY(workspace, commit_id)
+            REFERENCES commits(workspace, commit_id)
+            ON DELETE CASCADE
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_commit_authors_workspace_author
+          ON commit_authors(workspace, author, commit_id);
+
+        CREATE TABLE IF NOT EXISTS audit_log (
+          workspace TEXT NOT NULL,
+          seq INTEGER NOT NULL,
+          at_ms INTEGER NOT NULL,
+          op TEXT NOT NULL,
+          subject TEXT NOT NULL,
+          prev_hash TEXT NOT NULL,
+          hash TEXT NOT NULL,
+          digest TEXT,
+          branch TEXT,
+          PRIMARY KEY(workspace, seq)
+        );
+
+        CREATE TABLE IF NOT EXISTS audit_head (
+          workspace TEXT PRIMARY KEY,
+          seq INTEGER NOT NULL,
+          hash TEXT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS commit_picks (
+          workspace TEXT NOT NULL,
+          commit_id TEXT NOT NULL,
+          source_commit_id TEXT NOT NULL,
+          target_branch TEXT NOT NULL,
+          picked_at_ms INTEGER NOT NULL,
+          PRIMARY KEY(workspace, commit_id),
+          UNIQUE(workspace, target_branch, source_commit_id),
+          FOREIGN KEY(workspace, commit_id)
+            REFERENCES commits(workspace, commit_id)
+            ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS checkout_stack (
+          workspace TEXT NOT NULL,
+          depth INTEGER NOT NULL,
+          branch TEXT NOT NULL,
+          pushed_at_ms INTEGER NOT NULL,
+          PRIMARY KEY(workspace, depth)
+        );
+
+        CREATE TABLE IF NOT EXISTS commit_copies (
+          workspace TEXT NOT NULL,
+          commit_id TEXT NOT NULL,
+          source_workspace TEXT NOT NULL,
+          source_commit_id TEXT NOT NULL,
+          copied_at_ms INTEGER NOT NULL,
+          PRIMARY KEY(workspace, commit_id),
+          FOREIGN KEY(workspace, commit_id)
+            REFERENCES commits(workspace, commit_id)
+            ON DELETE CASCADE
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_commit_copies_source
+          ON commit_copies(source_workspace, source_commit_id);
+
+        CREATE TABLE IF NOT EXISTS commit_redactions (
+          workspace TEXT NOT NULL,
+          commit_id TEXT NOT NULL,
+          actor TEXT NOT NULL,
+          reason TEXT NOT NULL,
+          body_sha256 TEXT NOT NULL,
+          redacted_at_ms INTEGER NOT NULL,
+          message_sha256 TEXT,
+          PRIMARY KEY(workspace, commit_id),
+          FOREIGN KEY(workspace, commit_id)
+            REFERENCES commits(workspace, commit_id)
+            ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS workspace_locks (
+          workspace TEXT PRIMARY KEY,
+          holder TEXT NOT NULL,
+          purpose TEXT NOT NULL,
+          acquired_at_ms INTEGER NOT NULL,
+          expires_at_ms INTEGER NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS workspace_counters (
+          workspace TEXT NOT NULL,
+          name TEXT NOT NULL,
+          value INTEGER NOT NULL,
+          PRIMARY KEY(workspace, name)
+        );
+
+        CREATE TABLE IF NOT EXISTS commit_acks (
+          workspace TEXT NOT NULL,
+          commit_id TEXT NOT NULL,
+          actor TEXT NOT NULL,
+          kind TEXT NOT NULL CHECK(kind IN ('seen', 'agree', 'disagree')),
+          acked_at_ms INTEGER NOT NULL,
+          PRIMARY KEY(workspace, commit_id, actor, kind),
+          FOREIGN KEY(workspace, commit_id)
+            REFERENCES commits(workspace, commit_id)
+            ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS commit_templates (
+          workspace TEXT NOT NULL,
+          name TEXT NOT NULL,
+          body TEXT NOT NULL,
+          updated_at_ms INTEGER NOT NULL,
+          PRIMARY KEY(workspace, name)
+        );
+
+        CREATE TABLE IF NOT EXISTS commit_template_uses (
+          workspace TEXT NOT NULL,
+          commit_id TEXT NOT NULL,
+          template TEXT NOT NULL,
+          PRIMARY KEY(workspace, commit_id)
+        );
+
+        CREATE TABLE IF NOT EXISTS branch_scratch (
+          workspace TEXT NOT NULL,
+          branch TEXT NOT NULL,
+          expires_at_ms INTEGER NOT NULL,
+          PRIMARY KEY(workspace, branch)
+        );
+
+        CREATE TABLE IF NOT EXISTS branch_archive (
+          workspace TEXT NOT NULL,
+          branch TEXT NOT NULL,
+          archived_at_ms INTEGER NOT NULL,
+          PRIMARY KEY(workspace, branch),
+          FOREIGN KEY(workspace, branch)
+            REFERENCES branches(workspace, name)
+            ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS commit_pins (
+          workspace TEXT NOT NULL,
+          commit_id TEXT NOT NULL,
+          pinned_at_ms INTEGER NOT NULL,
+          PRIMARY KEY(workspace, commit_id),
+          FOREIGN KEY(workspace, commit_id)
+            REFERENCES commits(workspace, commit_id)
+            ON DELETE CASCADE
+        );
         "#,
     )?;
+    // Columns added to additive tables after their first release; rows written before carry
+    // NULL, which readers treat as "not recorded".
+    ensure_column(conn, "audit_log", "digest", "TEXT")?;
+    ensure_column(conn, "audit_log", "branch", "TEXT")?;
+    ensure_column(conn, "commit_redactions", "message_sha256", "TEXT")?;
 
-    conn.execute(
-        "INSERT INTO workspace_state(singleton, schema_version, created_at_ms, updated_at_ms) \
-         VALUES (1, ?1, ?2, ?2) \
-         ON CONFLICT(singleton) DO UPDATE SET schema_version=excluded.schema_version, updated_at_ms=excluded.updated_at_ms",
-        params![V3_SCHEMA_VERSION, now_ms],
-    )?;
+    let mut stmt = conn.prepare_cached(UPSERT_WORKSPACE_STATE_SQL)?;
+    stmt.execute(params![V3_SCHEMA_VERSION, now_ms])?;
 
     Ok(())
 }
 
+fn ensure_column(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    decl: &str,
+) -> Result<(), StoreError> {
+    let present = conn
+        .prepare("SELECT 1 FROM pragma_table_info(?1) WHERE name=?2")?
+        .exists(params![table, column])?;
+    if !present {
+        conn.execute_batch(&format!("ALTER TABLE {table} ADD COLUMN {column} {decl}"))?;
+    }
+    Ok(())
+}
+
+fn create_merge_record_tx(
+    tx: &Transaction<'_>,
+    request: CreateMergeRecordRequest,
+) -> Result<MergeRecord, StoreError> {
+    let workspace_id = canonicalize_workspace(&request.workspace_id)?;
+    let source_branch_id = canonicalize_branch(&request.source_branch_id)?;
+    let target_branch_id = canonicalize_branch(&request.target_branch_id)?;
+    let merge_id = canonicalize_merge(&request.merge_id)?;
+    let synthesis_commit_id = canonicalize_commit(&request.synthesis_commit_id)?;
+    let author = request
+        .author
+        .as_deref()
+        .map(canonicalize_author)
+        .transpose()?;
+
+    let source_state = branch_state_tx(tx, &workspace_id, &source_branch_id)?;
+    let target_state = branch_state_tx(tx, &workspace_id, &target_branch_id)?;
+    ensure_branch_active_tx(tx, &workspace_id, &source_branch_id)?;
+    ensure_branch_active_tx(tx, &workspace_id, &target_branch_id)?;
+
+    let synthesis_commit = ThoughtCommit::try_new(
+        workspace_id.clone(),
+        target_branch_id.clone(),
+        synthesis_commit_id,
+        target_state.head_commit_id,
+        request.synthesis_message,
+        request.synthesis_body,
+        request.created_at_ms,
+    )
+    .map_err(|_| StoreError::InvalidInput("invalid synthesis commit payload"))?;
+
+    if let Some(parent_commit_id) = synthesis_commit.parent_commit_id() {
+        ensure_commit_exists_tx(tx, &workspace_id, parent_commit_id)?;
+        ensure_commit_belongs_to_branch_tx(tx, &workspace_id, parent_commit_id, &target_branch_id)?;
+    }
+
+    let merge_record = MergeRecord::try_new(
+        workspace_id,
+        merge_id,
+        source_branch_id,
+        target_branch_id,
+        synthesis_commit.commit_id(),
+        request.strategy,
+        request.summary,
+        request.created_at_ms,
+    )
+    .map_err(|_| StoreError::InvalidInput("invalid merge payload"))?;
+
+    let mut stmt = tx.prepare_cached(INSERT_COMMIT_SQL)?;
+    stmt.execute(params![
+        synthesis_commit.workspace_id(),
+        synthesis_commit.branch_id(),
+        synthesis_commit.commit_id(),
+        synthesis_commit.parent_commit_id(),
+        synthesis_commit.message(),
+        synthesis_commit.body(),
+        synthesis_commit.created_at_ms(),
+    ])
+    .map_err(map_insert_conflict)?;
+
+    if let Some(author) = author.as_deref() {
+        insert_commit_author_tx(
+            tx,
+            synthesis_commit.workspace_id(),
+            synthesis_commit.commit_id(),
+            author,
+        )?;
+    }
+
+    let mut stmt = tx.prepare_cached(INSERT_MERGE_RECORD_SQL)?;
+    stmt.execute(params![
+        merge_record.workspace_id(),
+        merge_record.merge_id(),
+        merge_record.source_branch_id(),
+        merge_record.target_branch_id(),
+        merge_record.synthesis_commit_id(),
+        merge_record.strategy(),
+        merge_record.summary(),
+        merge_record.created_at_ms(),
+    ])
+    .map_err(map_insert_conflict)?;
+
+    if let Some(source_head_commit_id) = source_state.head_commit_id.as_deref() {
+        let mut stmt = tx.prepare_cached(INSERT_MERGE_SOURCE_SQL)?;
+        stmt.execute(params![
+            merge_record.workspace_id(),
+            merge_record.merge_id(),
+            source_head_commit_id,
+        ])?;
+    }
+
+    let updated_at_ms = target_state
+        .updated_at_ms
+        .max(synthesis_commit.created_at_ms());
+    let mut stmt = tx.prepare_cached(UPDATE_BRANCH_HEAD_SQL)?;
+    stmt.execute(params![
+        synthesis_commit.workspace_id(),
+        synthesis_commit.branch_id(),
+        synthesis_commit.commit_id(),
+        updated_at_ms,
+    ])?;
+
+    audit_commit_tx(
+        tx,
+        merge_record.workspace_id(),
+        "merge.create",
+        merge_record.merge_id(),
+        &synthesis_commit,
+        merge_record.created_at_ms(),
+    )?;
+
+    Ok(merge_record)
+}
+
 fn ensure_workspace_tx(
     tx: &Transaction<'_>,
     workspace_id: &str,
     now_ms: i64,
 ) -> Result<(), StoreError> {
+    let mut stmt = tx.prepare_cached(INSERT_WORKSPACE_SQL)?;
+    stmt.execute(params![workspace_id, now_ms])?;
+    Ok(())
+}
+
+fn insert_branch_tx(
+    tx: &Transaction<'_>,
+    workspace_id: &str,
+    branch_id: &str,
+    parent_branch_id: Option<&str>,
+    created_at_ms: i64,
+    max_depth: usize,
+) -> Result<ThoughtBranch, StoreError> {
+    let parent_head_commit_id = if let Some(parent_branch_id) = parent_branch_id {
+        let state = branch_state_tx(tx, workspace_id, parent_branch_id)?;
+        ensure_branch_active_tx(tx, workspace_id, parent_branch_id)?;
+        let depth = branch_depth_tx(tx, workspace_id, parent_branch_id, max_depth)?;
+        if depth + 1 > max_depth {
+            return Err(StoreError::BranchDepthExceeded);
+        }
+        state.head_commit_id
+    } else {
+        None
+    };
+
+    let branch = ThoughtBranch::try_new(
+        workspace_id,
+        branch_id,
+        parent_branch_id.map(ToOwned::to_owned),
+        parent_head_commit_id,
+        created_at_ms,
+        created_at_ms,
+    )
+    .map_err(|_| StoreError::InvalidInput("invalid branch payload"))?;
+
+    let mut stmt = tx.prepare_cached(INSERT_BRANCH_SQL)?;
+    stmt.execute(params![
+        branch.workspace_id(),
+        branch.branch_id(),
+        branch.parent_branch_id(),
+        branch.head_commit_id(),
+        branch.created_at_ms(),
+        branch.updated_at_ms(),
+    ])
+    .map_err(map_insert_conflict)?;
+
+    Ok(branch)
+}
+
+fn set_checkout_tx(
+    tx: &Transaction<'_>,
+    workspace_id: &str,
+    branch_id: &str,
+    now_ms: i64,
+) -> Result<Option<String>, StoreError> {
+    let previous = tx
+        .prepare_cached("SELECT branch FROM branch_checkout WHERE workspace=?1")?
+        .query_row(params![workspace_id], |row| row.get::<_, String>(0))
+        .optional()?;
+
     tx.execute(
-        "INSERT OR IGNORE INTO workspaces(workspace, created_at_ms) VALUES (?1, ?2)",
-        params![workspace_id, now_ms],
+        r#"
+        INSERT INTO branch_checkout(workspace, branch, updated_at_ms)
+        VALUES (?1, ?2, ?3)
+        ON CONFLICT(workspace) DO UPDATE SET branch=excluded.branch, updated_at_ms=excluded.updated_at_ms
+        "#,
+        params![workspace_id, branch_id, now_ms],
     )?;
-    Ok(())
+
+    Ok(previous)
 }
 
 fn branch_exists_tx(
@@ -691,11 +932,8 @@ fn branch_exists_tx(
     branch_id: &str,
 ) -> Result<bool, StoreError> {
     Ok(tx
-        .query_row(
-            "SELECT 1 FROM branches WHERE workspace=?1 AND name=?2",
-            params![workspace_id, branch_id],
-            |row| row.get::<_, i64>(0),
-        )
+        .prepare_cached("SELECT 1 FROM branches WHERE workspace=?1 AND name=?2")?
+        .query_row(params![workspace_id, branch_id], |row| row.get::<_, i64>(0))
         .optional()?
         .is_some())
 }
@@ -718,11 +956,12 @@ fn branch_state_tx(
     branch_id: &str,
 ) -> Result<BranchState, StoreError> {
     let value = tx
-        .query_row(
+        .prepare_cached(
             "SELECT head_commit_id, updated_at_ms FROM branches WHERE workspace=?1 AND name=?2",
-            params![workspace_id, branch_id],
-            |row| Ok((row.get::<_, Option<String>>(0)?, row.get::<_, i64>(1)?)),
-        )
+        )?
+        .query_row(params![workspace_id, branch_id], |row| {
+            Ok((row.get::<_, Option<String>>(0)?, row.get::<_, i64>(1)?))
+        })
         .optional()?;
 
     match value {
@@ -738,6 +977,7 @@ fn branch_depth_tx(
     tx: &Transaction<'_>,
     workspace_id: &str,
     branch_id: &str,
+    max_depth: usize,
 ) -> Result<usize, StoreError> {
     let mut current = Some(branch_id.to_string());
     let mut depth = 0usize;
@@ -749,18 +989,17 @@ fn branch_depth_tx(
         }
 
         let parent = tx
-            .query_row(
-                "SELECT parent_branch_id FROM branches WHERE workspace=?1 AND name=?2",
-                params![workspace_id, branch],
-                |row| row.get::<_, Option<String>>(0),
-            )
+            .prepare_cached("SELECT parent_branch_id FROM branches WHERE workspace=?1 AND name=?2")?
+            .query_row(params![workspace_id, branch], |row| {
+                row.get::<_, Option<String>>(0)
+            })
             .optional()?
             .flatten();
 
         current = parent;
         if current.is_some() {
             depth = depth.saturating_add(1);
-            if depth > MAX_BRANCH_DEPTH {
+            if depth > max_depth {
                 return Err(StoreError::BranchDepthExceeded);
             }
         }
@@ -775,11 +1014,8 @@ fn ensure_commit_exists_tx(
     commit_id: &str,
 ) -> Result<(), StoreError> {
     let exists = tx
-        .query_row(
-            "SELECT 1 FROM commits WHERE workspace=?1 AND commit_id=?2",
-            params![workspace_id, commit_id],
-            |row| row.get::<_, i64>(0),
-        )
+        .prepare_cached("SELECT 1 FROM commits WHERE workspace=?1 AND commit_id=?2")?
+        .query_row(params![workspace_id, commit_id], |row| row.get::<_, i64>(0))
         .optional()?
         .is_some();
 
@@ -797,11 +1033,10 @@ fn ensure_commit_belongs_to_branch_tx(
     branch_id: &str,
 ) -> Result<(), StoreError> {
     let belongs = tx
-        .query_row(
-            "SELECT 1 FROM commits WHERE workspace=?1 AND commit_id=?2 AND branch=?3",
-            params![workspace_id, commit_id, branch_id],
-            |row| row.get::<_, i64>(0),
-        )
+        .prepare_cached("SELECT 1 FROM commits WHERE workspace=?1 AND commit_id=?2 AND branch=?3")?
+        .query_row(params![workspace_id, commit_id, branch_id], |row| {
+            row.get::<_, i64>(0)
+        })
         .optional()?
         .is_some();
 
@@ -814,22 +1049,132 @@ fn ensure_commit_belongs_to_branch_tx(
     }
 }
 
+/// Appends one commit inside an open write transaction and moves the branch head to it.
+fn append_commit_tx(
+    tx: &Transaction<'_>,
+    request: AppendCommitRequest,
+) -> Result<ThoughtCommit, StoreError> {
+    let workspace_id = canonicalize_workspace(&request.workspace_id)?;
+    let branch_id = canonicalize_branch(&request.branch_id)?;
+    let commit_id = canonicalize_commit(&request.commit_id)?;
+    let explicit_parent = request
+        .parent_commit_id
+        .as_deref()
+        .map(canonicalize_commit)
+        .transpose()?;
+
+    let expected_head_commit_id = request
+        .expected_head_commit_id
+        .as_deref()
+        .map(canonicalize_commit)
+        .transpose()?;
+    let author = request
+        .author
+        .as_deref()
+        .map(canonicalize_author)
+        .transpose()?;
+
+    let branch_state = branch_state_tx(tx, &workspace_id, &branch_id)?;
+    ensure_branch_active_tx(tx, &workspace_id, &branch_id)?;
+
+    if let Some(expected) = expected_head_commit_id.as_deref()
+        && branch_state.head_commit_id.as_deref() != Some(expected)
+    {
+        return Err(StoreError::HeadMismatch {
+            current_head: branch_state.head_commit_id,
+        });
+    }
+
+    let parent_commit_id = explicit_parent.or(branch_state.head_commit_id);
+    if let Some(parent_commit_id) = parent_commit_id.as_deref() {
+        ensure_commit_exists_tx(tx, &workspace_id, parent_commit_id)?;
+        ensure_commit_belongs_to_branch_tx(tx, &workspace_id, parent_commit_id, &branch_id)?;
+    }
+
+    let commit = ThoughtCommit::try_new(
+        workspace_id,
+        branch_id,
+        commit_id,
+        parent_commit_id,
+        request.message,
+        request.body,
+        request.created_at_ms,
+    )
+    .map_err(|_| StoreError::InvalidInput("invalid commit payload"))?;
+
+    let mut stmt = tx.prepare_cached(INSERT_COMMIT_SQL)?;
+    stmt.execute(params![
+        commit.workspace_id(),
+        commit.branch_id(),
+        commit.commit_id(),
+        commit.parent_commit_id(),
+        commit.message(),
+        commit.body(),
+        commit.created_at_ms(),
+    ])
+    .map_err(map_insert_conflict)?;
+
+    if let Some(author) = author.as_deref() {
+        insert_commit_author_tx(tx, commit.workspace_id(), commit.commit_id(), author)?;
+    }
+
+    let updated_at_ms = branch_state.updated_at_ms.max(commit.created_at_ms());
+    let mut stmt = tx.prepare_cached(UPDATE_BRANCH_HEAD_SQL)?;
+    stmt.execute(params![
+        commit.workspace_id(),
+        commit.branch_id(),
+        commit.commit_id(),
+        updated_at_ms,
+    ])?;
+    audit_commit_tx(
+        tx,
+        commit.workspace_id(),
+        "commit.append",
+        commit.commit_id(),
+        &commit,
+        commit.created_at_ms(),
+    )?;
+    Ok(commit)
+}
+
+/// Removes a leaf branch with its commits, merge records and scratch marker.
+fn delete_branch_tx(
+    tx: &Transaction<'_>,
+    workspace_id: &str,
+    branch_id: &str,
+) -> Result<(), StoreError> {
+    ensure_branch_exists_tx(tx, workspace_id, branch_id)?;
+
+    let descendants = tx
+        .prepare_cached("SELECT COUNT(1) FROM branches WHERE workspace=?1 AND parent_branch_id=?2")?
+        .query_row(params![workspace_id, branch_id], |row| row.get::<_, i64>(0))?;
+
+    if descendants > 0 {
+        return Err(StoreError::InvalidInput(
+            "branch has descendants and cannot be deleted",
+        ));
+    }
+
+    let mut stmt = tx.prepare_cached(DELETE_BRANCH_MERGES_SQL)?;
+    stmt.execute(params![workspace_id, branch_id])?;
+
+    delete_branch_commits_tx(tx, workspace_id, branch_id)?;
+
+    let mut stmt = tx.prepare_cached(DELETE_BRANCH_SQL)?;
+    stmt.execute(params![workspace_id, branch_id])?;
+    let mut stmt = tx.prepare_cached(DELETE_BRANCH_SCRATCH_SQL)?;
+    stmt.execute(params![workspace_id, branch_id])?;
+    Ok(())
+}
+
 fn delete_branch_commits_tx(
     tx: &Transaction<'_>,
     workspace_id: &str,
     branch_id: &str,
 ) -> Result<(), StoreError> {
+    let mut stmt = tx.prepare_cached(DELETE_LEAF_COMMITS_SQL)?;
     loop {
-        let deleted = tx.execute(
-            "DELETE FROM commits \
-             WHERE workspace=?1 AND branch=?2 \
-               AND commit_id NOT IN ( \
-                   SELECT parent_commit_id \
-                   FROM commits \
-                   WHERE workspace=?1 AND branch=?2 AND parent_commit_id IS NOT NULL \
-               )",
-            params![workspace_id, branch_id],
-        )?;
+        let deleted = stmt.execute(params![workspace_id, branch_id])?;
 
         if deleted == 0 {
             break;
@@ -838,6 +1183,60 @@ fn delete_branch_commits_tx(
     Ok(())
 }
 
+fn commit_from_row(row: &Row<'_>) -> Result<ThoughtCommit, StoreError> {
+    ThoughtCommit::try_new(
+        row.get::<_, String>(0)?,
+        row.get::<_, String>(1)?,
+        row.get::<_, String>(2)?,
+        row.get::<_, Option<String>>(3)?,
+        row.get::<_, String>(4)?,
+        row.get::<_, String>(5)?,
+        row.get::<_, i64>(6)?,
+    )
+    .map_err(|_| StoreError::InvalidInput("invalid commit row"))
+}
+
+fn merge_record_from_row(row: &Row<'_>) -> Result<MergeRecord, StoreError> {
+    MergeRecord::try_new(
+        row.get::<_, String>(0)?,
+        row.get::<_, String>(1)?,
+        row.get::<_, String>(2)?,
+        row.get::<_, String>(3)?,
+        row.get::<_, String>(4)?,
+        row.get::<_, String>(5)?,
+        row.get::<_, String>(6)?,
+        row.get::<_, i64>(7)?,
+    )
+    .map_err(|_| StoreError::InvalidInput("invalid merge row"))
+}
+
+fn commit_by_id_sql() -> String {
+    format!("SELECT {COMMIT_COLUMNS} FROM commits WHERE workspace=?1 AND commit_id=?2")
+}
+
+fn list_merge_records_sql() -> String {
+    format!(
+        "SELECT {MERGE_COLUMNS} \
+         FROM merge_records \
+         WHERE workspace=?1 \
+         ORDER BY created_at_ms ASC, merge_id ASC \
+         LIMIT ?2 OFFSET ?3"
+    )
+}
+
+fn commit_by_id(
+    conn: &Connection,
+    workspace_id: &str,
+    commit_id: &str,
+) -> Result<Option<ThoughtCommit>, StoreError> {
+    let mut stmt = conn.prepare_cached(&commit_by_id_sql())?;
+    let mut rows = stmt.query(params![workspace_id, commit_id])?;
+    match rows.next()? {
+        Some(row) => Ok(Some(commit_from_row(row)?)),
+        None => Ok(None),
+    }
+}
+
 fn map_insert_conflict(err: rusqlite::Error) -> StoreError {
     if is_constraint_violation(&err) {
         return StoreError::BranchAlreadyExists;
@@ -877,6 +1276,11 @@ fn canonicalize_commit(value: &str) -> Result<String, StoreError> {
         .map_err(|_| StoreError::InvalidInput("invalid commit_id"))
 }
 
+fn canonicalize_author(value: &str) -> Result<String, StoreError> {
+    canonical_identifier("author", value.to_string())
+        .map_err(|_| StoreError::InvalidInput("invalid author"))
+}
+
 fn canonicalize_merge(value: &str) -> Result<String, StoreError> {
     canonical_identifier("merge_id", value.to_string())
         .map_err(|_| StoreError::InvalidInput("invalid merge_id"))
@@ -0,0 +1,77 @@
+#![forbid(unsafe_code)]
+
+use super::{
+    COMMIT_COLUMNS, CommitPinRequest, ListPinnedCommitsRequest, SqliteStore, StoreError,
+    audit::audit_tx, canonicalize_branch, canonicalize_commit, canonicalize_workspace,
+    commit_by_id, commit_from_row, to_sqlite_i64,
+};
+use bm_core::ThoughtCommit;
+use rusqlite::params;
+
+const INSERT_COMMIT_PIN_SQL: &str =
+    "INSERT OR IGNORE INTO commit_pins(workspace, commit_id, pinned_at_ms) VALUES (?1, ?2, ?3)";
+
+const DELETE_COMMIT_PIN_SQL: &str = "DELETE FROM commit_pins WHERE workspace=?1 AND commit_id=?2";
+
+impl SqliteStore {
+    /// Pins or unpins a commit. Returns `true` when the pin state changed.
+    ///
+    /// Pinned commits are read back by `list_pinned_commits` regardless of how far
+    /// they have scrolled out of the branch log.
+    pub fn commit_pin_set(&mut self, request: CommitPinRequest) -> Result<bool, StoreError> {
+        let workspace_id = canonicalize_workspace(&request.workspace_id)?;
+        let commit_id = canonicalize_commit(&request.commit_id)?;
+
+        let tx = self.write_tx()?;
+        if commit_by_id(&tx, &workspace_id, &commit_id)?.is_none() {
+            return Err(StoreError::UnknownId);
+        }
+
+        let changed = if request.pinned {
+            let mut stmt = tx.prepare_cached(INSERT_COMMIT_PIN_SQL)?;
+            stmt.execute(params![workspace_id, commit_id, request.pinned_at_ms])?
+        } else {
+            let mut stmt = tx.prepare_cached(DELETE_COMMIT_PIN_SQL)?;
+            stmt.execute(params![workspace_id, commit_id])?
+        };
+        if changed > 0 {
+            let op = if request.pinned {
+                "commit.pin"
+            } else {
+                "commit.unpin"
+            };
+            audit_tx(&tx, &workspace_id, op, &commit_id, request.pinned_at_ms)?;
+        }
+
+        self.commit_tx(tx)?;
+        Ok(changed > 0)
+    }
+
+    /// Lists up to `limit` pinned commits of a branch, newest first.
+    pub fn list_pinned_commits(
+        &self,
+        request: ListPinnedCommitsRequest,
+    ) -> Result<Vec<ThoughtCommit>, StoreError> {
+        let workspace_id = canonicalize_workspace(&request.workspace_id)?;
+        let branch_id = canonicalize_branch(&request.branch_id)?;
+        let limit = to_sqlite_i64(request.limit.min(self.config.max_page_limit))?;
+
+        let mut stmt = self.conn.prepare_cached(&list_pinned_commits_sql())?;
+        let mut rows = stmt.query(params![workspace_id, branch_id, limit])?;
+        let mut out = Vec::new();
+        while let Some(row) = rows.next()? {
+            out.push(commit_from_row(row)?);
+        }
+        Ok(out)
+    }
+}
+
+pub(super) fn list_pinned_commits_sql() -> String {
+    format!(
+        "SELECT {COMMIT_COLUMNS} FROM commits \
+         WHERE workspace=?1 AND branch=?2 \
+           AND commit_id IN (SELECT commit_id FROM commit_pins WHERE workspace=?1) \
+         ORDER BY created_at_ms DESC, commit_id ASC \
+         LIMIT ?3"
+    )
+}
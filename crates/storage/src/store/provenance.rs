@@ -0,0 +1,152 @@
+#![forbid(unsafe_code)]
+
+use super::authors::author_by_commit;
+use super::{
+    CommitDescendantsRequest, CommitProvenanceRequest, MERGE_COLUMNS, SqliteStore, StoreError,
+    canonicalize_commit, canonicalize_workspace, commit_by_id, merge_record_from_row,
+};
+use bm_core::{MergeRecord, ThoughtCommit};
+use rusqlite::{Connection, OptionalExtension, params};
+use std::collections::{BTreeSet, VecDeque};
+
+/// One commit in a merge provenance chain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProvenanceStep {
+    pub commit: ThoughtCommit,
+    /// Writer attributed to `commit`, if any.
+    pub author: Option<String>,
+    /// Merge that produced `commit` as its synthesis commit, if any.
+    pub merge: Option<MergeRecord>,
+}
+
+impl SqliteStore {
+    /// Resolves the merge chain behind a commit back to the commit it originated from.
+    ///
+    /// The first step is the requested commit. Every following step is the source branch head
+    /// recorded by the merge that synthesized the previous step; the last step is the origin.
+    pub fn commit_provenance(
+        &self,
+        request: CommitProvenanceRequest,
+    ) -> Result<Vec<ProvenanceStep>, StoreError> {
+        let workspace_id = canonicalize_workspace(&request.workspace_id)?;
+        let commit_id = canonicalize_commit(&request.commit_id)?;
+
+        let mut chain = Vec::new();
+        let mut seen = BTreeSet::new();
+        let mut cursor = Some(commit_id);
+
+        while let Some(commit_id) = cursor {
+            if !seen.insert(commit_id.clone()) {
+                return Err(StoreError::InvalidInput("merge provenance cycle detected"));
+            }
+
+            let commit = commit_by_id(&self.conn, &workspace_id, &commit_id)?
+                .ok_or(StoreError::UnknownId)?;
+            let merge = merge_by_synthesis(&self.conn, &workspace_id, &commit_id)?;
+            cursor = match merge.as_ref() {
+                Some(merge) => merge_source_head(&self.conn, &workspace_id, merge.merge_id())?,
+                None => None,
+            };
+            let author = author_by_commit(&self.conn, &workspace_id, &commit_id)?;
+            chain.push(ProvenanceStep {
+                commit,
+                author,
+                merge,
+            });
+        }
+
+        Ok(chain)
+    }
+
+    /// Lists synthesis commits that inherited a commit through merges, directly or transitively.
+    ///
+    /// A merge inherits a commit when the source head it recorded is that commit or one of its
+    /// descendants. Results are breadth-first and bounded by `limit`.
+    pub fn commit_descendants(
+        &self,
+        request: CommitDescendantsRequest,
+    ) -> Result<Vec<ProvenanceStep>, StoreError> {
+        let workspace_id = canonicalize_workspace(&request.workspace_id)?;
+        let commit_id = canonicalize_commit(&request.commit_id)?;
+
+        if commit_by_id(&self.conn, &workspace_id, &commit_id)?.is_none() {
+            return Err(StoreError::UnknownId);
+        }
+
+        let mut stmt = self.conn.prepare(&format!(
+            "WITH RECURSIVE reach(commit_id) AS ( \
+                 SELECT ?2 \
+                 UNION \
+                 SELECT c.commit_id FROM commits c \
+                 JOIN reach r ON c.parent_commit_id = r.commit_id \
+                 WHERE c.workspace=?1 \
+             ) \
+             SELECT {MERGE_COLUMNS} FROM merge_records \
+             WHERE workspace=?1 AND merge_id IN ( \
+                 SELECT merge_id FROM merge_sources \
+                 WHERE workspace=?1 AND source_head_commit_id IN (SELECT commit_id FROM reach) \
+             ) \
+             ORDER BY created_at_ms ASC, merge_id ASC"
+        ))?;
+
+        let mut out = Vec::new();
+        let mut seen = BTreeSet::from([commit_id.clone()]);
+        let mut queue = VecDeque::from([commit_id]);
+
+        while let Some(current) = queue.pop_front() {
+            let mut rows = stmt.query(params![workspace_id, current])?;
+            while let Some(row) = rows.next()? {
+                if out.len() >= request.limit.min(self.config.max_page_limit) {
+                    return Ok(out);
+                }
+
+                let merge = merge_record_from_row(row)?;
+                let synthesis_commit_id = merge.synthesis_commit_id().to_string();
+                if !seen.insert(synthesis_commit_id.clone()) {
+                    continue;
+                }
+
+                let commit = commit_by_id(&self.conn, &workspace_id, &synthesis_commit_id)?
+                    .ok_or(StoreError::UnknownId)?;
+                let author = author_by_commit(&self.conn, &workspace_id, &synthesis_commit_id)?;
+                out.push(ProvenanceStep {
+                    commit,
+                    author,
+                    merge: Some(merge),
+                });
+                queue.push_back(synthesis_commit_id);
+            }
+        }
+
+        Ok(out)
+    }
+}
+
+fn merge_by_synthesis(
+    conn: &Connection,
+    workspace_id: &str,
+    commit_id: &str,
+) -> Result<Option<MergeRecord>, StoreError> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {MERGE_COLUMNS} FROM merge_records WHERE workspace=?1 AND synthesis_commit_id=?2"
+    ))?;
+    let mut rows = stmt.query(params![workspace_id, commit_id])?;
+    match rows.next()? {
+        Some(row) => Ok(Some(merge_record_from_row(row)?)),
+        None => Ok(None),
+    }
+}
+
+fn merge_source_head(
+    conn: &Connection,
+    workspace_id: &str,
+    merge_id: &str,
+) -> Result<Option<String>, StoreError> {
+    Ok(conn
+        .query_row(
+            "SELECT source_head_commit_id FROM merge_sources WHERE workspace=?1 AND merge_id=?2",
+            params![workspace_id, merge_id],
+            |row| row.get::<_, String>(0),
+        )
+        .optional()?)
+}
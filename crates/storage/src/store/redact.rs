@@ -0,0 +1,178 @@
+#![forbid(unsafe_code)]
+
+use super::{
+    CommitRedactRequest, ShowCommitRequest, SqliteStore, StoreError, audit::audit_tx,
+    canonicalize_author, canonicalize_commit, canonicalize_workspace, ensure_commit_exists_tx,
+};
+use rusqlite::{OptionalExtension, Transaction, params};
+use sha2::{Digest, Sha256};
+
+/// Body stored in place of redacted commit content.
+pub const REDACTED_BODY: &str = "[redacted]";
+/// Message stored in place of a redacted commit message.
+pub const REDACTED_MESSAGE: &str = "[redacted]";
+
+/// Who removed a commit message and body, why, and digests of what was removed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommitRedaction {
+    pub actor: String,
+    pub reason: String,
+    /// Hex SHA-256 of the original body, so a holder of the original can prove what was removed.
+    pub body_sha256: String,
+    /// Hex SHA-256 of the original message; `None` for redactions that kept the message.
+    pub message_sha256: Option<String>,
+    pub redacted_at_ms: i64,
+}
+
+impl SqliteStore {
+    /// Replaces the message and body of a commit with `REDACTED_MESSAGE` / `REDACTED_BODY`,
+    /// together with every copy of it: cherry-picked copies in the same workspace and copies
+    /// made by `workspace_merge` in other workspaces, transitively. Ids, parents and
+    /// annotations are kept.
+    ///
+    /// Returns the `(workspace, commit)` pairs redacted by this call; commits that were already
+    /// redacted are skipped. Each redaction is audited in its own workspace. Freed pages are
+    /// zeroed so the original text does not linger in the database file.
+    pub fn commit_redact(
+        &mut self,
+        request: CommitRedactRequest,
+    ) -> Result<Vec<(String, String)>, StoreError> {
+        let workspace_id = canonicalize_workspace(&request.workspace_id)?;
+        let commit_id = canonicalize_commit(&request.commit_id)?;
+        let actor = canonicalize_author(&request.actor)?;
+        let reason = request.reason.trim().to_string();
+        if reason.is_empty() {
+            return Err(StoreError::InvalidInput(
+                "redaction reason must not be empty",
+            ));
+        }
+
+        self.conn.execute_batch("PRAGMA secure_delete = ON;")?;
+        let redacted = self.commit_redact_inner(
+            &workspace_id,
+            &commit_id,
+            &actor,
+            &reason,
+            request.redacted_at_ms,
+        );
+        self.conn.execute_batch("PRAGMA secure_delete = OFF;")?;
+        redacted
+    }
+
+    pub fn commit_redaction(
+        &self,
+        request: ShowCommitRequest,
+    ) -> Result<Option<CommitRedaction>, StoreError> {
+        let workspace_id = canonicalize_workspace(&request.workspace_id)?;
+        let commit_id = canonicalize_commit(&request.commit_id)?;
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT actor, reason, body_sha256, message_sha256, redacted_at_ms \
+                 FROM commit_redactions WHERE workspace=?1 AND commit_id=?2",
+                params![workspace_id, commit_id],
+                |row| {
+                    Ok(CommitRedaction {
+                        actor: row.get(0)?,
+                        reason: row.get(1)?,
+                        body_sha256: row.get(2)?,
+                        message_sha256: row.get(3)?,
+                        redacted_at_ms: row.get(4)?,
+                    })
+                },
+            )
+            .optional()?)
+    }
+
+    fn commit_redact_inner(
+        &self,
+        workspace_id: &str,
+        commit_id: &str,
+        actor: &str,
+        reason: &str,
+        redacted_at_ms: i64,
+    ) -> Result<Vec<(String, String)>, StoreError> {
+        let tx = self.write_tx()?;
+        ensure_commit_exists_tx(&tx, workspace_id, commit_id)?;
+
+        let mut targets = vec![(workspace_id.to_string(), commit_id.to_string())];
+        let mut next = 0;
+        while next < targets.len() {
+            let (workspace, commit) = targets[next].clone();
+            for copy in copies_tx(&tx, &workspace, &commit)? {
+                if !targets.contains(&copy) {
+                    targets.push(copy);
+                }
+            }
+            next += 1;
+        }
+
+        let mut redacted = Vec::new();
+        for (workspace, target) in targets {
+            let already = tx
+                .query_row(
+                    "SELECT 1 FROM commit_redactions WHERE workspace=?1 AND commit_id=?2",
+                    params![workspace, target],
+                    |row| row.get::<_, i64>(0),
+                )
+                .optional()?;
+            if already.is_some() {
+                continue;
+            }
+            let (message, body) = tx.query_row(
+                "SELECT message, body FROM commits WHERE workspace=?1 AND commit_id=?2",
+                params![workspace, target],
+                |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+            )?;
+            tx.execute(
+                "UPDATE commits SET message=?3, body=?4 WHERE workspace=?1 AND commit_id=?2",
+                params![workspace, target, REDACTED_MESSAGE, REDACTED_BODY],
+            )?;
+            tx.execute(
+                "INSERT INTO commit_redactions(workspace, commit_id, actor, reason, body_sha256, message_sha256, redacted_at_ms) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    workspace,
+                    target,
+                    actor,
+                    reason,
+                    sha256_hex(&body),
+                    sha256_hex(&message),
+                    redacted_at_ms
+                ],
+            )?;
+            audit_tx(&tx, &workspace, "commit.redact", &target, redacted_at_ms)?;
+            redacted.push((workspace, target));
+        }
+
+        self.commit_tx(tx)?;
+        Ok(redacted)
+    }
+}
+
+/// Direct copies of a commit: cherry-picks in its workspace and `workspace_merge` copies in
+/// other workspaces.
+fn copies_tx(
+    tx: &Transaction<'_>,
+    workspace_id: &str,
+    source_commit_id: &str,
+) -> Result<Vec<(String, String)>, StoreError> {
+    let mut stmt = tx.prepare(
+        "SELECT workspace, commit_id FROM commit_picks WHERE workspace=?1 AND source_commit_id=?2 \
+         UNION \
+         SELECT workspace, commit_id FROM commit_copies \
+         WHERE source_workspace=?1 AND source_commit_id=?2 \
+         ORDER BY 1, 2",
+    )?;
+    let rows = stmt.query_map(params![workspace_id, source_commit_id], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+    })?;
+    Ok(rows.collect::<Result<Vec<_>, _>>()?)
+}
+
+fn sha256_hex(text: &str) -> String {
+    Sha256::digest(text.as_bytes())
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
@@ -1,5 +1,8 @@
 #![forbid(unsafe_code)]
 
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct CreateBranchRequest {
     pub workspace_id: String,
@@ -27,8 +30,11 @@ pub struct AppendCommitRequest {
     pub branch_id: String,
     pub commit_id: String,
     pub parent_commit_id: Option<String>,
+    /// Compare-and-set guard: the write only applies while the branch head is this commit.
+    pub expected_head_commit_id: Option<String>,
     pub message: String,
     pub body: String,
+    pub author: Option<String>,
     pub created_at_ms: i64,
 }
 
@@ -49,12 +55,227 @@ pub struct CreateMergeRecordRequest {
     pub synthesis_commit_id: String,
     pub synthesis_message: String,
     pub synthesis_body: String,
+    pub author: Option<String>,
     pub created_at_ms: i64,
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CherryPickRequest {
+    pub workspace_id: String,
+    pub source_branch_id: String,
+    pub target_branch_id: String,
+    /// Source commits to copy, appended to the target in this order.
+    pub commit_ids: Vec<String>,
+    /// Attributed to every copy; defaults to each source commit's author.
+    pub author: Option<String>,
+    pub picked_at_ms: i64,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ListMergeRecordsRequest {
     pub workspace_id: String,
     pub limit: usize,
     pub offset: usize,
 }
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommitProvenanceRequest {
+    pub workspace_id: String,
+    pub commit_id: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommitDescendantsRequest {
+    pub workspace_id: String,
+    pub commit_id: String,
+    pub limit: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AutoCreateBranchRequest {
+    pub workspace_id: String,
+    pub scope: String,
+    pub parent_branch_id: Option<String>,
+    pub created_at_ms: i64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CheckoutPushRequest {
+    pub workspace_id: String,
+    pub branch_id: String,
+    pub at_ms: i64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CheckoutPopRequest {
+    pub workspace_id: String,
+    pub at_ms: i64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommitPinRequest {
+    pub workspace_id: String,
+    pub commit_id: String,
+    pub pinned: bool,
+    pub pinned_at_ms: i64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ListPinnedCommitsRequest {
+    pub workspace_id: String,
+    pub branch_id: String,
+    /// Capped at `StoreConfig::max_page_limit`.
+    pub limit: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WorkspaceMergeRequest {
+    pub source_workspace_id: String,
+    pub target_workspace_id: String,
+    pub prefix: String,
+    pub merged_at_ms: i64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WorkspaceDiffRequest {
+    pub workspace_id: String,
+    /// Inclusive.
+    pub since_ms: i64,
+    /// Exclusive.
+    pub until_ms: i64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ActivityBucket {
+    Day,
+    /// ISO weeks, starting Monday 00:00 UTC.
+    Week,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ActivityGroupBy {
+    Branch,
+    Author,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ActivityAggregateRequest {
+    pub workspace_id: String,
+    pub bucket: ActivityBucket,
+    pub group_by: ActivityGroupBy,
+    /// Only commits created at or after this instant are counted.
+    pub since_ms: Option<i64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArchiveBranchRequest {
+    pub workspace_id: String,
+    pub branch_id: String,
+    pub at_ms: i64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CreateScratchBranchRequest {
+    pub workspace_id: String,
+    pub branch_id: String,
+    pub parent_branch_id: Option<String>,
+    pub ttl_ms: i64,
+    pub created_at_ms: i64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PruneScratchBranchesRequest {
+    pub workspace_id: String,
+    pub now_ms: i64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SaveTemplateRequest {
+    pub workspace_id: String,
+    pub name: String,
+    pub body: String,
+    pub saved_at_ms: i64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AppendTemplatedCommitRequest {
+    /// `body` is ignored; it is replaced by the expanded template.
+    pub commit: AppendCommitRequest,
+    pub template: String,
+    pub vars: BTreeMap<String, String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WorkspaceDeleteRequest {
+    pub workspace_id: String,
+    /// Must equal `SqliteStore::workspace_delete_token` for the workspace.
+    pub confirm_token: String,
+    /// When set, a verified snapshot of the store is written here before anything is deleted.
+    pub export_to: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AckKind {
+    Seen,
+    Agree,
+    /// Replaces an earlier `Agree` by the same actor, and vice versa.
+    Disagree,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommitAckRequest {
+    pub workspace_id: String,
+    pub commit_id: String,
+    pub actor: String,
+    pub kind: AckKind,
+    pub acked_at_ms: i64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommitRedactRequest {
+    pub workspace_id: String,
+    pub commit_id: String,
+    pub actor: String,
+    pub reason: String,
+    pub redacted_at_ms: i64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WorkspaceLockRequest {
+    pub workspace_id: String,
+    pub holder: String,
+    /// Free text shown to whoever is blocked by the lock.
+    pub purpose: String,
+    pub ttl_ms: i64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IntegrityRepairRequest {
+    pub workspace_id: String,
+    pub repaired_at_ms: i64,
+}
+
+/// Tables `SqliteStore::export_table_csv` can export.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportTable {
+    Branches,
+    /// Commit metadata; bodies are exported only as their byte size, in `body_bytes`.
+    Commits,
+    Merges,
+    Audit,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExportTableRequest {
+    pub workspace_id: String,
+    pub table: ExportTable,
+}
+
+/// Read paths whose statements `SqliteStore::explain_query_plan` can explain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExplainTarget {
+    ListBranches,
+    ListMergeRecords,
+    ShowCommit,
+    ListPinnedCommits,
+}
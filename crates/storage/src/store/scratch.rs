@@ -0,0 +1,175 @@
+#![forbid(unsafe_code)]
+
+use super::{
+    CreateScratchBranchRequest, PruneScratchBranchesRequest, SqliteStore, StoreError,
+    audit::audit_tx, canonicalize_branch, canonicalize_workspace, delete_branch_tx,
+    ensure_unlocked, ensure_workspace_tx, insert_branch_tx,
+};
+use bm_core::ThoughtBranch;
+use rusqlite::{Transaction, params};
+
+/// A branch created as scratch, with the time after which it may be pruned.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScratchBranch {
+    pub branch_id: String,
+    pub expires_at_ms: i64,
+}
+
+/// Outcome of one `prune_scratch_branches` pass.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ScratchPruneReport {
+    pub deleted: Vec<String>,
+    /// Expired scratch branches left in place, with the reason they were kept.
+    pub kept: Vec<(String, &'static str)>,
+}
+
+impl SqliteStore {
+    /// Creates a branch marked as scratch. Once `ttl_ms` has elapsed the branch becomes
+    /// eligible for `prune_scratch_branches`.
+    pub fn create_scratch_branch(
+        &mut self,
+        request: CreateScratchBranchRequest,
+    ) -> Result<ThoughtBranch, StoreError> {
+        let workspace_id = canonicalize_workspace(&request.workspace_id)?;
+        let branch_id = canonicalize_branch(&request.branch_id)?;
+        let parent_branch_id = request
+            .parent_branch_id
+            .as_deref()
+            .map(canonicalize_branch)
+            .transpose()?;
+        if request.ttl_ms <= 0 {
+            return Err(StoreError::InvalidInput("ttl_ms must be positive"));
+        }
+        let expires_at_ms = request
+            .created_at_ms
+            .checked_add(request.ttl_ms)
+            .ok_or(StoreError::InvalidInput("ttl_ms is too large"))?;
+        if parent_branch_id.as_deref() == Some(branch_id.as_str()) {
+            return Err(StoreError::BranchCycle);
+        }
+
+        let tx = self.write_tx()?;
+        ensure_workspace_tx(&tx, &workspace_id, request.created_at_ms)?;
+        let branch = insert_branch_tx(
+            &tx,
+            &workspace_id,
+            &branch_id,
+            parent_branch_id.as_deref(),
+            request.created_at_ms,
+            self.config.max_branch_depth,
+        )?;
+        tx.execute(
+            "INSERT INTO branch_scratch(workspace, branch, expires_at_ms) VALUES (?1, ?2, ?3)",
+            params![workspace_id, branch_id, expires_at_ms],
+        )?;
+        audit_tx(
+            &tx,
+            &workspace_id,
+            "branch.create",
+            &branch_id,
+            request.created_at_ms,
+        )?;
+
+        self.commit_tx(tx)?;
+        Ok(branch)
+    }
+
+    /// Lists scratch branches of a workspace ordered by expiry.
+    pub fn list_scratch_branches(
+        &self,
+        workspace_id: &str,
+    ) -> Result<Vec<ScratchBranch>, StoreError> {
+        let workspace_id = canonicalize_workspace(workspace_id)?;
+        let mut stmt = self.conn.prepare(
+            "SELECT branch, expires_at_ms FROM branch_scratch \
+             WHERE workspace=?1 ORDER BY expires_at_ms ASC, branch ASC",
+        )?;
+        let rows = stmt.query_map(params![workspace_id], |row| {
+            Ok(ScratchBranch {
+                branch_id: row.get(0)?,
+                expires_at_ms: row.get(1)?,
+            })
+        })?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// Deletes scratch branches whose expiry is at or before `now_ms`.
+    ///
+    /// An expired branch is kept when something else still depends on it: child branches,
+    /// the current checkout, a merge that used it as a source, or a pinned commit.
+    pub fn prune_scratch_branches(
+        &mut self,
+        request: PruneScratchBranchesRequest,
+    ) -> Result<ScratchPruneReport, StoreError> {
+        let workspace_id = canonicalize_workspace(&request.workspace_id)?;
+
+        let tx = self.write_tx()?;
+        ensure_unlocked(&tx, &workspace_id, self.own_lock_holder(&workspace_id))?;
+        let expired = {
+            let mut stmt = tx.prepare(
+                "SELECT branch FROM branch_scratch \
+                 WHERE workspace=?1 AND expires_at_ms<=?2 \
+                 ORDER BY expires_at_ms ASC, branch ASC",
+            )?;
+            let rows = stmt.query_map(params![workspace_id, request.now_ms], |row| {
+                row.get::<_, String>(0)
+            })?;
+            rows.collect::<Result<Vec<_>, _>>()?
+        };
+
+        let mut report = ScratchPruneReport::default();
+        for branch_id in expired {
+            if let Some(reason) = keep_reason_tx(&tx, &workspace_id, &branch_id)? {
+                report.kept.push((branch_id, reason));
+                continue;
+            }
+            delete_branch_tx(&tx, &workspace_id, &branch_id)?;
+            audit_tx(
+                &tx,
+                &workspace_id,
+                "branch.prune",
+                &branch_id,
+                request.now_ms,
+            )?;
+            report.deleted.push(branch_id);
+        }
+
+        self.commit_tx(tx)?;
+        Ok(report)
+    }
+}
+
+fn keep_reason_tx(
+    tx: &Transaction<'_>,
+    workspace_id: &str,
+    branch_id: &str,
+) -> Result<Option<&'static str>, StoreError> {
+    let checks: [(&str, &'static str); 4] = [
+        (
+            "SELECT EXISTS(SELECT 1 FROM branches WHERE workspace=?1 AND parent_branch_id=?2)",
+            "has child branches",
+        ),
+        (
+            "SELECT EXISTS(SELECT 1 FROM branch_checkout WHERE workspace=?1 AND branch=?2)",
+            "checked out",
+        ),
+        (
+            "SELECT EXISTS(SELECT 1 FROM merge_records WHERE workspace=?1 AND source_branch=?2)",
+            "merged into another branch",
+        ),
+        (
+            "SELECT EXISTS(SELECT 1 FROM commits c JOIN commit_pins p \
+               ON p.workspace=c.workspace AND p.commit_id=c.commit_id \
+             WHERE c.workspace=?1 AND c.branch=?2)",
+            "has pinned commits",
+        ),
+    ];
+    for (sql, reason) in checks {
+        if tx.query_row(sql, params![workspace_id, branch_id], |row| {
+            row.get::<_, bool>(0)
+        })? {
+            return Ok(Some(reason));
+        }
+    }
+    Ok(None)
+}
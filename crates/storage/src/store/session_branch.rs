@@ -0,0 +1,112 @@
+#![forbid(unsafe_code)]
+
+use super::{
+    AutoCreateBranchRequest, SqliteStore, StoreError, audit::audit_tx, canonicalize_branch,
+    canonicalize_workspace, ensure_workspace_tx, insert_branch_tx, set_checkout_tx,
+};
+use bm_core::ThoughtBranch;
+use rusqlite::{OptionalExtension, Transaction, params};
+
+/// A session branch created and checked out in one write.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AutoBranch {
+    pub branch: ThoughtBranch,
+    pub previous_checkout: Option<String>,
+}
+
+impl SqliteStore {
+    /// Creates the next free `<scope>/s<N>` branch and checks it out.
+    ///
+    /// The parent is the explicit `parent_branch_id`, else the current checkout, else none.
+    /// `N` is one past the highest existing session number for the scope, so names are
+    /// deterministic for a given store state and never collide.
+    pub fn branch_auto_create(
+        &mut self,
+        request: AutoCreateBranchRequest,
+    ) -> Result<AutoBranch, StoreError> {
+        let workspace_id = canonicalize_workspace(&request.workspace_id)?;
+        let scope = canonicalize_branch(&request.scope)?;
+        let explicit_parent = request
+            .parent_branch_id
+            .as_deref()
+            .map(canonicalize_branch)
+            .transpose()?;
+
+        let tx = self.write_tx()?;
+        ensure_workspace_tx(&tx, &workspace_id, request.created_at_ms)?;
+
+        let parent_branch_id = match explicit_parent {
+            Some(parent) => Some(parent),
+            None => tx
+                .query_row(
+                    "SELECT branch FROM branch_checkout WHERE workspace=?1",
+                    params![workspace_id],
+                    |row| row.get::<_, String>(0),
+                )
+                .optional()?,
+        };
+
+        let session = next_session_number_tx(&tx, &workspace_id, &scope)?;
+        let branch_id = canonicalize_branch(&format!("{scope}/s{session}"))?;
+        let branch = insert_branch_tx(
+            &tx,
+            &workspace_id,
+            &branch_id,
+            parent_branch_id.as_deref(),
+            request.created_at_ms,
+            self.config.max_branch_depth,
+        )?;
+        let previous_checkout =
+            set_checkout_tx(&tx, &workspace_id, &branch_id, request.created_at_ms)?;
+        audit_tx(
+            &tx,
+            &workspace_id,
+            "branch.create",
+            &branch_id,
+            request.created_at_ms,
+        )?;
+        audit_tx(
+            &tx,
+            &workspace_id,
+            "branch.checkout",
+            &branch_id,
+            request.created_at_ms,
+        )?;
+
+        self.commit_tx(tx)?;
+        Ok(AutoBranch {
+            branch,
+            previous_checkout,
+        })
+    }
+}
+
+fn next_session_number_tx(
+    tx: &Transaction<'_>,
+    workspace_id: &str,
+    scope: &str,
+) -> Result<u64, StoreError> {
+    let prefix = format!("{scope}/s");
+    let prefix_len =
+        i64::try_from(prefix.len()).map_err(|_| StoreError::InvalidInput("numeric overflow"))?;
+
+    let mut stmt =
+        tx.prepare("SELECT name FROM branches WHERE workspace=?1 AND substr(name, 1, ?3)=?2")?;
+    let mut rows = stmt.query(params![workspace_id, prefix, prefix_len])?;
+
+    let mut highest = 0u64;
+    while let Some(row) = rows.next()? {
+        let name = row.get::<_, String>(0)?;
+        let suffix = &name[prefix.len()..];
+        if suffix.is_empty() || !suffix.chars().all(|ch| ch.is_ascii_digit()) {
+            continue;
+        }
+        if let Ok(value) = suffix.parse::<u64>() {
+            highest = highest.max(value);
+        }
+    }
+
+    highest
+        .checked_add(1)
+        .ok_or(StoreError::InvalidInput("numeric overflow"))
+}
@@ -0,0 +1,159 @@
+#![forbid(unsafe_code)]
+
+use super::{
+    AppendTemplatedCommitRequest, SaveTemplateRequest, ShowCommitRequest, SqliteStore, StoreError,
+    append_commit_tx, canonical_identifier, canonicalize_commit, canonicalize_workspace,
+};
+use bm_core::ThoughtCommit;
+use rusqlite::{OptionalExtension, params};
+use std::collections::{BTreeMap, BTreeSet};
+
+impl SqliteStore {
+    /// Stores (or replaces) a commit body template and returns its placeholder names.
+    ///
+    /// Placeholders are written `{{name}}` with `name` in lowercase letters, digits and `_`.
+    pub fn template_save(
+        &mut self,
+        request: SaveTemplateRequest,
+    ) -> Result<Vec<String>, StoreError> {
+        let workspace_id = canonicalize_workspace(&request.workspace_id)?;
+        let name = canonicalize_template(&request.name)?;
+        if request.body.trim().is_empty() {
+            return Err(StoreError::InvalidInput("template body must not be empty"));
+        }
+        let placeholders = template_placeholders(&request.body)?;
+
+        let tx = self.write_tx()?;
+        tx.execute(
+            "INSERT INTO commit_templates(workspace, name, body, updated_at_ms) VALUES (?1, ?2, ?3, ?4) \
+             ON CONFLICT(workspace, name) DO UPDATE SET body=excluded.body, updated_at_ms=excluded.updated_at_ms",
+            params![workspace_id, name, request.body, request.saved_at_ms],
+        )?;
+        self.commit_tx(tx)?;
+        Ok(placeholders.into_iter().collect())
+    }
+
+    pub fn template_body(
+        &self,
+        workspace_id: &str,
+        name: &str,
+    ) -> Result<Option<String>, StoreError> {
+        let workspace_id = canonicalize_workspace(workspace_id)?;
+        let name = canonicalize_template(name)?;
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT body FROM commit_templates WHERE workspace=?1 AND name=?2",
+                params![workspace_id, name],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()?)
+    }
+
+    /// Appends a commit whose body is the named template expanded with `vars`, and records
+    /// the template on the commit. Every placeholder needs a value and every value must be used.
+    pub fn append_templated_commit(
+        &mut self,
+        request: AppendTemplatedCommitRequest,
+    ) -> Result<ThoughtCommit, StoreError> {
+        let workspace_id = canonicalize_workspace(&request.commit.workspace_id)?;
+        let name = canonicalize_template(&request.template)?;
+
+        let tx = self.write_tx()?;
+        let body = tx
+            .query_row(
+                "SELECT body FROM commit_templates WHERE workspace=?1 AND name=?2",
+                params![workspace_id, name],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()?
+            .ok_or(StoreError::InvalidInput("unknown template"))?;
+        let mut commit_request = request.commit;
+        commit_request.body = expand_template(&body, &request.vars)?;
+
+        let commit = append_commit_tx(&tx, commit_request)?;
+        tx.execute(
+            "INSERT INTO commit_template_uses(workspace, commit_id, template) VALUES (?1, ?2, ?3)",
+            params![commit.workspace_id(), commit.commit_id(), name],
+        )?;
+
+        self.commit_tx(tx)?;
+        Ok(commit)
+    }
+
+    /// Returns the template a commit was expanded from, if any.
+    pub fn commit_template(
+        &self,
+        request: ShowCommitRequest,
+    ) -> Result<Option<String>, StoreError> {
+        let workspace_id = canonicalize_workspace(&request.workspace_id)?;
+        let commit_id = canonicalize_commit(&request.commit_id)?;
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT template FROM commit_template_uses WHERE workspace=?1 AND commit_id=?2",
+                params![workspace_id, commit_id],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()?)
+    }
+}
+
+fn canonicalize_template(value: &str) -> Result<String, StoreError> {
+    canonical_identifier("template", value.to_string())
+        .map_err(|_| StoreError::InvalidInput("invalid template name"))
+}
+
+/// Splits a template into literal text and placeholder names, in order.
+fn template_parts(body: &str) -> Result<Vec<(&str, Option<&str>)>, StoreError> {
+    let mut parts = Vec::new();
+    let mut rest = body;
+    while let Some(open) = rest.find("{{") {
+        let after = &rest[open + 2..];
+        let close = after.find("}}").ok_or(StoreError::InvalidInput(
+            "unterminated template placeholder",
+        ))?;
+        let name = after[..close].trim();
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|ch| ch.is_ascii_lowercase() || ch.is_ascii_digit() || ch == '_')
+        {
+            return Err(StoreError::InvalidInput(
+                "template placeholders must be {{lowercase_name}}",
+            ));
+        }
+        parts.push((&rest[..open], Some(name)));
+        rest = &after[close + 2..];
+    }
+    parts.push((rest, None));
+    Ok(parts)
+}
+
+fn template_placeholders(body: &str) -> Result<BTreeSet<String>, StoreError> {
+    Ok(template_parts(body)?
+        .into_iter()
+        .filter_map(|(_, name)| name.map(ToOwned::to_owned))
+        .collect())
+}
+
+fn expand_template(body: &str, vars: &BTreeMap<String, String>) -> Result<String, StoreError> {
+    let placeholders = template_placeholders(body)?;
+    if vars.keys().any(|key| !placeholders.contains(key)) {
+        return Err(StoreError::InvalidInput(
+            "template variable is not used by the template",
+        ));
+    }
+
+    let mut out = String::with_capacity(body.len());
+    for (text, name) in template_parts(body)? {
+        out.push_str(text);
+        if let Some(name) = name {
+            let value = vars
+                .get(name)
+                .ok_or(StoreError::InvalidInput("template variable is missing"))?;
+            out.push_str(value);
+        }
+    }
+    Ok(out)
+}
@@ -0,0 +1,114 @@
+#![forbid(unsafe_code)]
+
+use super::{
+    BackupManifest, SqliteStore, StoreError, V3_ADDITIVE_TABLES, V3_TABLES, WorkspaceDeleteRequest,
+    canonicalize_workspace, ensure_unlocked,
+};
+use rusqlite::{OptionalExtension, params};
+use sha2::{Digest, Sha256};
+
+// Provenance rows in other workspaces that point into the deleted one; left behind, they would
+// let a recreated workspace with reused commit ids cascade redactions into unrelated copies.
+const DELETE_COPY_SOURCES_SQL: &str = "DELETE FROM commit_copies WHERE source_workspace=?1";
+
+/// Outcome of deleting a workspace.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WorkspaceDeleteReport {
+    pub rows_deleted: usize,
+    pub export: Option<BackupManifest>,
+}
+
+impl SqliteStore {
+    /// Returns the confirmation token `workspace_delete` expects for a workspace.
+    ///
+    /// The token is derived from the workspace id alone, so a caller has to look it up for the
+    /// exact workspace it means to delete instead of passing a constant.
+    pub fn workspace_delete_token(&self, workspace_id: &str) -> Result<String, StoreError> {
+        let workspace_id = canonicalize_workspace(workspace_id)?;
+        let digest = Sha256::digest(format!("branchmind.workspace_delete\n{workspace_id}"));
+        let hex = digest
+            .iter()
+            .take(6)
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<String>();
+        Ok(format!("delete-{hex}"))
+    }
+
+    /// Removes every row of a workspace from every table, including its audit chain, and the
+    /// `commit_copies` rows in other workspaces that name it as their source.
+    ///
+    /// Refused unless `confirm_token` matches `workspace_delete_token`. With `export_to`, a
+    /// verified snapshot of the whole store is written first and the delete is skipped if the
+    /// snapshot fails.
+    pub fn workspace_delete(
+        &mut self,
+        request: WorkspaceDeleteRequest,
+    ) -> Result<WorkspaceDeleteReport, StoreError> {
+        let workspace_id = canonicalize_workspace(&request.workspace_id)?;
+        if request.confirm_token != self.workspace_delete_token(&workspace_id)? {
+            return Err(StoreError::InvalidInput(
+                "confirm_token does not match the workspace",
+            ));
+        }
+        let known = self
+            .conn
+            .query_row(
+                "SELECT 1 FROM workspaces WHERE workspace=?1",
+                params![workspace_id],
+                |row| row.get::<_, i64>(0),
+            )
+            .optional()?;
+        if known.is_none() {
+            return Err(StoreError::UnknownId);
+        }
+
+        // Checked again inside the deleting transaction; this early check only avoids writing
+        // an export for a workspace that is locked anyway.
+        ensure_unlocked(
+            &self.conn,
+            &workspace_id,
+            self.own_lock_holder(&workspace_id),
+        )?;
+
+        let export = request
+            .export_to
+            .map(|path| self.backup_to(path))
+            .transpose()?;
+
+        // Parent links use ON DELETE RESTRICT, which fires even for deferred checks, so rows are
+        // removed with enforcement off. Foreign keys are scoped by workspace; the only
+        // cross-workspace reference, `commit_copies.source_workspace`, is cleared with them.
+        self.conn.execute_batch("PRAGMA foreign_keys = OFF;")?;
+        let deleted = self.delete_workspace_rows(&workspace_id);
+        self.conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        let rows_deleted = deleted?;
+        self.lock_holders.remove(&workspace_id);
+
+        Ok(WorkspaceDeleteReport {
+            rows_deleted,
+            export,
+        })
+    }
+
+    fn delete_workspace_rows(&self, workspace_id: &str) -> Result<usize, StoreError> {
+        let tx = self.write_tx()?;
+        ensure_unlocked(&tx, workspace_id, self.own_lock_holder(workspace_id))?;
+        let mut rows_deleted = 0usize;
+        for table in V3_TABLES
+            .iter()
+            .chain(V3_ADDITIVE_TABLES.iter())
+            .filter(|table| **table != "workspace_state")
+        {
+            rows_deleted += tx.execute(
+                &format!("DELETE FROM {table} WHERE workspace=?1"),
+                params![workspace_id],
+            )?;
+        }
+        {
+            let mut stmt = tx.prepare_cached(DELETE_COPY_SOURCES_SQL)?;
+            rows_deleted += stmt.execute(params![workspace_id])?;
+        }
+        self.commit_tx(tx)?;
+        Ok(rows_deleted)
+    }
+}
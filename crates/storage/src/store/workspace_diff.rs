@@ -0,0 +1,175 @@
+#![forbid(unsafe_code)]
+
+use super::{
+    MERGE_COLUMNS, SqliteStore, StoreError, WorkspaceDiffRequest, canonicalize_workspace,
+    merge_record_from_row,
+};
+use bm_core::MergeRecord;
+use rusqlite::params;
+use std::fmt::Write as _;
+
+/// Commits appended to one branch inside the window.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BranchActivity {
+    pub branch_id: String,
+    pub commits: u64,
+    /// Newest commit of the branch inside the window.
+    pub latest_commit_id: String,
+}
+
+/// Everything that changed in a workspace between `since_ms` (inclusive) and `until_ms`
+/// (exclusive).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WorkspaceDiff {
+    pub workspace_id: String,
+    pub since_ms: i64,
+    pub until_ms: i64,
+    pub branches_created: Vec<String>,
+    pub branches_archived: Vec<String>,
+    /// Ordered by branch id.
+    pub commits: Vec<BranchActivity>,
+    pub merges: Vec<MergeRecord>,
+    pub pinned_commits: Vec<String>,
+}
+
+impl WorkspaceDiff {
+    pub fn is_empty(&self) -> bool {
+        self.branches_created.is_empty()
+            && self.branches_archived.is_empty()
+            && self.commits.is_empty()
+            && self.merges.is_empty()
+            && self.pinned_commits.is_empty()
+    }
+
+    /// Renders the report as markdown, e.g. for a standup or weekly summary. Empty sections
+    /// are left out.
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!(
+            "## {} — changes in [{}, {})\n",
+            self.workspace_id, self.since_ms, self.until_ms
+        );
+        if self.is_empty() {
+            out.push_str("\nNo changes.\n");
+            return out;
+        }
+        push_list(&mut out, "Branches created", &self.branches_created);
+        if !self.commits.is_empty() {
+            out.push_str("\n### Commits\n\n");
+            for activity in &self.commits {
+                let _ = writeln!(
+                    out,
+                    "- `{}`: {} (latest `{}`)",
+                    activity.branch_id, activity.commits, activity.latest_commit_id
+                );
+            }
+        }
+        if !self.merges.is_empty() {
+            out.push_str("\n### Merges\n\n");
+            for merge in &self.merges {
+                let _ = writeln!(
+                    out,
+                    "- `{}` → `{}` ({}): {}",
+                    merge.source_branch_id(),
+                    merge.target_branch_id(),
+                    merge.strategy(),
+                    merge.summary()
+                );
+            }
+        }
+        push_list(&mut out, "Pinned", &self.pinned_commits);
+        push_list(&mut out, "Archived", &self.branches_archived);
+        out
+    }
+}
+
+impl SqliteStore {
+    /// Summarizes branch, commit, merge, pin and archive activity inside a time window.
+    pub fn workspace_diff(
+        &self,
+        request: WorkspaceDiffRequest,
+    ) -> Result<WorkspaceDiff, StoreError> {
+        let workspace_id = canonicalize_workspace(&request.workspace_id)?;
+        if request.since_ms > request.until_ms {
+            return Err(StoreError::InvalidInput(
+                "since_ms must not be after until_ms",
+            ));
+        }
+        let window = params![workspace_id, request.since_ms, request.until_ms];
+
+        let branches_created = self.window_ids(
+            "SELECT name FROM branches \
+             WHERE workspace=?1 AND created_at_ms>=?2 AND created_at_ms<?3 \
+             ORDER BY created_at_ms ASC, name ASC",
+            window,
+        )?;
+        let branches_archived = self.window_ids(
+            "SELECT branch FROM branch_archive \
+             WHERE workspace=?1 AND archived_at_ms>=?2 AND archived_at_ms<?3 \
+             ORDER BY archived_at_ms ASC, branch ASC",
+            window,
+        )?;
+        let pinned_commits = self.window_ids(
+            "SELECT commit_id FROM commit_pins \
+             WHERE workspace=?1 AND pinned_at_ms>=?2 AND pinned_at_ms<?3 \
+             ORDER BY pinned_at_ms ASC, commit_id ASC",
+            window,
+        )?;
+
+        // SQLite returns the bare column from the row that produced MAX(), which is the newest commit.
+        let mut stmt = self.conn.prepare(
+            "SELECT branch, COUNT(1), commit_id, MAX(created_at_ms) FROM commits \
+             WHERE workspace=?1 AND created_at_ms>=?2 AND created_at_ms<?3 \
+             GROUP BY branch ORDER BY branch ASC",
+        )?;
+        let rows = stmt.query_map(window, |row| {
+            Ok(BranchActivity {
+                branch_id: row.get(0)?,
+                commits: u64::try_from(row.get::<_, i64>(1)?).unwrap_or(0),
+                latest_commit_id: row.get(2)?,
+            })
+        })?;
+        let commits = rows.collect::<Result<Vec<_>, _>>()?;
+
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {MERGE_COLUMNS} FROM merge_records \
+             WHERE workspace=?1 AND created_at_ms>=?2 AND created_at_ms<?3 \
+             ORDER BY created_at_ms ASC, merge_id ASC"
+        ))?;
+        let mut rows = stmt.query(window)?;
+        let mut merges = Vec::new();
+        while let Some(row) = rows.next()? {
+            merges.push(merge_record_from_row(row)?);
+        }
+
+        Ok(WorkspaceDiff {
+            workspace_id,
+            since_ms: request.since_ms,
+            until_ms: request.until_ms,
+            branches_created,
+            branches_archived,
+            commits,
+            merges,
+            pinned_commits,
+        })
+    }
+
+    fn window_ids(
+        &self,
+        sql: &str,
+        window: &[&dyn rusqlite::ToSql],
+    ) -> Result<Vec<String>, StoreError> {
+        let mut stmt = self.conn.prepare(sql)?;
+        let rows = stmt.query_map(window, |row| row.get::<_, String>(0))?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+}
+
+fn push_list(out: &mut String, title: &str, ids: &[String]) {
+    if ids.is_empty() {
+        return;
+    }
+    let _ = writeln!(out, "\n### {title}\n");
+    for id in ids {
+        let _ = writeln!(out, "- `{id}`");
+    }
+}
@@ -0,0 +1,335 @@
+#![forbid(unsafe_code)]
+
+use super::{
+    SqliteStore, StoreError, WorkspaceMergeRequest,
+    audit::{audit_commit_tx, audit_tx},
+    branch_exists_tx, canonicalize_branch, canonicalize_commit, canonicalize_merge,
+    canonicalize_workspace, ensure_unlocked, ensure_workspace_tx, map_insert_conflict,
+};
+use bm_core::{ThoughtCommit, canonical_identifier};
+use rusqlite::{OptionalExtension, Transaction, params};
+
+/// Outcome of copying one workspace into another.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WorkspaceMergeReport {
+    /// `(source branch, target branch)` pairs, in source creation order.
+    pub branches: Vec<(String, String)>,
+    pub commits: usize,
+    pub merges: usize,
+}
+
+#[derive(Debug)]
+struct BranchRow {
+    name: String,
+    parent: Option<String>,
+    head: Option<String>,
+    created_at_ms: i64,
+    updated_at_ms: i64,
+}
+
+#[derive(Debug)]
+struct CommitRow {
+    commit_id: String,
+    branch: String,
+    parent: Option<String>,
+    message: String,
+    body: String,
+    created_at_ms: i64,
+}
+
+impl SqliteStore {
+    /// Copies every branch, commit and merge record of the source workspace into the
+    /// target workspace in one transaction.
+    ///
+    /// Branches are renamed to `<prefix>/<branch>`; commit and merge ids become
+    /// `<prefix>-<id>`. Any collision in the target, including a same-named template with a
+    /// different body, aborts the whole copy. The source workspace is left untouched, and
+    /// the target checkout is not changed.
+    pub fn workspace_merge(
+        &mut self,
+        request: WorkspaceMergeRequest,
+    ) -> Result<WorkspaceMergeReport, StoreError> {
+        let source = canonicalize_workspace(&request.source_workspace_id)?;
+        let target = canonicalize_workspace(&request.target_workspace_id)?;
+        if source == target {
+            return Err(StoreError::InvalidInput(
+                "source and target workspace must differ",
+            ));
+        }
+        let prefix = canonical_identifier("prefix", request.prefix)
+            .map_err(|_| StoreError::InvalidInput("invalid prefix"))?;
+        let branch_key = |id: &str| canonicalize_branch(&format!("{prefix}/{id}"));
+        let commit_key = |id: &str| canonicalize_commit(&format!("{prefix}-{id}"));
+
+        let tx = self.write_tx()?;
+        let source_known = tx
+            .query_row(
+                "SELECT 1 FROM workspaces WHERE workspace=?1",
+                params![source],
+                |row| row.get::<_, i64>(0),
+            )
+            .optional()?;
+        if source_known.is_none() {
+            return Err(StoreError::UnknownId);
+        }
+        ensure_workspace_tx(&tx, &target, request.merged_at_ms)?;
+        ensure_unlocked(&tx, &target, self.own_lock_holder(&target))?;
+
+        let branches = source_branches_tx(&tx, &source)?;
+        let mut branch_map = Vec::with_capacity(branches.len());
+        for branch in &branches {
+            let renamed = branch_key(&branch.name)?;
+            if branch_exists_tx(&tx, &target, &renamed)? {
+                return Err(StoreError::BranchAlreadyExists);
+            }
+            tx.execute(
+                "INSERT INTO branches(workspace, name, parent_branch_id, head_commit_id, created_at_ms, updated_at_ms) \
+                 VALUES (?1, ?2, NULL, NULL, ?3, ?4)",
+                params![target, renamed, branch.created_at_ms, branch.updated_at_ms],
+            )?;
+            branch_map.push((branch.name.clone(), renamed));
+        }
+
+        // Parent links are restored after all rows exist so insert order cannot trip the foreign keys.
+        let commits = source_commits_tx(&tx, &source)?;
+        for commit in &commits {
+            tx.execute(
+                "INSERT INTO commits(workspace, branch, commit_id, parent_commit_id, message, body, created_at_ms) \
+                 VALUES (?1, ?2, ?3, NULL, ?4, ?5, ?6)",
+                params![
+                    target,
+                    branch_key(&commit.branch)?,
+                    commit_key(&commit.commit_id)?,
+                    commit.message,
+                    commit.body,
+                    commit.created_at_ms
+                ],
+            )
+            .map_err(map_insert_conflict)?;
+            tx.execute(
+                "INSERT INTO commit_copies(workspace, commit_id, source_workspace, source_commit_id, copied_at_ms) \
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    target,
+                    commit_key(&commit.commit_id)?,
+                    source,
+                    commit.commit_id,
+                    request.merged_at_ms
+                ],
+            )?;
+        }
+        // Each copy gets its own content digest in the target chain, as appended commits do.
+        for commit in &commits {
+            let commit_id = commit_key(&commit.commit_id)?;
+            let parent = commit.parent.as_deref().map(commit_key).transpose()?;
+            if let Some(parent) = parent.as_deref() {
+                tx.execute(
+                    "UPDATE commits SET parent_commit_id=?3 WHERE workspace=?1 AND commit_id=?2",
+                    params![target, commit_id, parent],
+                )?;
+            }
+            let copy = ThoughtCommit::try_new(
+                target.clone(),
+                branch_key(&commit.branch)?,
+                commit_id.clone(),
+                parent,
+                commit.message.clone(),
+                commit.body.clone(),
+                commit.created_at_ms,
+            )
+            .map_err(|_| StoreError::InvalidInput("invalid commit row"))?;
+            audit_commit_tx(
+                &tx,
+                &target,
+                "commit.copy",
+                &commit_id,
+                &copy,
+                request.merged_at_ms,
+            )?;
+        }
+        for branch in &branches {
+            let parent = branch.parent.as_deref().map(branch_key).transpose()?;
+            let head = branch.head.as_deref().map(commit_key).transpose()?;
+            tx.execute(
+                "UPDATE branches SET parent_branch_id=?3, head_commit_id=?4 WHERE workspace=?1 AND name=?2",
+                params![target, branch_key(&branch.name)?, parent, head],
+            )?;
+        }
+
+        let merges = copy_merge_records_tx(&tx, &source, &target, &prefix)?;
+        copy_annotations_tx(&tx, &source, &target, &prefix)?;
+        audit_tx(
+            &tx,
+            &target,
+            "workspace.merge",
+            &format!("{source} as {prefix}"),
+            request.merged_at_ms,
+        )?;
+
+        self.commit_tx(tx)?;
+        Ok(WorkspaceMergeReport {
+            branches: branch_map,
+            commits: commits.len(),
+            merges,
+        })
+    }
+}
+
+fn source_branches_tx(
+    tx: &Transaction<'_>,
+    workspace_id: &str,
+) -> Result<Vec<BranchRow>, StoreError> {
+    let mut stmt = tx.prepare(
+        "SELECT name, parent_branch_id, head_commit_id, created_at_ms, updated_at_ms \
+         FROM branches WHERE workspace=?1 ORDER BY created_at_ms ASC, name ASC",
+    )?;
+    let mut rows = stmt.query(params![workspace_id])?;
+    let mut out = Vec::new();
+    while let Some(row) = rows.next()? {
+        out.push(BranchRow {
+            name: row.get(0)?,
+            parent: row.get(1)?,
+            head: row.get(2)?,
+            created_at_ms: row.get(3)?,
+            updated_at_ms: row.get(4)?,
+        });
+    }
+    Ok(out)
+}
+
+fn source_commits_tx(
+    tx: &Transaction<'_>,
+    workspace_id: &str,
+) -> Result<Vec<CommitRow>, StoreError> {
+    let mut stmt = tx.prepare(
+        "SELECT commit_id, branch, parent_commit_id, message, body, created_at_ms \
+         FROM commits WHERE workspace=?1 ORDER BY created_at_ms ASC, commit_id ASC",
+    )?;
+    let mut rows = stmt.query(params![workspace_id])?;
+    let mut out = Vec::new();
+    while let Some(row) = rows.next()? {
+        out.push(CommitRow {
+            commit_id: row.get(0)?,
+            branch: row.get(1)?,
+            parent: row.get(2)?,
+            message: row.get(3)?,
+            body: row.get(4)?,
+            created_at_ms: row.get(5)?,
+        });
+    }
+    Ok(out)
+}
+
+fn copy_merge_records_tx(
+    tx: &Transaction<'_>,
+    source: &str,
+    target: &str,
+    prefix: &str,
+) -> Result<usize, StoreError> {
+    let mut stmt = tx.prepare(
+        "SELECT m.merge_id, m.source_branch, m.target_branch, m.synthesis_commit_id, m.strategy, m.summary, m.created_at_ms, s.source_head_commit_id \
+         FROM merge_records m \
+         LEFT JOIN merge_sources s ON s.workspace=m.workspace AND s.merge_id=m.merge_id \
+         WHERE m.workspace=?1 ORDER BY m.created_at_ms ASC, m.merge_id ASC",
+    )?;
+    let mut rows = stmt.query(params![source])?;
+    let mut copied = 0usize;
+    while let Some(row) = rows.next()? {
+        let merge_id = canonicalize_merge(&format!("{prefix}-{}", row.get::<_, String>(0)?))?;
+        let source_branch = canonicalize_branch(&format!("{prefix}/{}", row.get::<_, String>(1)?))?;
+        let target_branch = canonicalize_branch(&format!("{prefix}/{}", row.get::<_, String>(2)?))?;
+        let synthesis = canonicalize_commit(&format!("{prefix}-{}", row.get::<_, String>(3)?))?;
+        tx.execute(
+            "INSERT INTO merge_records(workspace, merge_id, source_branch, target_branch, synthesis_commit_id, strategy, summary, created_at_ms) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                target,
+                merge_id,
+                source_branch,
+                target_branch,
+                synthesis,
+                row.get::<_, String>(4)?,
+                row.get::<_, String>(5)?,
+                row.get::<_, i64>(6)?
+            ],
+        )
+        .map_err(map_insert_conflict)?;
+        if let Some(head) = row.get::<_, Option<String>>(7)? {
+            tx.execute(
+                "INSERT INTO merge_sources(workspace, merge_id, source_head_commit_id) VALUES (?1, ?2, ?3)",
+                params![target, merge_id, canonicalize_commit(&format!("{prefix}-{head}"))?],
+            )?;
+        }
+        copied += 1;
+    }
+    Ok(copied)
+}
+
+fn copy_annotations_tx(
+    tx: &Transaction<'_>,
+    source: &str,
+    target: &str,
+    prefix: &str,
+) -> Result<(), StoreError> {
+    // Ids were validated when branches and commits were copied, so plain concatenation is safe here.
+    tx.execute(
+        "INSERT INTO commit_authors(workspace, commit_id, author) \
+         SELECT ?2, ?3 || '-' || commit_id, author FROM commit_authors WHERE workspace=?1",
+        params![source, target, prefix],
+    )?;
+    tx.execute(
+        "INSERT INTO commit_pins(workspace, commit_id, pinned_at_ms) \
+         SELECT ?2, ?3 || '-' || commit_id, pinned_at_ms FROM commit_pins WHERE workspace=?1",
+        params![source, target, prefix],
+    )?;
+    tx.execute(
+        "INSERT INTO branch_archive(workspace, branch, archived_at_ms) \
+         SELECT ?2, ?3 || '/' || branch, archived_at_ms FROM branch_archive WHERE workspace=?1",
+        params![source, target, prefix],
+    )?;
+    tx.execute(
+        "INSERT INTO branch_scratch(workspace, branch, expires_at_ms) \
+         SELECT ?2, ?3 || '/' || branch, expires_at_ms FROM branch_scratch WHERE workspace=?1",
+        params![source, target, prefix],
+    )?;
+    tx.execute(
+        "INSERT INTO commit_acks(workspace, commit_id, actor, kind, acked_at_ms) \
+         SELECT ?2, ?3 || '-' || commit_id, actor, kind, acked_at_ms FROM commit_acks WHERE workspace=?1",
+        params![source, target, prefix],
+    )?;
+    tx.execute(
+        "INSERT INTO commit_picks(workspace, commit_id, source_commit_id, target_branch, picked_at_ms) \
+         SELECT ?2, ?3 || '-' || commit_id, ?3 || '-' || source_commit_id, ?3 || '/' || target_branch, picked_at_ms \
+         FROM commit_picks WHERE workspace=?1",
+        params![source, target, prefix],
+    )?;
+    tx.execute(
+        "INSERT INTO commit_redactions(workspace, commit_id, actor, reason, body_sha256, message_sha256, redacted_at_ms) \
+         SELECT ?2, ?3 || '-' || commit_id, actor, reason, body_sha256, message_sha256, redacted_at_ms \
+         FROM commit_redactions WHERE workspace=?1",
+        params![source, target, prefix],
+    )?;
+    // A same-named template is shared only if its body matches; otherwise the copied
+    // `commit_template_uses` rows would point at different content.
+    let template_conflict = tx.query_row(
+        "SELECT EXISTS(SELECT 1 FROM commit_templates s \
+         JOIN commit_templates t ON t.workspace=?2 AND t.name=s.name \
+         WHERE s.workspace=?1 AND t.body<>s.body)",
+        params![source, target],
+        |row| row.get::<_, bool>(0),
+    )?;
+    if template_conflict {
+        return Err(StoreError::BranchAlreadyExists);
+    }
+    tx.execute(
+        "INSERT OR IGNORE INTO commit_templates(workspace, name, body, updated_at_ms) \
+         SELECT ?2, name, body, updated_at_ms FROM commit_templates WHERE workspace=?1",
+        params![source, target],
+    )?;
+    tx.execute(
+        "INSERT INTO commit_template_uses(workspace, commit_id, template) \
+         SELECT ?2, ?3 || '-' || commit_id, template FROM commit_template_uses WHERE workspace=?1",
+        params![source, target, prefix],
+    )?;
+    Ok(())
+}
@@ -0,0 +1,75 @@
+mod support;
+
+use bm_storage::{
+    AckCounts, AckKind, AppendCommitRequest, CommitAckRequest, ShowCommitRequest, StoreError,
+};
+use support::{commit_request, create_branch, open_store};
+
+fn ack(commit_id: &str, actor: &str, kind: AckKind) -> CommitAckRequest {
+    CommitAckRequest {
+        workspace_id: "ws-acks".to_string(),
+        commit_id: commit_id.to_string(),
+        actor: actor.to_string(),
+        kind,
+        acked_at_ms: 10,
+    }
+}
+
+#[test]
+fn acks_count_distinct_actors_and_keep_one_verdict_per_actor() {
+    let (_dir, mut store) = open_store("acks-verdicts");
+    create_branch(&mut store, "ws-acks", "main", None);
+    store
+        .append_commit(AppendCommitRequest {
+            message: "conclusion".to_string(),
+            body: "use sqlite".to_string(),
+            ..commit_request("ws-acks", "main", "c1", 2)
+        })
+        .expect("commit should append");
+
+    assert!(
+        store
+            .commit_ack(ack("c1", "critic", AckKind::Seen))
+            .unwrap()
+    );
+    assert!(
+        !store
+            .commit_ack(ack("c1", "critic", AckKind::Seen))
+            .unwrap()
+    );
+    assert!(
+        store
+            .commit_ack(ack("c1", "critic", AckKind::Agree))
+            .unwrap()
+    );
+    assert!(
+        store
+            .commit_ack(ack("c1", "reviewer", AckKind::Agree))
+            .unwrap()
+    );
+    assert!(
+        store
+            .commit_ack(ack("c1", "critic", AckKind::Disagree))
+            .unwrap()
+    );
+
+    let counts = store
+        .commit_ack_counts(ShowCommitRequest {
+            workspace_id: "ws-acks".to_string(),
+            commit_id: "c1".to_string(),
+        })
+        .expect("counts should load");
+    assert_eq!(
+        counts,
+        AckCounts {
+            seen: 1,
+            agree: 1,
+            disagree: 1,
+        }
+    );
+
+    let err = store
+        .commit_ack(ack("missing", "critic", AckKind::Seen))
+        .expect_err("unknown commit must be rejected");
+    assert!(matches!(err, StoreError::UnknownId));
+}
@@ -0,0 +1,87 @@
+mod support;
+
+use bm_storage::{
+    ActivityAggregateRequest, ActivityBucket, ActivityGroupBy, ActivityRow, AppendCommitRequest,
+    SqliteStore,
+};
+use support::{branch_request, commit_request, open_store};
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+/// 2024-01-01 00:00 UTC, a Monday.
+const MONDAY_MS: i64 = 1_704_067_200_000;
+
+fn aggregate(
+    store: &SqliteStore,
+    bucket: ActivityBucket,
+    group_by: ActivityGroupBy,
+) -> Vec<ActivityRow> {
+    store
+        .activity_aggregate(ActivityAggregateRequest {
+            workspace_id: "ws-activity".to_string(),
+            bucket,
+            group_by,
+            since_ms: None,
+        })
+        .expect("activity should aggregate")
+}
+
+#[test]
+fn activity_aggregate_buckets_commits_by_day_and_week() {
+    let (_dir, mut store) = open_store("activity-buckets");
+
+    for branch_id in ["main", "idea"] {
+        store
+            .create_branch(branch_request("ws-activity", branch_id, None, MONDAY_MS))
+            .expect("branch should be created");
+    }
+    for (branch_id, commit_id, author, created_at_ms) in [
+        ("main", "c1", Some("alice"), MONDAY_MS + 1),
+        ("main", "c2", Some("alice"), MONDAY_MS + 2),
+        ("idea", "i1", None, MONDAY_MS + DAY_MS + 5),
+        ("main", "c3", Some("bob"), MONDAY_MS + 7 * DAY_MS),
+    ] {
+        store
+            .append_commit(AppendCommitRequest {
+                message: "ab".to_string(),
+                body: "cde".to_string(),
+                author: author.map(ToOwned::to_owned),
+                ..commit_request("ws-activity", branch_id, commit_id, created_at_ms)
+            })
+            .expect("commit should append");
+    }
+
+    let days = aggregate(&store, ActivityBucket::Day, ActivityGroupBy::Branch);
+    let summary = days
+        .iter()
+        .map(|row| {
+            (
+                row.bucket_start_ms,
+                row.key.as_deref(),
+                row.commits,
+                row.bytes,
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        vec![
+            (MONDAY_MS, Some("main"), 2, 10),
+            (MONDAY_MS + DAY_MS, Some("idea"), 1, 5),
+            (MONDAY_MS + 7 * DAY_MS, Some("main"), 1, 5),
+        ]
+    );
+
+    let weeks = aggregate(&store, ActivityBucket::Week, ActivityGroupBy::Author);
+    let summary = weeks
+        .iter()
+        .map(|row| (row.bucket_start_ms, row.key.as_deref(), row.commits))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        vec![
+            (MONDAY_MS, None, 1),
+            (MONDAY_MS, Some("alice"), 2),
+            (MONDAY_MS + 7 * DAY_MS, Some("bob"), 1),
+        ]
+    );
+}
@@ -0,0 +1,103 @@
+mod support;
+
+use bm_core::ids::WorkspaceId;
+use bm_storage::{
+    AppendCommitRequest, ArchiveBranchRequest, ListBranchesRequest, SqliteStore, StoreError,
+};
+use support::{commit_request, create_branch, open_store};
+
+fn archive(branch_id: &str) -> ArchiveBranchRequest {
+    ArchiveBranchRequest {
+        workspace_id: "ws-archive".to_string(),
+        branch_id: branch_id.to_string(),
+        at_ms: 10,
+    }
+}
+
+fn names(branches: Vec<bm_core::ThoughtBranch>) -> Vec<String> {
+    branches
+        .iter()
+        .map(|branch| branch.branch_id().to_string())
+        .collect()
+}
+
+fn list(store: &SqliteStore, archived: bool) -> Vec<String> {
+    let request = ListBranchesRequest {
+        workspace_id: "ws-archive".to_string(),
+        limit: 10,
+        offset: 0,
+    };
+    let listed = if archived {
+        store.list_archived_branches(request)
+    } else {
+        store.list_branches(request)
+    };
+    names(listed.expect("branches should list"))
+}
+
+#[test]
+fn archived_branch_is_hidden_frozen_and_restorable() {
+    let (_dir, mut store) = open_store("archive-lifecycle");
+    let workspace = WorkspaceId::try_new("ws-archive").expect("workspace id");
+
+    for (branch_id, parent) in [
+        ("main", None),
+        ("exp", Some("main")),
+        ("exp-child", Some("exp")),
+    ] {
+        create_branch(&mut store, "ws-archive", branch_id, parent);
+    }
+    store
+        .branch_checkout_set(&workspace, "main")
+        .expect("checkout main");
+
+    let err = store
+        .branch_archive(archive("main"))
+        .expect_err("checked-out branch must not be archived");
+    assert!(matches!(err, StoreError::InvalidInput(_)));
+    let err = store
+        .branch_archive(archive("exp"))
+        .expect_err("branch with an active child must not be archived");
+    assert!(matches!(err, StoreError::InvalidInput(_)));
+
+    assert!(
+        store
+            .branch_archive(archive("exp-child"))
+            .expect("archive child")
+    );
+    assert!(
+        store
+            .branch_archive(archive("exp"))
+            .expect("archive parent")
+    );
+    assert_eq!(list(&store, false), vec!["main"]);
+    assert_eq!(list(&store, true), vec!["exp", "exp-child"]);
+
+    let err = store
+        .append_commit(AppendCommitRequest {
+            message: "late".to_string(),
+            body: "late".to_string(),
+            ..commit_request("ws-archive", "exp", "c1", 11)
+        })
+        .expect_err("archived branch must reject commits");
+    assert!(matches!(
+        err,
+        StoreError::InvalidInput("branch is archived")
+    ));
+
+    let err = store
+        .branch_unarchive(archive("exp-child"))
+        .expect_err("child cannot be restored under an archived parent");
+    assert!(matches!(err, StoreError::InvalidInput(_)));
+    assert!(
+        store
+            .branch_unarchive(archive("exp"))
+            .expect("restore parent")
+    );
+    assert!(
+        !store
+            .branch_unarchive(archive("exp"))
+            .expect("repeat restore")
+    );
+    assert_eq!(list(&store, false), vec!["exp", "main"]);
+}
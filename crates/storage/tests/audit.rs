@@ -0,0 +1,189 @@
+mod support;
+
+use bm_storage::{CommitPinRequest, CommitRedactRequest, DeleteBranchRequest, SqliteStore};
+use rusqlite::{Connection, params};
+use std::path::Path;
+use support::{append_commit, commit_request, create_branch, temp_storage_dir};
+
+fn seed(dir: &Path) {
+    let mut store = SqliteStore::open(dir).expect("fresh storage should open");
+    create_branch(&mut store, "ws-audit", "main", None);
+    for (commit_id, created_at_ms) in [("c1", 2), ("c2", 3)] {
+        store
+            .append_commit(commit_request("ws-audit", "main", commit_id, created_at_ms))
+            .expect("commit should append");
+    }
+    store
+        .commit_pin_set(CommitPinRequest {
+            workspace_id: "ws-audit".to_string(),
+            commit_id: "c1".to_string(),
+            pinned: true,
+            pinned_at_ms: 4,
+        })
+        .expect("pin should succeed");
+}
+
+#[test]
+fn audit_chain_records_every_mutation_and_verifies() {
+    let dir = temp_storage_dir("audit-intact");
+    seed(&dir);
+    let store = SqliteStore::open(&dir).expect("storage should reopen");
+
+    let report = store.audit_verify("ws-audit").expect("verify should run");
+    assert!(report.is_intact(), "fresh chain must verify: {report:?}");
+    assert_eq!(report.records, 4);
+    assert!(report.head_hash.is_some());
+
+    let conn = Connection::open(dir.join("branchmind_rust.db")).expect("db must open");
+    let ops = conn
+        .prepare("SELECT op FROM audit_log WHERE workspace='ws-audit' ORDER BY seq")
+        .expect("audit_log should be queryable")
+        .query_map([], |row| row.get::<_, String>(0))
+        .expect("audit_log should be readable")
+        .collect::<Result<Vec<_>, _>>()
+        .expect("rows should decode");
+    assert_eq!(
+        ops,
+        vec![
+            "branch.create",
+            "commit.append",
+            "commit.append",
+            "commit.pin"
+        ]
+    );
+
+    let empty = store.audit_verify("ws-other").expect("verify should run");
+    assert!(empty.is_intact());
+    assert_eq!(empty.records, 0);
+    assert_eq!(empty.head_hash, None);
+}
+
+#[test]
+fn audit_verify_detects_edited_and_removed_records() {
+    let dir = temp_storage_dir("audit-tamper");
+    seed(&dir);
+    let conn = Connection::open(dir.join("branchmind_rust.db")).expect("db must open");
+
+    conn.execute(
+        "UPDATE audit_log SET subject=?1 WHERE workspace='ws-audit' AND seq=2",
+        params!["forged"],
+    )
+    .expect("raw edit should apply");
+    let store = SqliteStore::open(&dir).expect("storage should reopen");
+    let report = store.audit_verify("ws-audit").expect("verify should run");
+    assert_eq!(report.broken_at_seq, Some(2));
+    assert!(!report.is_intact());
+    drop(store);
+
+    conn.execute(
+        "UPDATE audit_log SET subject='c1' WHERE workspace='ws-audit' AND seq=2",
+        [],
+    )
+    .expect("raw restore should apply");
+    conn.execute(
+        "DELETE FROM audit_log WHERE workspace='ws-audit' AND seq=4",
+        [],
+    )
+    .expect("raw delete should apply");
+    let store = SqliteStore::open(&dir).expect("storage should reopen");
+    let report = store.audit_verify("ws-audit").expect("verify should run");
+    assert_eq!(report.broken_at_seq, None);
+    assert!(report.truncated);
+    assert_eq!(report.records, 3);
+}
+
+#[test]
+fn audit_verify_detects_commit_content_edited_in_place() {
+    let dir = temp_storage_dir("audit-content");
+    seed(&dir);
+    let conn = Connection::open(dir.join("branchmind_rust.db")).expect("db must open");
+    conn.execute(
+        "UPDATE commits SET body=?1 WHERE workspace='ws-audit' AND commit_id='c2'",
+        params!["rewritten reasoning"],
+    )
+    .expect("raw body edit should apply");
+
+    let store = SqliteStore::open(&dir).expect("storage should reopen");
+    let report = store.audit_verify("ws-audit").expect("verify should run");
+    assert_eq!(report.broken_at_seq, Some(3), "c2 is appended by record 3");
+    assert!(!report.truncated);
+}
+
+#[test]
+fn audit_verify_detects_commits_moved_retimed_or_removed() {
+    for (label, tamper) in [
+        (
+            "audit-move",
+            "UPDATE commits SET branch='side' WHERE workspace='ws-audit' AND commit_id='c2'",
+        ),
+        (
+            "audit-retime",
+            "UPDATE commits SET created_at_ms=99 WHERE workspace='ws-audit' AND commit_id='c2'",
+        ),
+        (
+            "audit-remove",
+            "DELETE FROM commits WHERE workspace='ws-audit' AND commit_id='c2'",
+        ),
+    ] {
+        let dir = temp_storage_dir(label);
+        seed(&dir);
+        let conn = Connection::open(dir.join("branchmind_rust.db")).expect("db must open");
+        conn.execute_batch(&format!("PRAGMA foreign_keys=OFF; {tamper};"))
+            .expect("raw tamper should apply");
+
+        let store = SqliteStore::open(&dir).expect("storage should reopen");
+        let report = store.audit_verify("ws-audit").expect("verify should run");
+        assert_eq!(report.broken_at_seq, Some(3), "{label}: {report:?}");
+    }
+}
+
+#[test]
+fn audit_verify_accepts_commits_removed_with_their_branch() {
+    let dir = temp_storage_dir("audit-branch-delete");
+    seed(&dir);
+    let mut store = SqliteStore::open(&dir).expect("storage should reopen");
+    create_branch(&mut store, "ws-audit", "side", None);
+    append_commit(&mut store, "ws-audit", "side", "s1", 5);
+    store
+        .delete_branch(DeleteBranchRequest {
+            workspace_id: "ws-audit".to_string(),
+            branch_id: "side".to_string(),
+        })
+        .expect("branch should delete");
+
+    let report = store.audit_verify("ws-audit").expect("verify should run");
+    assert!(
+        report.is_intact(),
+        "audited deletion must verify: {report:?}"
+    );
+}
+
+#[test]
+fn audit_verify_accepts_redaction_and_flags_a_wiped_chain() {
+    let dir = temp_storage_dir("audit-redact-wipe");
+    seed(&dir);
+    let mut store = SqliteStore::open(&dir).expect("storage should reopen");
+    store
+        .commit_redact(CommitRedactRequest {
+            workspace_id: "ws-audit".to_string(),
+            commit_id: "c1".to_string(),
+            actor: "admin".to_string(),
+            reason: "leaked token".to_string(),
+            redacted_at_ms: 5,
+        })
+        .expect("redaction should succeed");
+    let report = store.audit_verify("ws-audit").expect("verify should run");
+    assert!(report.is_intact(), "redaction is audited: {report:?}");
+    drop(store);
+
+    let conn = Connection::open(dir.join("branchmind_rust.db")).expect("db must open");
+    conn.execute_batch(
+        "DELETE FROM audit_log WHERE workspace='ws-audit'; \
+         DELETE FROM audit_head WHERE workspace='ws-audit';",
+    )
+    .expect("raw wipe should apply");
+    let store = SqliteStore::open(&dir).expect("storage should reopen");
+    let report = store.audit_verify("ws-audit").expect("verify should run");
+    assert_eq!(report.records, 0);
+    assert!(report.truncated, "empty chain with commits must not verify");
+}
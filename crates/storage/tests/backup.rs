@@ -0,0 +1,54 @@
+mod support;
+
+use bm_storage::{AppendCommitRequest, ListBranchesRequest, ShowCommitRequest, SqliteStore};
+use support::{commit_request, create_branch, open_store, temp_storage_dir};
+
+#[test]
+fn backup_to_writes_verified_snapshot_that_reopens_as_a_store() {
+    let (_dir, mut store) = open_store("backup-source");
+
+    for workspace in ["ws-b", "ws-a"] {
+        create_branch(&mut store, workspace, "main", None);
+    }
+    store
+        .append_commit(AppendCommitRequest {
+            message: "first".to_string(),
+            body: "first body".to_string(),
+            ..commit_request("ws-a", "main", "c1", 2)
+        })
+        .expect("commit should append");
+
+    let backup_dir = temp_storage_dir("backup-target");
+    let backup_path = backup_dir.join("branchmind_rust.db");
+    let manifest = store
+        .backup_to(&backup_path)
+        .expect("backup should succeed");
+    assert_eq!(manifest.path, backup_path);
+    assert_eq!(manifest.schema_version, 3);
+    assert_eq!(manifest.workspaces, vec!["ws-a", "ws-b"]);
+
+    let err = store
+        .backup_to(&backup_path)
+        .expect_err("existing backup target must not be overwritten");
+    assert_eq!(err.code(), "INVALID_INPUT");
+
+    let restored = SqliteStore::open(&backup_dir).expect("snapshot should open as a store");
+    let commit = restored
+        .show_commit(ShowCommitRequest {
+            workspace_id: "ws-a".to_string(),
+            commit_id: "c1".to_string(),
+        })
+        .expect("show commit should succeed");
+    assert_eq!(
+        commit.map(|c| c.body().to_string()),
+        Some("first body".to_string())
+    );
+    let branches = restored
+        .list_branches(ListBranchesRequest {
+            workspace_id: "ws-b".to_string(),
+            limit: 10,
+            offset: 0,
+        })
+        .expect("branches should list");
+    assert_eq!(branches.len(), 1);
+}
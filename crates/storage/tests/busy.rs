@@ -0,0 +1,109 @@
+mod support;
+
+use bm_storage::{CreateBranchRequest, SqliteStore, StoreConfig, StoreError};
+use rusqlite::Connection;
+use std::time::{Duration, Instant};
+use support::{open_store, temp_storage_dir};
+
+fn branch(branch_id: &str) -> CreateBranchRequest {
+    CreateBranchRequest {
+        workspace_id: "ws-busy".to_string(),
+        branch_id: branch_id.to_string(),
+        parent_branch_id: None,
+        created_at_ms: 1,
+    }
+}
+
+#[test]
+fn write_against_held_lock_reports_busy_then_succeeds_after_release() {
+    let (dir, mut store) = open_store("busy-held-lock");
+    store
+        .set_busy_timeout(Duration::from_millis(1))
+        .expect("busy timeout should be settable");
+
+    let holder = Connection::open(dir.join("branchmind_rust.db")).expect("db must open");
+    holder
+        .execute_batch("BEGIN IMMEDIATE;")
+        .expect("second connection should take the write lock");
+
+    let err = store
+        .create_branch(branch("main"))
+        .expect_err("write must fail while another writer holds the lock");
+    let StoreError::Busy { waited_ms } = err else {
+        panic!("expected StoreError::Busy, got {err:?}");
+    };
+    assert!(waited_ms > 0, "retries should back off before giving up");
+
+    holder.execute_batch("ROLLBACK;").expect("lock release");
+    store
+        .create_branch(branch("main"))
+        .expect("write should succeed once the lock is released");
+}
+
+#[test]
+fn max_busy_retries_stay_within_a_bounded_wait() {
+    let dir = temp_storage_dir("busy-max-retries");
+    let mut store = SqliteStore::open_with_config(
+        &dir,
+        StoreConfig {
+            busy_timeout: Duration::from_millis(1),
+            busy_retries: 32,
+            ..StoreConfig::default()
+        },
+    )
+    .expect("storage with max retries should open");
+
+    let holder = Connection::open(dir.join("branchmind_rust.db")).expect("db must open");
+    holder
+        .execute_batch("BEGIN IMMEDIATE;")
+        .expect("second connection should take the write lock");
+
+    let started = Instant::now();
+    let err = store
+        .create_branch(branch("main"))
+        .expect_err("write must fail while another writer holds the lock");
+    assert!(matches!(err, StoreError::Busy { .. }), "{err:?}");
+    assert!(
+        started.elapsed() < Duration::from_secs(5),
+        "32 retries must not back off unboundedly: {:?}",
+        started.elapsed()
+    );
+    holder.execute_batch("ROLLBACK;").expect("lock release");
+}
+
+#[test]
+fn commit_blocked_by_a_reader_reports_busy_and_rolls_back() {
+    let (dir, mut store) = open_store("busy-commit");
+    store
+        .set_busy_timeout(Duration::from_millis(1))
+        .expect("busy timeout should be settable");
+
+    // In rollback-journal mode a reader's shared lock lets BEGIN IMMEDIATE through but
+    // keeps COMMIT from taking the exclusive lock.
+    let reader = Connection::open(dir.join("branchmind_rust.db")).expect("db must open");
+    reader
+        .execute_batch("BEGIN; SELECT COUNT(1) FROM branches;")
+        .expect("second connection should hold a read lock");
+
+    let err = store
+        .create_branch(branch("main"))
+        .expect_err("commit must fail while a reader holds the database");
+    assert!(matches!(err, StoreError::Busy { .. }), "{err:?}");
+
+    reader.execute_batch("COMMIT;").expect("read lock release");
+    store
+        .create_branch(branch("main"))
+        .expect("the failed write must have rolled back, so the branch is new");
+}
+
+#[test]
+fn set_busy_timeout_is_bounded_like_the_config() {
+    let (_dir, mut store) = open_store("busy-timeout-bound");
+    assert!(matches!(
+        store.set_busy_timeout(Duration::from_secs(61)),
+        Err(StoreError::InvalidInput(_))
+    ));
+    store
+        .set_busy_timeout(Duration::from_secs(60))
+        .expect("the documented maximum is accepted");
+}
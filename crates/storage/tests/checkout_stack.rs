@@ -0,0 +1,74 @@
+mod support;
+
+use bm_core::ids::WorkspaceId;
+use bm_storage::{
+    ArchiveBranchRequest, CheckoutPopRequest, CheckoutPushRequest, DeleteBranchRequest, SqliteStore,
+};
+use support::{create_branch, open_store};
+
+fn push(store: &mut SqliteStore, branch_id: &str, at_ms: i64) -> Option<String> {
+    store
+        .branch_checkout_push(CheckoutPushRequest {
+            workspace_id: "ws-stack".to_string(),
+            branch_id: branch_id.to_string(),
+            at_ms,
+        })
+        .expect("push should succeed")
+}
+
+fn pop(store: &mut SqliteStore) -> Option<String> {
+    store
+        .branch_checkout_pop(CheckoutPopRequest {
+            workspace_id: "ws-stack".to_string(),
+            at_ms: 99,
+        })
+        .expect("pop should succeed")
+}
+
+fn current(store: &SqliteStore) -> Option<String> {
+    let workspace = WorkspaceId::try_new("ws-stack").expect("workspace id must be valid");
+    store
+        .branch_checkout_get(&workspace)
+        .expect("checkout should load")
+}
+
+#[test]
+fn checkout_stack_returns_to_previous_branches_in_order() {
+    let (_dir, mut store) = open_store("checkout-stack-order");
+    for branch_id in ["main", "blocker", "gone", "frozen", "deep"] {
+        create_branch(&mut store, "ws-stack", branch_id, None);
+    }
+
+    assert_eq!(push(&mut store, "main", 2), None);
+    assert_eq!(push(&mut store, "blocker", 3), Some("main".to_string()));
+    assert_eq!(push(&mut store, "gone", 4), Some("blocker".to_string()));
+    assert_eq!(push(&mut store, "frozen", 5), Some("gone".to_string()));
+    assert_eq!(push(&mut store, "deep", 6), Some("frozen".to_string()));
+    let stack = store
+        .branch_checkout_stack("ws-stack")
+        .expect("stack should list")
+        .into_iter()
+        .map(|frame| frame.branch_id)
+        .collect::<Vec<_>>();
+    assert_eq!(stack, vec!["frozen", "gone", "blocker", "main"]);
+
+    store
+        .branch_archive(ArchiveBranchRequest {
+            workspace_id: "ws-stack".to_string(),
+            branch_id: "frozen".to_string(),
+            at_ms: 7,
+        })
+        .expect("archive should succeed");
+    store
+        .delete_branch(DeleteBranchRequest {
+            workspace_id: "ws-stack".to_string(),
+            branch_id: "gone".to_string(),
+        })
+        .expect("delete should succeed");
+
+    assert_eq!(pop(&mut store), Some("blocker".to_string()));
+    assert_eq!(current(&store), Some("blocker".to_string()));
+    assert_eq!(pop(&mut store), Some("main".to_string()));
+    assert_eq!(pop(&mut store), None);
+    assert_eq!(current(&store), Some("main".to_string()));
+}
@@ -0,0 +1,104 @@
+mod support;
+
+use bm_storage::{
+    AppendCommitRequest, CherryPickRequest, ShowCommitRequest, SqliteStore, StoreError,
+};
+use support::{branch_request, commit_request, open_store};
+
+fn create_branch(store: &mut SqliteStore, branch_id: &str, parent: Option<&str>) {
+    store
+        .create_branch(branch_request("ws-pick", branch_id, parent, 1))
+        .expect("branch should be created");
+}
+
+fn append(store: &mut SqliteStore, branch_id: &str, commit_id: &str, author: Option<&str>) {
+    store
+        .append_commit(AppendCommitRequest {
+            message: format!("msg {commit_id}"),
+            body: format!("body {commit_id}"),
+            author: author.map(str::to_string),
+            ..commit_request("ws-pick", branch_id, commit_id, 2)
+        })
+        .expect("commit should append");
+}
+
+fn pick(commit_ids: &[&str]) -> CherryPickRequest {
+    CherryPickRequest {
+        workspace_id: "ws-pick".to_string(),
+        source_branch_id: "idea".to_string(),
+        target_branch_id: "main".to_string(),
+        commit_ids: commit_ids.iter().map(|id| id.to_string()).collect(),
+        author: None,
+        picked_at_ms: 5,
+    }
+}
+
+#[test]
+fn cherry_pick_copies_selected_commits_once_with_provenance() {
+    let (_dir, mut store) = open_store("cherry-pick-copies");
+    create_branch(&mut store, "main", None);
+    create_branch(&mut store, "idea", Some("main"));
+    append(&mut store, "main", "m1", None);
+    append(&mut store, "idea", "i1", Some("explorer"));
+    append(&mut store, "idea", "i2", None);
+    append(&mut store, "idea", "i3", None);
+
+    let report = store
+        .commit_cherry_pick(pick(&["i3", "i1", "i3"]))
+        .expect("cherry-pick should succeed");
+    assert!(report.already_picked.is_empty());
+    assert_eq!(report.picked.len(), 2);
+    assert_eq!(report.picked[0].body(), "body i3");
+    assert_eq!(report.picked[0].parent_commit_id(), Some("m1"));
+    assert_eq!(
+        report.picked[1].parent_commit_id(),
+        Some(report.picked[0].commit_id())
+    );
+    assert_eq!(report.picked[1].branch_id(), "main");
+
+    let copy = ShowCommitRequest {
+        workspace_id: "ws-pick".to_string(),
+        commit_id: report.picked[1].commit_id().to_string(),
+    };
+    assert_eq!(
+        store
+            .commit_picked_from(copy.clone())
+            .expect("provenance should load"),
+        Some("i1".to_string())
+    );
+    assert_eq!(
+        store.commit_author(copy).expect("author should load"),
+        Some("explorer".to_string())
+    );
+
+    let again = store
+        .commit_cherry_pick(pick(&["i1", "i2"]))
+        .expect("repeat cherry-pick should succeed");
+    assert_eq!(again.already_picked, vec!["i1".to_string()]);
+    assert_eq!(again.picked.len(), 1);
+    assert_eq!(again.picked[0].message(), "msg i2");
+}
+
+#[test]
+fn cherry_pick_rejects_commits_outside_the_source_branch() {
+    let (_dir, mut store) = open_store("cherry-pick-reject");
+    create_branch(&mut store, "main", None);
+    create_branch(&mut store, "idea", Some("main"));
+    append(&mut store, "main", "m1", None);
+    append(&mut store, "idea", "i1", None);
+
+    let err = store
+        .commit_cherry_pick(pick(&["i1", "m1"]))
+        .expect_err("commit from another branch must be rejected");
+    assert!(matches!(err, StoreError::InvalidInput(_)));
+    let err = store
+        .commit_cherry_pick(pick(&["missing"]))
+        .expect_err("unknown commit must be rejected");
+    assert!(matches!(err, StoreError::UnknownId));
+
+    // The failed batch must not leave a partial copy of i1 behind.
+    let retry = store
+        .commit_cherry_pick(pick(&["i1"]))
+        .expect("cherry-pick should succeed");
+    assert_eq!(retry.picked.len(), 1);
+}
@@ -0,0 +1,65 @@
+mod support;
+
+use bm_storage::{
+    AppendCommitRequest, CreateMergeRecordRequest, ShowCommitRequest, SqliteStore, StoreError,
+};
+use support::{commit_request, create_branch, open_store};
+
+fn author_of(store: &SqliteStore, commit_id: &str) -> Option<String> {
+    store
+        .commit_author(ShowCommitRequest {
+            workspace_id: "ws-team".to_string(),
+            commit_id: commit_id.to_string(),
+        })
+        .expect("commit author should be readable")
+}
+
+#[test]
+fn shared_branch_records_author_per_commit_and_merge() {
+    let (_dir, mut store) = open_store("authors-shared");
+
+    for (branch_id, parent) in [("main", None), ("idea", Some("main"))] {
+        create_branch(&mut store, "ws-team", branch_id, parent);
+    }
+
+    let commit = |branch_id: &str, commit_id: &str, author: Option<&str>| AppendCommitRequest {
+        author: author.map(ToOwned::to_owned),
+        ..commit_request("ws-team", branch_id, commit_id, 2)
+    };
+
+    store
+        .append_commit(commit("idea", "c1", Some("Agent-A")))
+        .expect("first writer commit");
+    store
+        .append_commit(commit("idea", "c2", Some("agent-b")))
+        .expect("second writer commit");
+    store
+        .append_commit(commit("idea", "c3", None))
+        .expect("anonymous commit");
+
+    assert_eq!(author_of(&store, "c1").as_deref(), Some("agent-a"));
+    assert_eq!(author_of(&store, "c2").as_deref(), Some("agent-b"));
+    assert_eq!(author_of(&store, "c3"), None);
+
+    let err = store
+        .append_commit(commit("idea", "c4", Some("bad author")))
+        .expect_err("invalid author must be rejected");
+    assert!(matches!(err, StoreError::InvalidInput(_)));
+
+    store
+        .create_merge_record(CreateMergeRecordRequest {
+            workspace_id: "ws-team".to_string(),
+            merge_id: "m1".to_string(),
+            source_branch_id: "idea".to_string(),
+            target_branch_id: "main".to_string(),
+            strategy: "squash".to_string(),
+            summary: "merge idea".to_string(),
+            synthesis_commit_id: "c-merge".to_string(),
+            synthesis_message: "merge idea".to_string(),
+            synthesis_body: "merge idea".to_string(),
+            author: Some("agent-a".to_string()),
+            created_at_ms: 3,
+        })
+        .expect("merge should be recorded");
+    assert_eq!(author_of(&store, "c-merge").as_deref(), Some("agent-a"));
+}
@@ -0,0 +1,73 @@
+mod support;
+
+use bm_storage::{CreateBranchRequest, ListBranchesRequest, SqliteStore, StoreConfig, StoreError};
+use std::time::Duration;
+use support::temp_storage_dir;
+
+fn branch(branch_id: &str, parent: Option<&str>) -> CreateBranchRequest {
+    CreateBranchRequest {
+        workspace_id: "ws-config".to_string(),
+        branch_id: branch_id.to_string(),
+        parent_branch_id: parent.map(ToOwned::to_owned),
+        created_at_ms: 1,
+    }
+}
+
+#[test]
+fn open_with_config_rejects_out_of_range_limits() {
+    for config in [
+        StoreConfig {
+            max_page_limit: 0,
+            ..StoreConfig::default()
+        },
+        StoreConfig {
+            max_branch_depth: 10_000,
+            ..StoreConfig::default()
+        },
+        StoreConfig {
+            busy_timeout: Duration::from_secs(600),
+            ..StoreConfig::default()
+        },
+    ] {
+        let dir = temp_storage_dir("config-invalid");
+        let err = SqliteStore::open_with_config(&dir, config)
+            .expect_err("invalid config must be rejected");
+        assert!(matches!(err, StoreError::InvalidInput(_)));
+    }
+}
+
+#[test]
+fn configured_depth_and_page_limits_are_enforced() {
+    let dir = temp_storage_dir("config-limits");
+    let mut store = SqliteStore::open_with_config(
+        &dir,
+        StoreConfig {
+            max_branch_depth: 2,
+            max_page_limit: 2,
+            ..StoreConfig::default()
+        },
+    )
+    .expect("valid config should open");
+    assert_eq!(store.config().max_branch_depth, 2);
+
+    store.create_branch(branch("a", None)).expect("root");
+    store
+        .create_branch(branch("b", Some("a")))
+        .expect("depth 1");
+    store
+        .create_branch(branch("c", Some("b")))
+        .expect("depth 2");
+    let err = store
+        .create_branch(branch("d", Some("c")))
+        .expect_err("depth 3 exceeds the configured limit");
+    assert!(matches!(err, StoreError::BranchDepthExceeded));
+
+    let listed = store
+        .list_branches(ListBranchesRequest {
+            workspace_id: "ws-config".to_string(),
+            limit: 50,
+            offset: 0,
+        })
+        .expect("branches should list");
+    assert_eq!(listed.len(), 2);
+}
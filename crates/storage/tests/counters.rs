@@ -0,0 +1,109 @@
+mod support;
+
+use bm_storage::{CreateMergeRecordRequest, SqliteStore, StoreError};
+use support::{append_commit, create_branch, open_store};
+
+#[test]
+fn counters_are_monotonic_per_workspace_and_survive_reopen() {
+    let (dir, mut store) = open_store("counters-monotonic");
+
+    assert_eq!(
+        store
+            .counter_peek("ws-a", "experiment")
+            .expect("counter call should succeed"),
+        0
+    );
+    assert_eq!(
+        store
+            .counter_next("ws-a", "experiment")
+            .expect("counter call should succeed"),
+        1
+    );
+    assert_eq!(
+        store
+            .counter_next("ws-a", "experiment")
+            .expect("counter call should succeed"),
+        2
+    );
+    assert_eq!(
+        store
+            .counter_next("ws-b", "experiment")
+            .expect("counter call should succeed"),
+        1
+    );
+    assert_eq!(
+        store
+            .counter_next("ws-a", "run")
+            .expect("counter call should succeed"),
+        1
+    );
+    drop(store);
+
+    let mut store = SqliteStore::open(&dir).expect("storage should reopen");
+    assert_eq!(
+        store
+            .counter_peek("ws-a", "experiment")
+            .expect("counter call should succeed"),
+        2
+    );
+    assert_eq!(
+        store
+            .counter_next("ws-a", "experiment")
+            .expect("counter call should succeed"),
+        3
+    );
+}
+
+#[test]
+fn counter_names_are_validated_and_bm_prefix_is_reserved() {
+    let (_dir, mut store) = open_store("counters-reserved");
+
+    for name in ["bm.session", "BM.session", "", "-lead"] {
+        let err = store
+            .counter_next("ws-a", name)
+            .expect_err("invalid counter name must be rejected");
+        assert!(matches!(err, StoreError::InvalidInput(_)), "{name}");
+    }
+}
+
+#[test]
+fn numbered_merge_allocates_its_id_in_the_merge_transaction() {
+    let (_dir, mut store) = open_store("counters-numbered-merge");
+    create_branch(&mut store, "ws-a", "main", None);
+    append_commit(&mut store, "ws-a", "main", "c1", 2);
+    create_branch(&mut store, "ws-a", "idea", Some("main"));
+    let merge = |source: &str| {
+        let source = source.to_string();
+        move |seq: u64| CreateMergeRecordRequest {
+            workspace_id: "ws-a".to_string(),
+            merge_id: format!("m{seq}"),
+            source_branch_id: source,
+            target_branch_id: "main".to_string(),
+            strategy: "squash".to_string(),
+            summary: "merge".to_string(),
+            synthesis_commit_id: format!("s{seq}"),
+            synthesis_message: "merge".to_string(),
+            synthesis_body: "merge".to_string(),
+            author: None,
+            created_at_ms: 3,
+        }
+    };
+
+    let err = store
+        .create_merge_record_numbered("ws-a", "merge", merge("missing"))
+        .expect_err("merge from an unknown branch must fail");
+    assert!(matches!(err, StoreError::UnknownId), "{err:?}");
+    assert_eq!(
+        store
+            .counter_peek("ws-a", "merge")
+            .expect("counter call should succeed"),
+        0,
+        "a failed merge must not consume its id"
+    );
+
+    let record = store
+        .create_merge_record_numbered("ws-a", "merge", merge("idea"))
+        .expect("merge should be recorded");
+    assert_eq!(record.merge_id(), "m1");
+    assert_eq!(record.synthesis_commit_id(), "s1");
+}
@@ -0,0 +1,57 @@
+mod support;
+
+use bm_storage::{ExplainTarget, ListBranchesRequest, SqliteStore, StoreConfig};
+use support::{create_branch, temp_storage_dir};
+
+#[test]
+fn explain_query_plan_covers_every_target_and_uses_indexes() {
+    let dir = temp_storage_dir("explain-plans");
+    let store = SqliteStore::open(&dir).expect("fresh storage should open");
+
+    for target in [
+        ExplainTarget::ListBranches,
+        ExplainTarget::ListMergeRecords,
+        ExplainTarget::ShowCommit,
+        ExplainTarget::ListPinnedCommits,
+    ] {
+        let plan = store
+            .explain_query_plan(target)
+            .expect("plan should be explained");
+        assert!(plan.sql.starts_with("SELECT"), "{target:?}: {}", plan.sql);
+        assert!(!plan.steps.is_empty(), "{target:?} must have a plan");
+    }
+
+    let branches = store
+        .explain_query_plan(ExplainTarget::ListBranches)
+        .expect("plan should be explained");
+    assert!(
+        branches
+            .steps
+            .iter()
+            .any(|step| step.contains("idx_branches_workspace_created")),
+        "branch listing should use its index: {:?}",
+        branches.steps
+    );
+}
+
+#[test]
+fn traced_store_behaves_like_an_untraced_one() {
+    let dir = temp_storage_dir("explain-trace");
+    let mut store = SqliteStore::open_with_config(
+        &dir,
+        StoreConfig {
+            trace_sql: true,
+            ..StoreConfig::default()
+        },
+    )
+    .expect("traced storage should open");
+    create_branch(&mut store, "ws-trace", "main", None);
+    let listed = store
+        .list_branches(ListBranchesRequest {
+            workspace_id: "ws-trace".to_string(),
+            limit: 10,
+            offset: 0,
+        })
+        .expect("branches should list");
+    assert_eq!(listed.len(), 1);
+}
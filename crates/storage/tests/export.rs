@@ -0,0 +1,52 @@
+mod support;
+
+use bm_storage::{AppendCommitRequest, ExportTable, ExportTableRequest, SqliteStore};
+use support::{commit_request, create_branch, open_store};
+
+fn export(store: &SqliteStore, table: ExportTable) -> (usize, String) {
+    let mut out = Vec::new();
+    let rows = store
+        .export_table_csv(
+            ExportTableRequest {
+                workspace_id: "ws-export".to_string(),
+                table,
+            },
+            &mut out,
+        )
+        .expect("export should run");
+    (rows, String::from_utf8(out).expect("csv must be utf-8"))
+}
+
+#[test]
+fn export_writes_stable_columns_and_escapes_fields() {
+    let (_dir, mut store) = open_store("export-csv");
+    create_branch(&mut store, "ws-export", "main", None);
+    store
+        .append_commit(AppendCommitRequest {
+            message: "pick \"sqlite\", not files".to_string(),
+            body: "secret body".to_string(),
+            author: Some("alice".to_string()),
+            ..commit_request("ws-export", "main", "c1", 2)
+        })
+        .expect("commit should append");
+
+    let (rows, csv) = export(&store, ExportTable::Commits);
+    assert_eq!(rows, 1);
+    assert_eq!(
+        csv,
+        "workspace,commit_id,branch,parent_commit_id,author,message,body_bytes,created_at_ms\r\n\
+         ws-export,c1,main,,alice,\"pick \"\"sqlite\"\", not files\",11,2\r\n"
+    );
+    assert!(!csv.contains("secret body"));
+
+    let (rows, csv) = export(&store, ExportTable::Branches);
+    assert_eq!(rows, 1);
+    assert!(csv.ends_with("ws-export,main,,c1,1,2,0\r\n"), "{csv}");
+
+    let (rows, csv) = export(&store, ExportTable::Merges);
+    assert_eq!(rows, 0);
+    assert_eq!(csv.trim_end(), ExportTable::Merges.columns().join(","));
+
+    let (rows, _) = export(&store, ExportTable::Audit);
+    assert_eq!(rows, 2);
+}
@@ -0,0 +1,130 @@
+mod support;
+
+use bm_storage::{IntegrityIssueKind, IntegrityRepairRequest, ListBranchesRequest, SqliteStore};
+use rusqlite::{Connection, params};
+use support::{commit_request, create_branch, open_store};
+
+fn seed(store: &mut SqliteStore) {
+    create_branch(store, "ws-int", "main", None);
+    for (commit_id, at) in [("c1", 2), ("c2", 3)] {
+        store
+            .append_commit(commit_request("ws-int", "main", commit_id, at))
+            .expect("commit should append");
+    }
+}
+
+#[test]
+fn integrity_check_is_clean_for_a_fresh_workspace() {
+    let (_dir, mut store) = open_store("integrity-clean");
+    seed(&mut store);
+
+    let report = store.integrity_check("ws-int").expect("check should run");
+    assert!(report.is_clean(), "{report:?}");
+}
+
+#[test]
+fn integrity_repair_fixes_safe_cases_and_leaves_the_rest() {
+    let (dir, mut store) = open_store("integrity-repair");
+    seed(&mut store);
+    drop(store);
+
+    let conn = Connection::open(dir.join("branchmind_rust.db")).expect("db must open");
+    conn.execute(
+        "UPDATE branches SET head_commit_id='gone' WHERE workspace='ws-int' AND name='main'",
+        [],
+    )
+    .expect("head must be corrupted");
+    conn.execute(
+        "INSERT INTO commit_template_uses(workspace, commit_id, template) VALUES (?1, 'gone', 't')",
+        params!["ws-int"],
+    )
+    .expect("orphan template use must insert");
+    conn.execute(
+        "INSERT INTO branch_scratch(workspace, branch, expires_at_ms) VALUES (?1, 'gone', 9)",
+        params!["ws-int"],
+    )
+    .expect("orphan scratch must insert");
+    conn.execute(
+        "UPDATE audit_log SET subject='tampered' WHERE workspace='ws-int' AND seq=1",
+        [],
+    )
+    .expect("audit must be tampered");
+    drop(conn);
+
+    let mut store = SqliteStore::open(&dir).expect("storage should reopen");
+    let kinds = store
+        .integrity_check("ws-int")
+        .expect("check should run")
+        .issues
+        .iter()
+        .map(|issue| issue.kind)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        vec![
+            IntegrityIssueKind::DanglingHead,
+            IntegrityIssueKind::OrphanTemplateUse,
+            IntegrityIssueKind::OrphanScratch,
+            IntegrityIssueKind::AuditChain,
+        ]
+    );
+
+    let repaired = store
+        .integrity_repair(IntegrityRepairRequest {
+            workspace_id: "ws-int".to_string(),
+            repaired_at_ms: 10,
+        })
+        .expect("repair should run");
+    assert_eq!(repaired.issues.len(), 3);
+
+    let branches = store
+        .list_branches(ListBranchesRequest {
+            workspace_id: "ws-int".to_string(),
+            limit: 10,
+            offset: 0,
+        })
+        .expect("branches should list");
+    assert_eq!(branches[0].head_commit_id(), Some("c2"));
+
+    let remaining = store.integrity_check("ws-int").expect("check should run");
+    assert_eq!(remaining.issues.len(), 1);
+    assert_eq!(remaining.issues[0].kind, IntegrityIssueKind::AuditChain);
+    assert!(!remaining.issues[0].kind.is_repairable());
+}
+
+#[test]
+fn dangling_head_repair_follows_the_chain_not_the_clock() {
+    let (dir, mut store) = open_store("integrity-stale-clock");
+    create_branch(&mut store, "ws-int", "main", None);
+    // The tip carries an older timestamp than its parent.
+    for (commit_id, at) in [("c1", 5), ("c2", 3)] {
+        store
+            .append_commit(commit_request("ws-int", "main", commit_id, at))
+            .expect("commit should append");
+    }
+    drop(store);
+
+    let conn = Connection::open(dir.join("branchmind_rust.db")).expect("db must open");
+    conn.execute(
+        "UPDATE branches SET head_commit_id='gone' WHERE workspace='ws-int' AND name='main'",
+        [],
+    )
+    .expect("head must be corrupted");
+    drop(conn);
+
+    let mut store = SqliteStore::open(&dir).expect("storage should reopen");
+    store
+        .integrity_repair(IntegrityRepairRequest {
+            workspace_id: "ws-int".to_string(),
+            repaired_at_ms: 10,
+        })
+        .expect("repair should run");
+    let branches = store
+        .list_branches(ListBranchesRequest {
+            workspace_id: "ws-int".to_string(),
+            limit: 10,
+            offset: 0,
+        })
+        .expect("branches should list");
+    assert_eq!(branches[0].head_commit_id(), Some("c2"));
+}
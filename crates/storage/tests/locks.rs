@@ -0,0 +1,177 @@
+mod support;
+
+use bm_storage::{
+    ArchiveBranchRequest, DeleteBranchRequest, PruneScratchBranchesRequest, SqliteStore,
+    StoreError, WorkspaceLockRequest,
+};
+use support::{create_branch, temp_storage_dir};
+
+fn lock(holder: &str, ttl_ms: i64) -> WorkspaceLockRequest {
+    WorkspaceLockRequest {
+        workspace_id: "ws-lock".to_string(),
+        holder: holder.to_string(),
+        purpose: "nightly prune".to_string(),
+        ttl_ms,
+    }
+}
+
+fn delete(branch_id: &str) -> DeleteBranchRequest {
+    DeleteBranchRequest {
+        workspace_id: "ws-lock".to_string(),
+        branch_id: branch_id.to_string(),
+    }
+}
+
+#[test]
+fn lock_blocks_destructive_work_from_other_handles_only() {
+    let dir = temp_storage_dir("locks-blocks");
+    let mut cron = SqliteStore::open(&dir).expect("fresh storage should open");
+    let mut agent = SqliteStore::open(&dir).expect("second handle should open");
+    for branch_id in ["a", "b"] {
+        create_branch(&mut cron, "ws-lock", branch_id, None);
+    }
+
+    let held = cron
+        .workspace_lock(lock("cron", 60_000))
+        .expect("lock should be taken");
+    assert_eq!(held.holder, "cron");
+
+    let err = agent
+        .delete_branch(delete("a"))
+        .expect_err("other handle must be blocked");
+    let StoreError::WorkspaceLocked { holder } = err else {
+        panic!("expected WorkspaceLocked, got {err:?}");
+    };
+    assert_eq!(holder, "cron");
+    assert!(matches!(
+        agent.prune_scratch_branches(PruneScratchBranchesRequest {
+            workspace_id: "ws-lock".to_string(),
+            now_ms: 2,
+        }),
+        Err(StoreError::WorkspaceLocked { .. })
+    ));
+    let archive = ArchiveBranchRequest {
+        workspace_id: "ws-lock".to_string(),
+        branch_id: "b".to_string(),
+        at_ms: 2,
+    };
+    assert!(matches!(
+        agent.branch_archive(archive.clone()),
+        Err(StoreError::WorkspaceLocked { .. })
+    ));
+    assert!(matches!(
+        agent.branch_unarchive(archive),
+        Err(StoreError::WorkspaceLocked { .. })
+    ));
+    assert!(matches!(
+        agent.workspace_lock(lock("agent", 60_000)),
+        Err(StoreError::WorkspaceLocked { .. })
+    ));
+
+    cron.delete_branch(delete("a"))
+        .expect("lock holder may delete");
+    assert_eq!(
+        agent
+            .list_workspace_locks()
+            .expect("locks should list")
+            .len(),
+        1
+    );
+
+    assert!(
+        !agent
+            .workspace_unlock("ws-lock", "agent")
+            .expect("unlock should run")
+    );
+    assert!(
+        cron.workspace_unlock("ws-lock", "cron")
+            .expect("unlock should run")
+    );
+    agent
+        .delete_branch(delete("b"))
+        .expect("released lock must not block");
+}
+
+#[test]
+fn force_release_clears_a_stale_lock() {
+    let dir = temp_storage_dir("locks-force");
+    let mut crashed = SqliteStore::open(&dir).expect("fresh storage should open");
+    crashed
+        .workspace_lock(lock("crashed", 60_000))
+        .expect("lock should be taken");
+    drop(crashed);
+
+    let mut operator = SqliteStore::open(&dir).expect("storage should reopen");
+    let released = operator
+        .workspace_lock_force_release("ws-lock")
+        .expect("force release should run")
+        .expect("a lock should have been released");
+    assert_eq!(released.holder, "crashed");
+    assert!(
+        operator
+            .list_workspace_locks()
+            .expect("locks should list")
+            .is_empty()
+    );
+    assert!(
+        operator
+            .audit_verify("ws-lock")
+            .expect("audit should verify")
+            .is_intact()
+    );
+
+    let err = operator
+        .workspace_lock(lock("operator", 0))
+        .expect_err("non-positive ttl must be rejected");
+    assert!(matches!(err, StoreError::InvalidInput(_)));
+}
+
+#[test]
+fn lock_holders_are_tracked_per_workspace_and_cleared_on_release() {
+    let dir = temp_storage_dir("locks-per-workspace");
+    let mut cron = SqliteStore::open(&dir).expect("fresh storage should open");
+    let mut agent = SqliteStore::open(&dir).expect("second handle should open");
+    for workspace_id in ["ws-lock", "ws-other"] {
+        for branch_id in ["a", "b"] {
+            create_branch(&mut cron, workspace_id, branch_id, None);
+        }
+    }
+
+    cron.workspace_lock(lock("cron-a", 60_000))
+        .expect("first lock should be taken");
+    cron.workspace_lock(WorkspaceLockRequest {
+        workspace_id: "ws-other".to_string(),
+        ..lock("cron-b", 60_000)
+    })
+    .expect("second lock should be taken");
+    cron.delete_branch(delete("a"))
+        .expect("locking another workspace must not drop the first lock");
+
+    assert!(
+        cron.workspace_unlock("ws-lock", "cron-a")
+            .expect("unlock should run")
+    );
+    agent
+        .workspace_lock(lock("cron-a", 60_000))
+        .expect("released lock can be taken by another handle");
+    let err = cron
+        .delete_branch(delete("b"))
+        .expect_err("unlocked handle must not keep acting as the holder");
+    assert!(matches!(err, StoreError::WorkspaceLocked { .. }), "{err:?}");
+
+    cron.workspace_lock_force_release("ws-other")
+        .expect("force release should run");
+    agent
+        .workspace_lock(WorkspaceLockRequest {
+            workspace_id: "ws-other".to_string(),
+            ..lock("cron-b", 60_000)
+        })
+        .expect("force released lock can be taken by another handle");
+    let err = cron
+        .delete_branch(DeleteBranchRequest {
+            workspace_id: "ws-other".to_string(),
+            branch_id: "a".to_string(),
+        })
+        .expect_err("force release must drop this handle's claim too");
+    assert!(matches!(err, StoreError::WorkspaceLocked { .. }), "{err:?}");
+}
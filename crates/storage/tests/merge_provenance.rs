@@ -0,0 +1,131 @@
+mod support;
+
+use bm_storage::{
+    CommitDescendantsRequest, CommitProvenanceRequest, CreateMergeRecordRequest, SqliteStore,
+};
+use support::{append_commit, branch_request, open_store};
+
+fn branch(store: &mut SqliteStore, branch_id: &str, parent: Option<&str>, at: i64) {
+    store
+        .create_branch(branch_request("ws-prov", branch_id, parent, at))
+        .expect("branch should be created");
+}
+
+fn commit(store: &mut SqliteStore, branch_id: &str, commit_id: &str, at: i64) {
+    append_commit(store, "ws-prov", branch_id, commit_id, at);
+}
+
+fn merge(store: &mut SqliteStore, merge_id: &str, source: &str, target: &str, at: i64) {
+    store
+        .create_merge_record(CreateMergeRecordRequest {
+            workspace_id: "ws-prov".to_string(),
+            merge_id: merge_id.to_string(),
+            source_branch_id: source.to_string(),
+            target_branch_id: target.to_string(),
+            strategy: "squash".to_string(),
+            summary: format!("{merge_id} summary"),
+            synthesis_commit_id: format!("c-{merge_id}"),
+            synthesis_message: format!("{merge_id} synthesis"),
+            synthesis_body: format!("{merge_id} synthesis body"),
+            author: None,
+            created_at_ms: at,
+        })
+        .expect("merge should be recorded");
+}
+
+#[test]
+fn provenance_follows_merge_chain_back_to_origin_and_descendants_invert_it() {
+    let (_dir, mut store) = open_store("provenance-chain");
+
+    branch(&mut store, "main", None, 1);
+    branch(&mut store, "release", Some("main"), 2);
+    branch(&mut store, "feature", Some("main"), 3);
+    commit(&mut store, "feature", "c-f-1", 4);
+    commit(&mut store, "feature", "c-f-2", 5);
+    merge(&mut store, "m-feature", "feature", "main", 6);
+    commit(&mut store, "main", "c-m-1", 7);
+    merge(&mut store, "m-release", "main", "release", 8);
+
+    let chain = store
+        .commit_provenance(CommitProvenanceRequest {
+            workspace_id: "ws-prov".to_string(),
+            commit_id: "c-m-release".to_string(),
+        })
+        .expect("provenance should resolve");
+    let ids = chain
+        .iter()
+        .map(|step| step.commit.commit_id())
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec!["c-m-release", "c-m-1"]);
+    assert_eq!(
+        chain[0].merge.as_ref().map(|merge| merge.merge_id()),
+        Some("m-release")
+    );
+    assert!(chain[1].merge.is_none(), "origin has no producing merge");
+
+    let chain = store
+        .commit_provenance(CommitProvenanceRequest {
+            workspace_id: "ws-prov".to_string(),
+            commit_id: "c-m-feature".to_string(),
+        })
+        .expect("provenance should resolve");
+    let ids = chain
+        .iter()
+        .map(|step| step.commit.commit_id())
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec!["c-m-feature", "c-f-2"]);
+
+    let descendants = store
+        .commit_descendants(CommitDescendantsRequest {
+            workspace_id: "ws-prov".to_string(),
+            commit_id: "c-f-1".to_string(),
+            limit: 10,
+        })
+        .expect("descendants should resolve");
+    let ids = descendants
+        .iter()
+        .map(|step| step.commit.commit_id())
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec!["c-m-feature", "c-m-release"]);
+
+    let bounded = store
+        .commit_descendants(CommitDescendantsRequest {
+            workspace_id: "ws-prov".to_string(),
+            commit_id: "c-f-1".to_string(),
+            limit: 1,
+        })
+        .expect("bounded descendants should resolve");
+    assert_eq!(bounded.len(), 1);
+
+    let err = store
+        .commit_provenance(CommitProvenanceRequest {
+            workspace_id: "ws-prov".to_string(),
+            commit_id: "c-missing".to_string(),
+        })
+        .expect_err("unknown commit must fail");
+    assert_eq!(err.code(), "NOT_FOUND");
+}
+
+#[test]
+fn provenance_stops_at_merge_from_empty_source_branch() {
+    let (_dir, mut store) = open_store("provenance-empty-source");
+
+    branch(&mut store, "main", None, 1);
+    branch(&mut store, "idea", None, 2);
+    merge(&mut store, "m-idea", "idea", "main", 3);
+
+    let chain = store
+        .commit_provenance(CommitProvenanceRequest {
+            workspace_id: "ws-prov".to_string(),
+            commit_id: "c-m-idea".to_string(),
+        })
+        .expect("provenance should resolve");
+    assert_eq!(chain.len(), 1);
+    assert_eq!(
+        chain[0]
+            .merge
+            .as_ref()
+            .map(|merge| merge.source_branch_id()),
+        Some("idea")
+    );
+}
@@ -0,0 +1,78 @@
+mod support;
+
+use bm_storage::{CommitPinRequest, ListPinnedCommitsRequest, StoreError};
+use support::{commit_request, create_branch, open_store};
+
+fn pin(commit_id: &str, pinned: bool) -> CommitPinRequest {
+    CommitPinRequest {
+        workspace_id: "ws-pins".to_string(),
+        commit_id: commit_id.to_string(),
+        pinned,
+        pinned_at_ms: 10,
+    }
+}
+
+#[test]
+fn pinned_commits_are_listed_per_branch_and_unpin_is_idempotent() {
+    let (_dir, mut store) = open_store("pins-per-branch");
+
+    for branch_id in ["main", "side"] {
+        create_branch(&mut store, "ws-pins", branch_id, None);
+    }
+    for (branch_id, commit_id, created_at_ms) in
+        [("main", "c1", 2), ("main", "c2", 3), ("side", "s1", 4)]
+    {
+        store
+            .append_commit(commit_request(
+                "ws-pins",
+                branch_id,
+                commit_id,
+                created_at_ms,
+            ))
+            .expect("commit should append");
+    }
+
+    assert!(store.commit_pin_set(pin("c1", true)).expect("pin c1"));
+    assert!(!store.commit_pin_set(pin("c1", true)).expect("re-pin c1"));
+    assert!(store.commit_pin_set(pin("s1", true)).expect("pin s1"));
+
+    let pinned = store
+        .list_pinned_commits(ListPinnedCommitsRequest {
+            workspace_id: "ws-pins".to_string(),
+            branch_id: "main".to_string(),
+            limit: 10,
+        })
+        .expect("pinned commits should list");
+    let ids = pinned.iter().map(|c| c.commit_id()).collect::<Vec<_>>();
+    assert_eq!(ids, vec!["c1"]);
+
+    assert!(store.commit_pin_set(pin("c1", false)).expect("unpin c1"));
+    assert!(!store.commit_pin_set(pin("c1", false)).expect("re-unpin c1"));
+
+    let err = store
+        .commit_pin_set(pin("missing", true))
+        .expect_err("pinning an unknown commit must fail");
+    assert!(matches!(err, StoreError::UnknownId));
+}
+
+#[test]
+fn pinned_commits_are_limited_to_the_newest() {
+    let (_dir, mut store) = open_store("pins-limit");
+    create_branch(&mut store, "ws-pins", "main", None);
+    for (commit_id, created_at_ms) in [("c1", 2), ("c2", 3), ("c3", 4)] {
+        store
+            .append_commit(commit_request("ws-pins", "main", commit_id, created_at_ms))
+            .expect("commit should append");
+        assert!(store.commit_pin_set(pin(commit_id, true)).expect("pin"));
+    }
+
+    let pinned = store
+        .list_pinned_commits(ListPinnedCommitsRequest {
+            workspace_id: "ws-pins".to_string(),
+            branch_id: "main".to_string(),
+            limit: 2,
+        })
+        .expect("pinned commits should list");
+    let ids = pinned.iter().map(|c| c.commit_id()).collect::<Vec<_>>();
+    assert_eq!(ids, vec!["c3", "c2"]);
+}
@@ -0,0 +1,156 @@
+mod support;
+
+use bm_storage::{
+    AppendCommitRequest, CherryPickRequest, CommitRedactRequest, REDACTED_BODY, REDACTED_MESSAGE,
+    ShowCommitRequest, StoreError, WorkspaceMergeRequest,
+};
+use support::{commit_request, create_branch, open_store};
+
+fn show(commit_id: &str) -> ShowCommitRequest {
+    ShowCommitRequest {
+        workspace_id: "ws-redact".to_string(),
+        commit_id: commit_id.to_string(),
+    }
+}
+
+fn redact(commit_id: &str) -> CommitRedactRequest {
+    CommitRedactRequest {
+        workspace_id: "ws-redact".to_string(),
+        commit_id: commit_id.to_string(),
+        actor: "security".to_string(),
+        reason: "api key pasted".to_string(),
+        redacted_at_ms: 9,
+    }
+}
+
+#[test]
+fn redaction_replaces_message_and_body_of_commit_and_its_picked_copies() {
+    let (_dir, mut store) = open_store("redact-cascade");
+    for (branch_id, parent) in [("main", None), ("idea", Some("main"))] {
+        create_branch(&mut store, "ws-redact", branch_id, parent);
+    }
+    store
+        .append_commit(AppendCommitRequest {
+            message: "token check".to_string(),
+            body: "token=sk-secret".to_string(),
+            ..commit_request("ws-redact", "idea", "leak", 2)
+        })
+        .expect("commit should append");
+    let copy = store
+        .commit_cherry_pick(CherryPickRequest {
+            workspace_id: "ws-redact".to_string(),
+            source_branch_id: "idea".to_string(),
+            target_branch_id: "main".to_string(),
+            commit_ids: vec!["leak".to_string()],
+            author: None,
+            picked_at_ms: 3,
+        })
+        .expect("cherry-pick should succeed")
+        .picked
+        .remove(0);
+
+    let redacted = store
+        .commit_redact(redact("leak"))
+        .expect("redact should run");
+    assert_eq!(
+        redacted,
+        vec![
+            ("ws-redact".to_string(), "leak".to_string()),
+            ("ws-redact".to_string(), copy.commit_id().to_string()),
+        ]
+    );
+    for (_, commit_id) in &redacted {
+        let commit = store
+            .show_commit(show(commit_id))
+            .expect("show should run")
+            .expect("commit must still exist");
+        assert_eq!(commit.body(), REDACTED_BODY);
+        assert_eq!(commit.message(), REDACTED_MESSAGE);
+    }
+    let record = store
+        .commit_redaction(show("leak"))
+        .expect("redaction should load")
+        .expect("redaction must be recorded");
+    assert_eq!(record.actor, "security");
+    assert_eq!(record.reason, "api key pasted");
+    assert_eq!(record.body_sha256.len(), 64);
+    assert_eq!(record.message_sha256.as_deref().map(str::len), Some(64));
+
+    assert!(
+        store
+            .commit_redact(redact("leak"))
+            .expect("repeat redact should run")
+            .is_empty()
+    );
+    assert!(
+        store
+            .audit_verify("ws-redact")
+            .expect("audit should verify")
+            .is_intact()
+    );
+}
+
+#[test]
+fn redaction_reaches_copies_made_by_workspace_merge() {
+    let (_dir, mut store) = open_store("redact-workspace-merge");
+    create_branch(&mut store, "ws-redact", "main", None);
+    store
+        .append_commit(AppendCommitRequest {
+            message: "token sk-secret".to_string(),
+            body: "token=sk-secret".to_string(),
+            ..commit_request("ws-redact", "main", "leak", 2)
+        })
+        .expect("commit should append");
+    store
+        .workspace_merge(WorkspaceMergeRequest {
+            source_workspace_id: "ws-redact".to_string(),
+            target_workspace_id: "ws-team".to_string(),
+            prefix: "imported".to_string(),
+            merged_at_ms: 3,
+        })
+        .expect("workspace merge should succeed");
+
+    let redacted = store
+        .commit_redact(redact("leak"))
+        .expect("redact should run");
+    assert_eq!(
+        redacted,
+        vec![
+            ("ws-redact".to_string(), "leak".to_string()),
+            ("ws-team".to_string(), "imported-leak".to_string()),
+        ]
+    );
+    let copy = store
+        .show_commit(ShowCommitRequest {
+            workspace_id: "ws-team".to_string(),
+            commit_id: "imported-leak".to_string(),
+        })
+        .expect("show should run")
+        .expect("copy must still exist");
+    assert_eq!(copy.message(), REDACTED_MESSAGE);
+    assert_eq!(copy.body(), REDACTED_BODY);
+    for workspace_id in ["ws-redact", "ws-team"] {
+        assert!(
+            store
+                .audit_verify(workspace_id)
+                .expect("audit should verify")
+                .is_intact()
+        );
+    }
+}
+
+#[test]
+fn redaction_requires_a_reason_and_a_known_commit() {
+    let (_dir, mut store) = open_store("redact-reject");
+
+    let mut no_reason = redact("leak");
+    no_reason.reason = "  ".to_string();
+    assert!(matches!(
+        store.commit_redact(no_reason),
+        Err(StoreError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        store.commit_redact(redact("missing")),
+        Err(StoreError::UnknownId)
+    ));
+}
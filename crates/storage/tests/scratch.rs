@@ -0,0 +1,111 @@
+mod support;
+
+use bm_storage::{
+    AppendCommitRequest, CreateScratchBranchRequest, ListBranchesRequest,
+    PruneScratchBranchesRequest, StoreError,
+};
+use support::{branch_request, commit_request, create_branch, open_store};
+
+fn scratch(branch_id: &str, parent: &str, ttl_ms: i64) -> CreateScratchBranchRequest {
+    CreateScratchBranchRequest {
+        workspace_id: "ws-scratch".to_string(),
+        branch_id: branch_id.to_string(),
+        parent_branch_id: Some(parent.to_string()),
+        ttl_ms,
+        created_at_ms: 10,
+    }
+}
+
+fn prune(now_ms: i64) -> PruneScratchBranchesRequest {
+    PruneScratchBranchesRequest {
+        workspace_id: "ws-scratch".to_string(),
+        now_ms,
+    }
+}
+
+#[test]
+fn expired_scratch_branches_are_pruned_unless_something_depends_on_them() {
+    let (_dir, mut store) = open_store("scratch-prune");
+
+    create_branch(&mut store, "ws-scratch", "main", None);
+    store
+        .create_scratch_branch(scratch("tmp-a", "main", 100))
+        .expect("scratch branch should be created");
+    store
+        .create_scratch_branch(scratch("tmp-b", "main", 100))
+        .expect("scratch branch should be created");
+    store
+        .create_scratch_branch(scratch("tmp-late", "main", 10_000))
+        .expect("scratch branch should be created");
+    store
+        .create_branch(branch_request("ws-scratch", "keeper", Some("tmp-b"), 20))
+        .expect("child of a scratch branch should be created");
+    store
+        .append_commit(AppendCommitRequest {
+            message: "what if".to_string(),
+            body: "throwaway".to_string(),
+            ..commit_request("ws-scratch", "tmp-a", "a1", 30)
+        })
+        .expect("commit on scratch branch should append");
+
+    let scratch_ids = store
+        .list_scratch_branches("ws-scratch")
+        .expect("scratch branches should list")
+        .into_iter()
+        .map(|s| (s.branch_id, s.expires_at_ms))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        scratch_ids,
+        vec![
+            ("tmp-a".to_string(), 110),
+            ("tmp-b".to_string(), 110),
+            ("tmp-late".to_string(), 10_010)
+        ]
+    );
+
+    let early = store.prune_scratch_branches(prune(50)).expect("prune runs");
+    assert!(early.deleted.is_empty() && early.kept.is_empty());
+
+    let report = store
+        .prune_scratch_branches(prune(200))
+        .expect("prune runs");
+    assert_eq!(report.deleted, vec!["tmp-a".to_string()]);
+    assert_eq!(
+        report.kept,
+        vec![("tmp-b".to_string(), "has child branches")]
+    );
+
+    let remaining = store
+        .list_branches(ListBranchesRequest {
+            workspace_id: "ws-scratch".to_string(),
+            limit: 50,
+            offset: 0,
+        })
+        .expect("branches should list")
+        .iter()
+        .map(|b| b.branch_id().to_string())
+        .collect::<Vec<_>>();
+    assert!(!remaining.contains(&"tmp-a".to_string()));
+    assert!(remaining.contains(&"tmp-b".to_string()));
+    assert!(remaining.contains(&"tmp-late".to_string()));
+
+    // The name is free again and a plain branch reusing it is not scratch.
+    store
+        .create_branch(branch_request("ws-scratch", "tmp-a", None, 300))
+        .expect("pruned name should be reusable");
+    let report = store
+        .prune_scratch_branches(prune(400))
+        .expect("prune runs");
+    assert!(report.deleted.is_empty());
+}
+
+#[test]
+fn scratch_branch_rejects_non_positive_ttl() {
+    let (_dir, mut store) = open_store("scratch-ttl");
+    create_branch(&mut store, "ws-scratch", "main", None);
+
+    let err = store
+        .create_scratch_branch(scratch("tmp", "main", 0))
+        .expect_err("zero ttl must be rejected");
+    assert!(matches!(err, StoreError::InvalidInput(_)));
+}
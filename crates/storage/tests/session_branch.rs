@@ -0,0 +1,48 @@
+mod support;
+
+use bm_core::ids::WorkspaceId;
+use bm_storage::AutoCreateBranchRequest;
+use support::{branch_request, create_branch, open_store};
+
+#[test]
+fn branch_auto_create_numbers_sessions_and_defaults_parent_to_checkout() {
+    let (_dir, mut store) = open_store("session-numbering");
+    let workspace = WorkspaceId::try_new("ws-auto").expect("workspace id");
+
+    create_branch(&mut store, "ws-auto", "main", None);
+    store
+        .create_branch(branch_request("ws-auto", "task-7/s4", Some("main"), 2))
+        .expect("pre-existing session branch should be created");
+    store
+        .branch_checkout_set(&workspace, "main")
+        .expect("checkout should be set");
+
+    let created = store
+        .branch_auto_create(AutoCreateBranchRequest {
+            workspace_id: "ws-auto".to_string(),
+            scope: "TASK-7".to_string(),
+            parent_branch_id: None,
+            created_at_ms: 3,
+        })
+        .expect("session branch should be created");
+    assert_eq!(created.branch.branch_id(), "task-7/s5");
+    assert_eq!(created.branch.parent_branch_id(), Some("main"));
+    assert_eq!(created.previous_checkout.as_deref(), Some("main"));
+    assert_eq!(
+        store
+            .branch_checkout_get(&workspace)
+            .expect("checkout should read"),
+        Some("task-7/s5".to_string())
+    );
+
+    let nested = store
+        .branch_auto_create(AutoCreateBranchRequest {
+            workspace_id: "ws-auto".to_string(),
+            scope: "task-7".to_string(),
+            parent_branch_id: None,
+            created_at_ms: 4,
+        })
+        .expect("second session branch should be created");
+    assert_eq!(nested.branch.branch_id(), "task-7/s6");
+    assert_eq!(nested.branch.parent_branch_id(), Some("task-7/s5"));
+}
@@ -0,0 +1,86 @@
+#![forbid(unsafe_code)]
+#![allow(dead_code)]
+
+use bm_storage::{AppendCommitRequest, CreateBranchRequest, SqliteStore};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub(crate) fn temp_storage_dir(label: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("clock should be monotonic enough for tests")
+        .as_nanos();
+    path.push(format!("bm-storage-{label}-{}-{nanos}", std::process::id()));
+    std::fs::create_dir_all(&path).expect("temp storage dir must be creatable");
+    path
+}
+
+/// Opens a store in a fresh temp dir; the dir is returned for tests that reopen it.
+pub(crate) fn open_store(label: &str) -> (PathBuf, SqliteStore) {
+    let dir = temp_storage_dir(label);
+    let store = SqliteStore::open(&dir).expect("fresh storage should open");
+    (dir, store)
+}
+
+pub(crate) fn branch_request(
+    workspace_id: &str,
+    branch_id: &str,
+    parent_branch_id: Option<&str>,
+    created_at_ms: i64,
+) -> CreateBranchRequest {
+    CreateBranchRequest {
+        workspace_id: workspace_id.to_string(),
+        branch_id: branch_id.to_string(),
+        parent_branch_id: parent_branch_id.map(ToOwned::to_owned),
+        created_at_ms,
+    }
+}
+
+/// Commit request with `"<id> message"` / `"<id> body"`; override fields with `..` syntax.
+pub(crate) fn commit_request(
+    workspace_id: &str,
+    branch_id: &str,
+    commit_id: &str,
+    created_at_ms: i64,
+) -> AppendCommitRequest {
+    AppendCommitRequest {
+        workspace_id: workspace_id.to_string(),
+        branch_id: branch_id.to_string(),
+        commit_id: commit_id.to_string(),
+        parent_commit_id: None,
+        expected_head_commit_id: None,
+        message: format!("{commit_id} message"),
+        body: format!("{commit_id} body"),
+        author: None,
+        created_at_ms,
+    }
+}
+
+pub(crate) fn create_branch(
+    store: &mut SqliteStore,
+    workspace_id: &str,
+    branch_id: &str,
+    parent_branch_id: Option<&str>,
+) {
+    store
+        .create_branch(branch_request(workspace_id, branch_id, parent_branch_id, 1))
+        .expect("branch should be created");
+}
+
+pub(crate) fn append_commit(
+    store: &mut SqliteStore,
+    workspace_id: &str,
+    branch_id: &str,
+    commit_id: &str,
+    created_at_ms: i64,
+) {
+    store
+        .append_commit(commit_request(
+            workspace_id,
+            branch_id,
+            commit_id,
+            created_at_ms,
+        ))
+        .expect("commit should append");
+}
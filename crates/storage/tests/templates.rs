@@ -0,0 +1,92 @@
+mod support;
+
+use bm_storage::{
+    AppendCommitRequest, AppendTemplatedCommitRequest, SaveTemplateRequest, ShowCommitRequest,
+    StoreError,
+};
+use std::collections::BTreeMap;
+use support::{commit_request, create_branch, open_store};
+
+fn templated(commit_id: &str, vars: &[(&str, &str)]) -> AppendTemplatedCommitRequest {
+    AppendTemplatedCommitRequest {
+        commit: AppendCommitRequest {
+            message: "retro".to_string(),
+            body: String::new(),
+            ..commit_request("ws-tpl", "main", commit_id, 5)
+        },
+        template: "retro".to_string(),
+        vars: vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect::<BTreeMap<_, _>>(),
+    }
+}
+
+#[test]
+fn templated_commit_expands_variables_and_records_template() {
+    let (_dir, mut store) = open_store("templates-expand");
+    create_branch(&mut store, "ws-tpl", "main", None);
+
+    let placeholders = store
+        .template_save(SaveTemplateRequest {
+            workspace_id: "ws-tpl".to_string(),
+            name: "retro".to_string(),
+            body: "## Went well\n{{good}}\n## Next\n{{next}} (owner: {{good}})".to_string(),
+            saved_at_ms: 2,
+        })
+        .expect("template should save");
+    assert_eq!(placeholders, vec!["good".to_string(), "next".to_string()]);
+
+    let commit = store
+        .append_templated_commit(templated("r1", &[("good", "cache"), ("next", "ship")]))
+        .expect("templated commit should append");
+    assert_eq!(
+        commit.body(),
+        "## Went well\ncache\n## Next\nship (owner: cache)"
+    );
+    let template = store
+        .commit_template(ShowCommitRequest {
+            workspace_id: "ws-tpl".to_string(),
+            commit_id: "r1".to_string(),
+        })
+        .expect("template lookup should succeed");
+    assert_eq!(template.as_deref(), Some("retro"));
+
+    let missing = store
+        .append_templated_commit(templated("r2", &[("good", "cache")]))
+        .expect_err("missing variable must be rejected");
+    assert!(matches!(missing, StoreError::InvalidInput(_)));
+    let unused = store
+        .append_templated_commit(templated(
+            "r3",
+            &[("good", "a"), ("next", "b"), ("extra", "c")],
+        ))
+        .expect_err("unused variable must be rejected");
+    assert!(matches!(unused, StoreError::InvalidInput(_)));
+    assert!(
+        store
+            .show_commit(ShowCommitRequest {
+                workspace_id: "ws-tpl".to_string(),
+                commit_id: "r2".to_string(),
+            })
+            .expect("show should succeed")
+            .is_none(),
+        "rejected expansion must not append"
+    );
+}
+
+#[test]
+fn template_save_rejects_malformed_placeholders() {
+    let (_dir, mut store) = open_store("templates-malformed");
+    for body in ["open {{name", "bad {{Name}}", "empty {{ }}"] {
+        let err = store
+            .template_save(SaveTemplateRequest {
+                workspace_id: "ws-tpl".to_string(),
+                name: "t".to_string(),
+                body: body.to_string(),
+                saved_at_ms: 1,
+            })
+            .expect_err("malformed template must be rejected");
+        assert!(matches!(err, StoreError::InvalidInput(_)), "{body}");
+    }
+}
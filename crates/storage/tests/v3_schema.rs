@@ -1,28 +1,15 @@
+mod support;
+
 use bm_storage::{
     AppendCommitRequest, CreateBranchRequest, CreateMergeRecordRequest, DeleteBranchRequest,
     ListBranchesRequest, ListMergeRecordsRequest, ShowCommitRequest, SqliteStore, StoreError,
 };
 use rusqlite::Connection;
-use std::path::PathBuf;
-use std::time::{SystemTime, UNIX_EPOCH};
-
-fn temp_storage_dir(label: &str) -> PathBuf {
-    let mut path = std::env::temp_dir();
-    let nanos = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .expect("clock should be monotonic enough for tests")
-        .as_nanos();
-    path.push(format!(
-        "bm-storage-v3-{label}-{}-{nanos}",
-        std::process::id()
-    ));
-    std::fs::create_dir_all(&path).expect("temp storage dir must be creatable");
-    path
-}
+use support::temp_storage_dir;
 
 #[test]
 fn storage_open_is_fail_closed_on_unsupported_schema() {
-    let dir = temp_storage_dir("unsupported-reset-required");
+    let dir = temp_storage_dir("v3-unsupported-reset-required");
     let db_path = dir.join("branchmind_rust.db");
 
     let conn = Connection::open(db_path).expect("seed db must open");
@@ -40,7 +27,7 @@ fn storage_open_is_fail_closed_on_unsupported_schema() {
 
 #[test]
 fn v3_branch_commit_merge_api_and_atomic_merge_write() {
-    let dir = temp_storage_dir("merge-atomicity");
+    let dir = temp_storage_dir("v3-merge-atomicity");
     let mut store = SqliteStore::open(&dir).expect("fresh storage should open");
 
     store
@@ -67,8 +54,10 @@ fn v3_branch_commit_merge_api_and_atomic_merge_write() {
             branch_id: "feature".to_string(),
             commit_id: "c-f-1".to_string(),
             parent_commit_id: None,
+            expected_head_commit_id: None,
             message: "feature init".to_string(),
             body: "feature work".to_string(),
+            author: None,
             created_at_ms: 12,
         })
         .expect("feature commit should be appended");
@@ -79,8 +68,10 @@ fn v3_branch_commit_merge_api_and_atomic_merge_write() {
             branch_id: "feature".to_string(),
             commit_id: "c-f-2".to_string(),
             parent_commit_id: None,
+            expected_head_commit_id: None,
             message: "feature follow-up".to_string(),
             body: "feature work 2".to_string(),
+            author: None,
             created_at_ms: 12,
         })
         .expect("feature second commit should be appended");
@@ -91,8 +82,10 @@ fn v3_branch_commit_merge_api_and_atomic_merge_write() {
             branch_id: "main".to_string(),
             commit_id: "c-m-1".to_string(),
             parent_commit_id: None,
+            expected_head_commit_id: None,
             message: "main init".to_string(),
             body: "main work".to_string(),
+            author: None,
             created_at_ms: 13,
         })
         .expect("main commit should be appended");
@@ -130,6 +123,7 @@ fn v3_branch_commit_merge_api_and_atomic_merge_write() {
             synthesis_commit_id: "c-m-merge-1".to_string(),
             synthesis_message: "merge feature".to_string(),
             synthesis_body: "synthesis content".to_string(),
+            author: None,
             created_at_ms: 14,
         })
         .expect("merge record should be created");
@@ -155,6 +149,7 @@ fn v3_branch_commit_merge_api_and_atomic_merge_write() {
             synthesis_commit_id: "c-m-merge-2".to_string(),
             synthesis_message: "merge feature second".to_string(),
             synthesis_body: "should rollback".to_string(),
+            author: None,
             created_at_ms: 15,
         })
         .expect_err("duplicate merge id should fail and rollback");
@@ -230,7 +225,7 @@ fn v3_branch_commit_merge_api_and_atomic_merge_write() {
 
 #[test]
 fn delete_branch_fails_when_descendants_exist() {
-    let dir = temp_storage_dir("delete-branch-descendants");
+    let dir = temp_storage_dir("v3-delete-branch-descendants");
     let mut store = SqliteStore::open(&dir).expect("fresh storage should open");
 
     store
@@ -273,7 +268,7 @@ fn delete_branch_fails_when_descendants_exist() {
 
 #[test]
 fn branch_inserts_also_set_non_null_updated_at_ms() {
-    let dir = temp_storage_dir("branch-updated-at");
+    let dir = temp_storage_dir("v3-branch-updated-at");
     let mut store = SqliteStore::open(&dir).expect("fresh storage should open");
 
     store
@@ -311,7 +306,7 @@ fn branch_inserts_also_set_non_null_updated_at_ms() {
 
 #[test]
 fn branch_updated_at_is_monotonic_for_stale_commit_and_merge_timestamps() {
-    let dir = temp_storage_dir("branch-updated-at-monotonic");
+    let dir = temp_storage_dir("v3-branch-updated-at-monotonic");
     let mut store = SqliteStore::open(&dir).expect("fresh storage should open");
 
     store
@@ -338,8 +333,10 @@ fn branch_updated_at_is_monotonic_for_stale_commit_and_merge_timestamps() {
             branch_id: "main".to_string(),
             commit_id: "c-main-1".to_string(),
             parent_commit_id: None,
+            expected_head_commit_id: None,
             message: "first main".to_string(),
             body: "first main body".to_string(),
+            author: None,
             created_at_ms: 200,
         })
         .expect("first main commit should be appended");
@@ -350,8 +347,10 @@ fn branch_updated_at_is_monotonic_for_stale_commit_and_merge_timestamps() {
             branch_id: "main".to_string(),
             commit_id: "c-main-stale".to_string(),
             parent_commit_id: None,
+            expected_head_commit_id: None,
             message: "stale main".to_string(),
             body: "stale body".to_string(),
+            author: None,
             created_at_ms: 150,
         })
         .expect("stale main commit should be accepted with clamped updated_at_ms");
@@ -362,8 +361,10 @@ fn branch_updated_at_is_monotonic_for_stale_commit_and_merge_timestamps() {
             branch_id: "feature".to_string(),
             commit_id: "c-feature-1".to_string(),
             parent_commit_id: None,
+            expected_head_commit_id: None,
             message: "feature init".to_string(),
             body: "feature body".to_string(),
+            author: None,
             created_at_ms: 220,
         })
         .expect("feature commit should be appended");
@@ -379,6 +380,7 @@ fn branch_updated_at_is_monotonic_for_stale_commit_and_merge_timestamps() {
             synthesis_commit_id: "c-main-merge-stale".to_string(),
             synthesis_message: "merge stale".to_string(),
             synthesis_body: "merge stale body".to_string(),
+            author: None,
             created_at_ms: 180,
         })
         .expect("merge should succeed with clamped updated_at_ms");
@@ -399,3 +401,78 @@ fn branch_updated_at_is_monotonic_for_stale_commit_and_merge_timestamps() {
     assert_eq!(main_branch.updated_at_ms(), 200);
     assert_eq!(main_branch.head_commit_id(), Some("c-main-merge-stale"));
 }
+
+#[test]
+fn storage_open_creates_missing_additive_tables() {
+    let dir = temp_storage_dir("v3-additive-tables");
+    let store = SqliteStore::open(&dir).expect("fresh storage should open");
+    drop(store);
+
+    let conn = Connection::open(dir.join("branchmind_rust.db")).expect("db must open");
+    conn.execute("DROP TABLE merge_sources", [])
+        .expect("additive table should be droppable");
+    drop(conn);
+
+    SqliteStore::open(&dir).expect("store without additive tables must open without reset");
+
+    let conn = Connection::open(dir.join("branchmind_rust.db")).expect("db must open");
+    let restored = conn
+        .query_row(
+            "SELECT COUNT(1) FROM sqlite_master WHERE type='table' AND name='merge_sources'",
+            [],
+            |row| row.get::<_, i64>(0),
+        )
+        .expect("sqlite_master should be readable");
+    assert_eq!(restored, 1);
+}
+
+#[test]
+fn append_commit_with_expected_head_is_compare_and_set() {
+    let dir = temp_storage_dir("v3-append-expected-head");
+    let mut store = SqliteStore::open(&dir).expect("fresh storage should open");
+
+    store
+        .create_branch(CreateBranchRequest {
+            workspace_id: "ws-cas".to_string(),
+            branch_id: "main".to_string(),
+            parent_branch_id: None,
+            created_at_ms: 1,
+        })
+        .expect("main branch should be created");
+
+    let commit = |commit_id: &str, expected: Option<&str>| AppendCommitRequest {
+        workspace_id: "ws-cas".to_string(),
+        branch_id: "main".to_string(),
+        commit_id: commit_id.to_string(),
+        parent_commit_id: None,
+        expected_head_commit_id: expected.map(ToOwned::to_owned),
+        message: format!("{commit_id} message"),
+        body: format!("{commit_id} body"),
+        author: None,
+        created_at_ms: 2,
+    };
+
+    store
+        .append_commit(commit("c1", None))
+        .expect("unguarded commit should append");
+    store
+        .append_commit(commit("c2", Some("c1")))
+        .expect("guard on current head should append");
+
+    let err = store
+        .append_commit(commit("c3", Some("c1")))
+        .expect_err("stale guard must be rejected");
+    assert_eq!(err.code(), "HEAD_MISMATCH");
+    assert!(matches!(
+        err,
+        StoreError::HeadMismatch { current_head: Some(ref head) } if head == "c2"
+    ));
+
+    let rejected = store
+        .show_commit(ShowCommitRequest {
+            workspace_id: "ws-cas".to_string(),
+            commit_id: "c3".to_string(),
+        })
+        .expect("show commit should succeed");
+    assert!(rejected.is_none(), "rejected write must not persist");
+}
@@ -0,0 +1,148 @@
+mod support;
+
+use bm_storage::{
+    AppendCommitRequest, CommitRedactRequest, CreateMergeRecordRequest, ListBranchesRequest,
+    SqliteStore, StoreError, WorkspaceDeleteRequest, WorkspaceMergeRequest,
+};
+use support::{commit_request, create_branch, open_store};
+
+fn seed(store: &mut SqliteStore, workspace_id: &str) {
+    for (branch_id, parent) in [("main", None), ("idea", Some("main"))] {
+        create_branch(store, workspace_id, branch_id, parent);
+    }
+    for (branch_id, commit_id) in [("main", "c1"), ("main", "c2"), ("idea", "i1")] {
+        store
+            .append_commit(AppendCommitRequest {
+                author: Some("alice".to_string()),
+                ..commit_request(workspace_id, branch_id, commit_id, 2)
+            })
+            .expect("commit should append");
+    }
+    store
+        .create_merge_record(CreateMergeRecordRequest {
+            workspace_id: workspace_id.to_string(),
+            merge_id: "m1".to_string(),
+            source_branch_id: "idea".to_string(),
+            target_branch_id: "main".to_string(),
+            strategy: "squash".to_string(),
+            summary: "merge idea".to_string(),
+            synthesis_commit_id: "c3".to_string(),
+            synthesis_message: "merge idea".to_string(),
+            synthesis_body: "merge idea".to_string(),
+            author: None,
+            created_at_ms: 3,
+        })
+        .expect("merge should be recorded");
+}
+
+fn branch_count(store: &SqliteStore, workspace_id: &str) -> usize {
+    store
+        .list_branches(ListBranchesRequest {
+            workspace_id: workspace_id.to_string(),
+            limit: 50,
+            offset: 0,
+        })
+        .expect("branches should list")
+        .len()
+}
+
+#[test]
+fn workspace_delete_requires_token_and_leaves_other_workspaces_intact() {
+    let (dir, mut store) = open_store("workspace-delete-delete");
+    seed(&mut store, "ws-gone");
+    seed(&mut store, "ws-kept");
+
+    let wrong = store
+        .workspace_delete(WorkspaceDeleteRequest {
+            workspace_id: "ws-gone".to_string(),
+            confirm_token: store
+                .workspace_delete_token("ws-kept")
+                .expect("token should derive"),
+            export_to: None,
+        })
+        .expect_err("token of another workspace must be rejected");
+    assert!(matches!(wrong, StoreError::InvalidInput(_)));
+    assert_eq!(branch_count(&store, "ws-gone"), 2);
+
+    let export_path = dir.join("exports").join("before-delete.db");
+    let report = store
+        .workspace_delete(WorkspaceDeleteRequest {
+            workspace_id: "ws-gone".to_string(),
+            confirm_token: store
+                .workspace_delete_token("ws-gone")
+                .expect("token should derive"),
+            export_to: Some(export_path.clone()),
+        })
+        .expect("confirmed delete should succeed");
+    assert!(report.rows_deleted > 0);
+    let export = report.export.expect("export should be reported");
+    assert!(export.workspaces.contains(&"ws-gone".to_string()));
+    assert!(export_path.exists());
+
+    assert_eq!(branch_count(&store, "ws-gone"), 0);
+    assert_eq!(branch_count(&store, "ws-kept"), 2);
+    assert!(
+        store
+            .audit_verify("ws-kept")
+            .expect("verify should run")
+            .is_intact()
+    );
+    assert_eq!(
+        store
+            .audit_verify("ws-gone")
+            .expect("verify should run")
+            .records,
+        0
+    );
+
+    // The id is free to be used again.
+    seed(&mut store, "ws-gone");
+    assert_eq!(branch_count(&store, "ws-gone"), 2);
+
+    let unknown = store
+        .workspace_delete(WorkspaceDeleteRequest {
+            workspace_id: "ws-never".to_string(),
+            confirm_token: store
+                .workspace_delete_token("ws-never")
+                .expect("token should derive"),
+            export_to: None,
+        })
+        .expect_err("unknown workspace must be rejected");
+    assert!(matches!(unknown, StoreError::UnknownId));
+}
+
+#[test]
+fn workspace_delete_clears_copy_provenance_in_other_workspaces() {
+    let (_dir, mut store) = open_store("workspace-delete-copies");
+    seed(&mut store, "ws-gone");
+    store
+        .workspace_merge(WorkspaceMergeRequest {
+            source_workspace_id: "ws-gone".to_string(),
+            target_workspace_id: "ws-team".to_string(),
+            prefix: "imported".to_string(),
+            merged_at_ms: 4,
+        })
+        .expect("workspace merge should succeed");
+    store
+        .workspace_delete(WorkspaceDeleteRequest {
+            workspace_id: "ws-gone".to_string(),
+            confirm_token: store
+                .workspace_delete_token("ws-gone")
+                .expect("token should derive"),
+            export_to: None,
+        })
+        .expect("confirmed delete should succeed");
+
+    // A recreated workspace reusing the commit ids must not reach the old copies.
+    seed(&mut store, "ws-gone");
+    let redacted = store
+        .commit_redact(CommitRedactRequest {
+            workspace_id: "ws-gone".to_string(),
+            commit_id: "c1".to_string(),
+            actor: "security".to_string(),
+            reason: "api key pasted".to_string(),
+            redacted_at_ms: 5,
+        })
+        .expect("redact should run");
+    assert_eq!(redacted, vec![("ws-gone".to_string(), "c1".to_string())]);
+}
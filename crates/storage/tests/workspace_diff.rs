@@ -0,0 +1,99 @@
+mod support;
+
+use bm_storage::{
+    ArchiveBranchRequest, CommitPinRequest, CreateMergeRecordRequest, SqliteStore, StoreError,
+    WorkspaceDiffRequest,
+};
+use support::{branch_request, commit_request, open_store};
+
+fn branch(store: &mut SqliteStore, branch_id: &str, at: i64) {
+    store
+        .create_branch(branch_request("ws-diff", branch_id, None, at))
+        .expect("branch should be created");
+}
+
+fn commit(store: &mut SqliteStore, branch_id: &str, commit_id: &str, at: i64) {
+    store
+        .append_commit(commit_request("ws-diff", branch_id, commit_id, at))
+        .expect("commit should append");
+}
+
+fn window(since_ms: i64, until_ms: i64) -> WorkspaceDiffRequest {
+    WorkspaceDiffRequest {
+        workspace_id: "ws-diff".to_string(),
+        since_ms,
+        until_ms,
+    }
+}
+
+#[test]
+fn workspace_diff_reports_only_changes_inside_the_window() {
+    let (_dir, mut store) = open_store("workspace-diff-window");
+    branch(&mut store, "main", 10);
+    commit(&mut store, "main", "old", 20);
+    branch(&mut store, "idea", 100);
+    commit(&mut store, "idea", "i1", 110);
+    commit(&mut store, "idea", "i2", 120);
+    commit(&mut store, "main", "m1", 130);
+    store
+        .create_merge_record(CreateMergeRecordRequest {
+            workspace_id: "ws-diff".to_string(),
+            merge_id: "merge-1".to_string(),
+            source_branch_id: "idea".to_string(),
+            target_branch_id: "main".to_string(),
+            strategy: "squash".to_string(),
+            summary: "adopt idea".to_string(),
+            synthesis_commit_id: "m2".to_string(),
+            synthesis_message: "adopt idea".to_string(),
+            synthesis_body: "adopt idea".to_string(),
+            author: None,
+            created_at_ms: 140,
+        })
+        .expect("merge should succeed");
+    store
+        .commit_pin_set(CommitPinRequest {
+            workspace_id: "ws-diff".to_string(),
+            commit_id: "i2".to_string(),
+            pinned: true,
+            pinned_at_ms: 150,
+        })
+        .expect("pin should succeed");
+    store
+        .branch_archive(ArchiveBranchRequest {
+            workspace_id: "ws-diff".to_string(),
+            branch_id: "idea".to_string(),
+            at_ms: 160,
+        })
+        .expect("archive should succeed");
+    commit(&mut store, "main", "late", 200);
+
+    let diff = store
+        .workspace_diff(window(100, 200))
+        .expect("diff should run");
+    assert_eq!(diff.branches_created, vec!["idea".to_string()]);
+    assert_eq!(diff.branches_archived, vec!["idea".to_string()]);
+    assert_eq!(diff.pinned_commits, vec!["i2".to_string()]);
+    let commits = diff
+        .commits
+        .iter()
+        .map(|a| (a.branch_id.as_str(), a.commits, a.latest_commit_id.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(commits, vec![("idea", 2, "i2"), ("main", 2, "m2")]);
+    assert_eq!(diff.merges.len(), 1);
+
+    let markdown = diff.to_markdown();
+    assert!(markdown.contains("### Merges"), "{markdown}");
+    assert!(markdown.contains("- `idea` → `main` (squash): adopt idea"));
+    assert!(markdown.contains("- `main`: 2 (latest `m2`)"));
+
+    let empty = store
+        .workspace_diff(window(300, 400))
+        .expect("diff should run");
+    assert!(empty.is_empty());
+    assert!(empty.to_markdown().contains("No changes."));
+
+    assert!(matches!(
+        store.workspace_diff(window(5, 1)),
+        Err(StoreError::InvalidInput(_))
+    ));
+}
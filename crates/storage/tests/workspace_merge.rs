@@ -0,0 +1,196 @@
+mod support;
+
+use bm_storage::{
+    AppendCommitRequest, CreateMergeRecordRequest, ListBranchesRequest, ListMergeRecordsRequest,
+    SaveTemplateRequest, ShowCommitRequest, SqliteStore, StoreError, WorkspaceMergeRequest,
+};
+use rusqlite::{Connection, params};
+use support::{commit_request, create_branch, open_store};
+
+fn commit(store: &mut SqliteStore, branch: &str, commit_id: &str, created_at_ms: i64) {
+    store
+        .append_commit(AppendCommitRequest {
+            author: Some("alice".to_string()),
+            ..commit_request("ws-alice", branch, commit_id, created_at_ms)
+        })
+        .expect("commit should append");
+}
+
+#[test]
+fn workspace_merge_copies_history_under_prefix_and_is_atomic_on_collision() {
+    let (_dir, mut store) = open_store("wsmerge-prefix");
+
+    create_branch(&mut store, "ws-alice", "main", None);
+    create_branch(&mut store, "ws-alice", "idea", Some("main"));
+    commit(&mut store, "main", "c1", 2);
+    commit(&mut store, "main", "c2", 3);
+    commit(&mut store, "idea", "i1", 4);
+    store
+        .create_merge_record(CreateMergeRecordRequest {
+            workspace_id: "ws-alice".to_string(),
+            merge_id: "m1".to_string(),
+            source_branch_id: "idea".to_string(),
+            target_branch_id: "main".to_string(),
+            strategy: "squash".to_string(),
+            summary: "merge idea".to_string(),
+            synthesis_commit_id: "c3".to_string(),
+            synthesis_message: "merge idea".to_string(),
+            synthesis_body: "merge idea".to_string(),
+            author: None,
+            created_at_ms: 5,
+        })
+        .expect("merge should be recorded");
+    create_branch(&mut store, "ws-team", "main", None);
+
+    let merge = |prefix: &str| WorkspaceMergeRequest {
+        source_workspace_id: "ws-alice".to_string(),
+        target_workspace_id: "ws-team".to_string(),
+        prefix: prefix.to_string(),
+        merged_at_ms: 10,
+    };
+    let report = store
+        .workspace_merge(merge("alice"))
+        .expect("workspace merge should succeed");
+    let mut mapped = report.branches.clone();
+    mapped.sort();
+    assert_eq!(
+        mapped,
+        vec![
+            ("idea".to_string(), "alice/idea".to_string()),
+            ("main".to_string(), "alice/main".to_string()),
+        ]
+    );
+    assert_eq!(report.commits, 4);
+    assert_eq!(report.merges, 1);
+
+    let branches = store
+        .list_branches(ListBranchesRequest {
+            workspace_id: "ws-team".to_string(),
+            limit: 10,
+            offset: 0,
+        })
+        .expect("target branches should list");
+    let idea = branches
+        .iter()
+        .find(|b| b.branch_id() == "alice/idea")
+        .expect("renamed branch should exist");
+    assert_eq!(idea.parent_branch_id(), Some("alice/main"));
+    let main = branches
+        .iter()
+        .find(|b| b.branch_id() == "alice/main")
+        .expect("renamed main should exist");
+    assert_eq!(main.head_commit_id(), Some("alice-c3"));
+
+    let c2 = store
+        .show_commit(ShowCommitRequest {
+            workspace_id: "ws-team".to_string(),
+            commit_id: "alice-c2".to_string(),
+        })
+        .expect("show commit")
+        .expect("copied commit should exist");
+    assert_eq!(c2.parent_commit_id(), Some("alice-c1"));
+    assert_eq!(
+        store
+            .commit_author(ShowCommitRequest {
+                workspace_id: "ws-team".to_string(),
+                commit_id: "alice-c2".to_string(),
+            })
+            .expect("author should read")
+            .as_deref(),
+        Some("alice")
+    );
+
+    let merges = store
+        .list_merge_records(ListMergeRecordsRequest {
+            workspace_id: "ws-team".to_string(),
+            limit: 10,
+            offset: 0,
+        })
+        .expect("merges should list");
+    assert_eq!(merges.len(), 1);
+    assert_eq!(merges[0].synthesis_commit_id(), "alice-c3");
+
+    let err = store
+        .workspace_merge(merge("alice"))
+        .expect_err("repeated prefix must collide");
+    assert!(matches!(err, StoreError::BranchAlreadyExists));
+    let after = store
+        .list_branches(ListBranchesRequest {
+            workspace_id: "ws-team".to_string(),
+            limit: 10,
+            offset: 0,
+        })
+        .expect("target branches should list");
+    assert_eq!(after.len(), branches.len());
+}
+
+fn save_template(store: &mut SqliteStore, workspace_id: &str, body: &str) {
+    store
+        .template_save(SaveTemplateRequest {
+            workspace_id: workspace_id.to_string(),
+            name: "review".to_string(),
+            body: body.to_string(),
+            saved_at_ms: 1,
+        })
+        .expect("template should save");
+}
+
+fn merge_into_team(store: &mut SqliteStore) -> Result<(), StoreError> {
+    store
+        .workspace_merge(WorkspaceMergeRequest {
+            source_workspace_id: "ws-alice".to_string(),
+            target_workspace_id: "ws-team".to_string(),
+            prefix: "alice".to_string(),
+            merged_at_ms: 10,
+        })
+        .map(|_| ())
+}
+
+#[test]
+fn workspace_merge_rejects_a_same_named_template_with_another_body() {
+    let (_dir, mut store) = open_store("wsmerge-template-conflict");
+    create_branch(&mut store, "ws-alice", "main", None);
+    commit(&mut store, "main", "c1", 2);
+    save_template(&mut store, "ws-alice", "Goal: {{goal}}");
+    save_template(&mut store, "ws-team", "Risk: {{risk}}");
+
+    let err = merge_into_team(&mut store).expect_err("differing template must conflict");
+    assert!(matches!(err, StoreError::BranchAlreadyExists));
+    let branches = store
+        .list_branches(ListBranchesRequest {
+            workspace_id: "ws-team".to_string(),
+            limit: 10,
+            offset: 0,
+        })
+        .expect("target branches should list");
+    assert!(branches.is_empty(), "conflict must abort the whole copy");
+
+    save_template(&mut store, "ws-team", "Goal: {{goal}}");
+    merge_into_team(&mut store).expect("identical template is shared");
+}
+
+#[test]
+fn workspace_merge_copies_are_covered_by_the_target_audit_chain() {
+    let (dir, mut store) = open_store("wsmerge-audit");
+    create_branch(&mut store, "ws-alice", "main", None);
+    commit(&mut store, "main", "c1", 2);
+    commit(&mut store, "main", "c2", 3);
+    merge_into_team(&mut store).expect("workspace merge should succeed");
+    assert!(
+        store
+            .audit_verify("ws-team")
+            .expect("audit should verify")
+            .is_intact()
+    );
+    drop(store);
+
+    let conn = Connection::open(dir.join("branchmind_rust.db")).expect("db must open");
+    conn.execute(
+        "UPDATE commits SET body=?1 WHERE workspace='ws-team' AND commit_id='alice-c2'",
+        params!["rewritten reasoning"],
+    )
+    .expect("raw body edit should apply");
+    let store = SqliteStore::open(&dir).expect("storage should reopen");
+    let report = store.audit_verify("ws-team").expect("audit should verify");
+    assert!(report.broken_at_seq.is_some(), "{report:?}");
+}